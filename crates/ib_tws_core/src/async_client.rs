@@ -1,69 +1,85 @@
 use std::{
+    collections::BTreeMap,
     io,
     sync::{
+        self,
         atomic::{AtomicI32, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
-use async_broadcast::SendError;
-use futures::{channel::mpsc, lock::Mutex, Future, Sink, SinkExt, Stream, StreamExt, TryStreamExt};
+use futures::{
+    channel::mpsc,
+    future::{self, BoxFuture, Either},
+    Future, Sink, Stream, StreamExt,
+};
 
 use crate::{
-    domain::{market_data::MarketDataType, misc::ServerLogLevel, ContractDetails},
+    connection::{Connect, Connection, ConnectionEvent, ConnectionState},
+    domain::{
+        market_data::MarketDataType, misc::ServerLogLevel, Contract, ContractDetails, Order,
+        WshEvent, WshEventData,
+    },
     message::{
-        constants::{MAX_VERSION, MIN_VERSION},
+        commander::{self, Commander},
+        dispatcher::Dispatcher,
         request::{
-            Handshake, ReqAccountSummary, ReqContractDetails, ReqMarketDataType, ReqMktData,
-            ReqMktDepth, ReqMktDepthExchanges, ReqTickByTickData, SetServerLogLevel, StartApi, ReqHistoricalData,
+            CancelAccountSummary, CancelHeadTimestamp, CancelHistogramData, CancelHistoricalData,
+            CancelMktData, CancelMktDepth, CancelPnl, CancelPnlSingle, CancelPositionsMulti,
+            CancelRealtimeBars, CancelScannerSubscription, CancelTickByTickData,
+            CancelWshEventData, CancelWshMetaData, PlaceOrder, ReqAccountSummary,
+            ReqAccountUpdates, ReqAllOpenOrders, ReqCompletedOrders, ReqContractDetails,
+            ReqHeadTimestamp, ReqHistogramData, ReqHistoricalData, ReqIds, ReqMarketDataType,
+            ReqMktData, ReqMktDepth, ReqMktDepthExchanges, ReqOpenOrders, ReqPnl, ReqPnlSingle,
+            ReqPositionsMulti, ReqRealtimeBars, ReqScannerSubscription, ReqTickByTickData,
+            ReqWshEventData, ReqWshMetaData, SetServerLogLevel,
+        },
+        response::{
+            AccountSummaryMsg, CompletedOrderMsg, ErrMsgMsg, HeadTimestampMsg, HistogramDataMsg,
+            HistoricalDataMsg, MktDepthExchangesMsg, OpenOrderMsg, PnlMsg, PnlSingleMsg,
+            PositionMultiMsg, RealTimeBarsMsg, ScannerDataMsg,
         },
-        response::{AccountSummaryMsg, HandshakeAck, MktDepthExchangesMsg, HistoricalDataMsg},
         Request, Response,
     },
-    Error,
+    order_handle::OrderHandle,
+    order_ids::{OrderIdAllocator, FIRST_REQUEST_ID},
+    pacing::Pacer,
+    routing::api_error,
+    subscription::Subscription,
+    ConnectOptions, Error,
 };
 
+/// How long the one-shot requests wait for their reply, unless set otherwise with
+/// [`AsyncClient::set_default_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub struct AsyncClient {
-    request_tx: mpsc::UnboundedSender<Request>,
-    response_rx: async_broadcast::Receiver<Response>,
+    commander: Commander<i32, Request, Response, Error>,
+    /// Responses which aren't routed to a request, see [`AsyncClient::response_stream`].
+    response_rx: async_broadcast::InactiveReceiver<Response>,
+    event_rx: async_broadcast::InactiveReceiver<ConnectionEvent>,
 
     request_id: AtomicI32,
-    managed_accounts: Arc<Mutex<Vec<String>>>,
-    next_valid_order_id: AtomicI32,
-    server_version: AtomicI32,
-}
-
-async fn request_forwarder<S: Sink<Request>>(
-    mut request_rx: mpsc::UnboundedReceiver<Request>,
-    transport_tx: S,
-) -> Result<(), S::Error>
-where
-    S::Error: Send,
-{
-    let mut transport_tx = Box::pin(transport_tx);
-    while let Some(message) = request_rx.next().await {
-        transport_tx.send(message).await?;
-    }
-    Ok(())
+    managed_accounts: Arc<sync::Mutex<Vec<String>>>,
+    order_ids: Arc<OrderIdAllocator>,
+    server_version: Arc<AtomicI32>,
+    live_requests: Arc<sync::Mutex<BTreeMap<i32, Request>>>,
+    connection_state: Arc<sync::Mutex<ConnectionState>>,
+    pacer: Arc<sync::Mutex<Pacer>>,
+    default_timeout: Option<Duration>,
+    sleep: fn(Duration) -> BoxFuture<'static, ()>,
 }
 
-async fn response_forwarder<S: Stream<Item = Result<Response, io::Error>>, T>(
-    response_tx: async_broadcast::Sender<Response>,
-    transport_rx: S,
-) -> Result<(), Error>
-where
-    T: Send,
-    SendError<T>: From<SendError<Request>>,
-{
-    let mut transport_rx = Box::pin(transport_rx);
-    while let Some(message) = transport_rx.try_next().await.map_err(Error::TransportIo)? {
-        response_tx
-            .broadcast(message)
-            .await
-            .map_err(|_| Error::ResponseChannelClosed)?;
+impl From<commander::Error> for Error {
+    fn from(error: commander::Error) -> Self {
+        match error {
+            commander::Error::FailedToReceiveOneshotResponse => Error::ResponseChannelClosed,
+            commander::Error::FailedToSendNewRequest
+            | commander::Error::FailedToSubscribeNewRequest => Error::RequestChannelClosed,
+        }
     }
-    Ok(())
 }
 
 impl AsyncClient {
@@ -72,270 +88,898 @@ impl AsyncClient {
     /// Returns an error if either the handshake or initial start API request fails.
     pub async fn setup<T>(transport: T, client_id: i32) -> Result<Self, Error>
     where
-        T: Sink<Request> + Stream<Item = Result<Response, io::Error>> + SpawnTask + Send + 'static,
-        <T as Sink<Request>>::Error: std::marker::Send,
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send
+            + 'static,
     {
-        info!("setting up client");
+        Self::setup_with_options(transport, ConnectOptions::new(client_id)).await
+    }
 
-        let (transport_tx, transport_rx) = transport.split();
-        let (request_tx, request_rx) = mpsc::unbounded();
-        let (response_tx, response_rx) = async_broadcast::broadcast(1000);
+    /// Setup a new client with a specified transport and the options of the handshake.
+    /// # Errors
+    /// Returns `Error::Redirected` if the Gateway asks to connect to another address, or an
+    /// error if either the handshake or initial start API request fails.
+    pub async fn setup_with_options<T>(transport: T, options: ConnectOptions) -> Result<Self, Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send
+            + 'static,
+    {
+        Self::start(transport, None, options).await
+    }
 
-        let _request_forwarder = T::spawn_task("request_forwarder", async move {
-            request_forwarder(request_rx, transport_tx).await
-        });
-        let _response_forwarder = T::spawn_task("response_forwarder", async move {
-            response_forwarder(response_tx, transport_rx).await
-        });
+    /// Setup a client which opens a new transport with `connect` whenever the connection is
+    /// lost, e.g. during the nightly restart of TWS. Once connected again, the handshake is
+    /// performed and the live subscriptions are sent again with their original parameters,
+    /// their streams carry on and a [`ConnectionEvent::Reconnected`] is emitted.
+    /// # Errors
+    /// Returns an error if the first connection, the handshake or initial start API request
+    /// fails.
+    pub async fn setup_reconnecting<T, C, F>(connect: C, client_id: i32) -> Result<Self, Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send
+            + 'static,
+        C: FnMut() -> F + Send + 'static,
+        F: Future<Output = Result<T, io::Error>> + Send + 'static,
+    {
+        Self::setup_reconnecting_with_options(connect, ConnectOptions::new(client_id)).await
+    }
 
-        let client = Self {
-            request_tx,
-            response_rx,
-            request_id: AtomicI32::new(0),
+    /// Setup a reconnecting client with the options of the handshake, see
+    /// [`setup_reconnecting`](AsyncClient::setup_reconnecting).
+    /// # Errors
+    /// Returns `Error::Redirected` if the Gateway asks to connect to another address, or an
+    /// error if the first connection, the handshake or initial start API request fails.
+    pub async fn setup_reconnecting_with_options<T, C, F>(
+        mut connect: C,
+        options: ConnectOptions,
+    ) -> Result<Self, Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send
+            + 'static,
+        C: FnMut() -> F + Send + 'static,
+        F: Future<Output = Result<T, io::Error>> + Send + 'static,
+    {
+        let transport = connect().await?;
+        let connect: Connect<T> = Box::new(move || Box::pin(connect()));
+        Self::start(transport, Some(connect), options).await
+    }
+
+    async fn start<T>(
+        transport: T,
+        connect: Option<Connect<T>>,
+        options: ConnectOptions,
+    ) -> Result<Self, Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send
+            + 'static,
+    {
+        info!("setting up client");
+
+        let (command_tx, command_rx) = mpsc::unbounded();
+        let (mut response_tx, response_rx) = async_broadcast::broadcast(1000);
+        response_tx.set_overflow(true);
+        let (mut event_tx, event_rx) = async_broadcast::broadcast(100);
+        event_tx.set_overflow(true);
+
+        let connection = Connection {
+            options,
+            dispatcher: Arc::new(sync::Mutex::new(Dispatcher::new())),
+            order_ids: Arc::default(),
             managed_accounts: Arc::default(),
-            next_valid_order_id: AtomicI32::new(0),
-            server_version: AtomicI32::new(0),
+            server_version: Arc::default(),
+            live_requests: Arc::default(),
+            response_tx,
+            event_tx,
+            state: Arc::default(),
+            pacer: Arc::default(),
+        };
+        let client = Self {
+            commander: Commander(command_tx),
+            response_rx: response_rx.deactivate(),
+            event_rx: event_rx.deactivate(),
+            request_id: AtomicI32::new(FIRST_REQUEST_ID),
+            managed_accounts: connection.managed_accounts.clone(),
+            order_ids: connection.order_ids.clone(),
+            server_version: connection.server_version.clone(),
+            live_requests: connection.live_requests.clone(),
+            connection_state: connection.state.clone(),
+            pacer: connection.pacer.clone(),
+            default_timeout: Some(DEFAULT_TIMEOUT),
+            sleep: |duration| Box::pin(T::sleep(duration)),
         };
-        let _handshake_ack = client.handshake().await?;
 
-        client.start_api(client_id).await?;
+        let mut transport = Box::pin(transport);
+        let handshake_ack = connection.establish(&mut transport).await?;
+
+        let responses = client.response_stream();
+        let _connection = T::spawn_task("connection", async move {
+            let result = connection
+                .run(transport, handshake_ack, connect, command_rx)
+                .await;
+            if let Err(ref error) = result {
+                error!(%error, "connection failed");
+            }
+            result
+        });
+
+        client.wait_until_started(responses).await?;
 
         Ok(client)
     }
 
+    /// Wait for the managed accounts and next valid id, which TWS sends after `StartApi`.
+    #[instrument(skip_all)]
+    async fn wait_until_started(
+        &self,
+        responses: async_broadcast::Receiver<Response>,
+    ) -> Result<(), Error> {
+        let (managed_accts_msg, next_valid_id_msg) = {
+            let mut managed_accts_stream =
+                Box::pin(responses.clone().filter_map(|response| async move {
+                    match response {
+                        Response::ManagedAcctsMsg(msg) => Some(msg),
+                        _ => None,
+                    }
+                }));
+            let mut next_valid_id_stream = Box::pin(responses.filter_map(|response| async move {
+                match response {
+                    Response::NextValidIdMsg(msg) => Some(msg),
+                    _ => None,
+                }
+            }));
+            futures::join!(managed_accts_stream.next(), next_valid_id_stream.next())
+        };
+
+        managed_accts_msg.ok_or(Error::ResponseChannelClosed)?;
+        next_valid_id_msg.ok_or(Error::ResponseChannelClosed)?;
+        debug!(
+            server_version = self.server_version(),
+            next_valid_order_id = self.next_valid_order_id(),
+            "client started"
+        );
+        Ok(())
+    }
+
+    /// Send a request without waiting for its replies, which end up in the
+    /// [`response_stream`](AsyncClient::response_stream).
     /// # Errors
-    /// Returns an error if the request channel is closed.
-    #[allow(clippy::missing_panics_doc)]
+    /// Returns an error if the request channel is closed, or `Error::Encode` if the server
+    /// version doesn't support the request.
+    #[allow(clippy::unused_async)]
     pub async fn send(&self, mut request: Request) -> Result<i32, Error> {
-        let request_id = self
-            .request_id
-            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |request_id| {
-                Some(request_id + 1)
-            })
-            .unwrap();
+        let request_id = self.next_request_id();
         request.set_request_id(request_id);
         info!(?request, "sending message");
 
-        self.request_tx
-            .clone()
-            .send(request)
-            .await
-            .map_err(|_error| Error::RequestChannelClosed)?;
+        // Nothing would see the error once the request is queued.
+        Connection::encode(&request, self.server_version())?;
+        self.commander.notify(request)?;
 
         Ok(request_id)
     }
 
-    /// Get a cloned receiver for the response channel.
-    pub fn response_stream(&self) -> async_broadcast::Receiver<Response> {
-        self.response_rx.clone()
+    /// Send a request as is, without assigning it a request id.
+    pub(crate) fn notify(&self, request: Request) -> Result<(), Error> {
+        info!(?request, "sending message");
+        Connection::encode(&request, self.server_version())?;
+        self.commander.notify(request)?;
+        Ok(())
     }
 
-    fn response_stream_by_id(&self, id: Option<i32>) -> impl Stream<Item = Response> + '_ {
-        self.response_stream().filter(move |response| {
-            let response_request_id = response.request_id();
-            async move { response_request_id == id }
-        })
+    /// The timeout of the one-shot requests which aren't given one, `None` waits forever.
+    #[must_use]
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
     }
 
-    #[instrument(skip(self))]
-    async fn start_api(&self, client_id: i32) -> Result<(), Error> {
-        debug!("requesting start api");
-        self.send(Request::StartApi(StartApi {
-            client_id,
-            optional_capabilities: String::new(),
-        }))
-        .await?;
+    /// Set the timeout of the one-shot requests which aren't given one, `None` waits forever.
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
 
-        let (managed_accts_msg, next_valid_id_msg) = {
-            let mut managed_accts_stream =
-                Box::pin(self.response_stream().filter_map(|response| async move {
-                    match response {
-                        Response::ManagedAcctsMsg(msg) => Some(msg),
-                        _ => None,
-                    }
-                }));
-            let mut next_valid_id_stream =
-                Box::pin(self.response_stream().filter_map(|response| async move {
-                    match response {
-                        Response::NextValidIdMsg(msg) => Some(msg),
-                        _ => None,
-                    }
-                }));
-            futures::join!(managed_accts_stream.next(), next_valid_id_stream.next())
+    /// Wait for `future` until the timeout elapses.
+    async fn with_timeout<R>(
+        &self,
+        timeout: Option<Duration>,
+        future: impl Future<Output = Result<R, Error>>,
+    ) -> Result<R, Error> {
+        let Some(timeout) = timeout else {
+            return future.await;
         };
+        match future::select(Box::pin(future), (self.sleep)(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), _)) => {
+                warn!(?timeout, "request timed out");
+                Err(Error::Timeout)
+            }
+        }
+    }
 
-        let (managed_accts_msg, next_valid_id_msg) = (
-            managed_accts_msg.ok_or(Error::ResponseChannelClosed)?,
-            next_valid_id_msg.ok_or(Error::ResponseChannelClosed)?,
-        );
+    /// Send a request and wait for the first reply routed to its request id, giving up after
+    /// `timeout`, which starts once the pacing rules let the request through. The request is
+    /// cancelled with `cancel_request` when it times out.
+    async fn send_oneshot(
+        &self,
+        mut request: Request,
+        timeout: Option<Duration>,
+        cancel_request: fn(i32) -> Request,
+    ) -> Result<Response, Error> {
+        let request_id = self.next_request_id();
+        request.set_request_id(request_id);
+        let timeout = timeout.map(|timeout| timeout + self.pacing_delay(&request));
+        info!(?request, "sending message");
+
+        match self
+            .with_timeout(timeout, async {
+                self.commander.send(request_id, request).await?
+            })
+            .await
         {
-            let accounts = managed_accts_msg
-                .accounts
-                .split(',')
-                .map(String::from)
-                .collect();
-            info!(?accounts, "updating managed accounts");
-            *self.managed_accounts.lock().await = accounts;
+            Err(Error::Timeout) => {
+                if let Err(error) = self.notify(cancel_request(request_id)) {
+                    debug!(request_id, %error, "failed to cancel timed out request");
+                }
+                Err(Error::Timeout)
+            }
+            response => response,
         }
+    }
 
-        {
-            let order_id = next_valid_id_msg.order_id;
-            info!(?order_id, "updating next valid id");
-            self.next_valid_order_id.swap(order_id, Ordering::Relaxed);
+    /// Send a request and stream every reply routed to its request id. Live requests are sent
+    /// again after a reconnect, until cancelled with
+    /// [`cancel_subscription`](AsyncClient::cancel_subscription).
+    fn subscribe(
+        &self,
+        mut request: Request,
+        live: bool,
+    ) -> Result<(i32, impl Stream<Item = Result<Response, Error>>), Error> {
+        let request_id = self.next_request_id();
+        request.set_request_id(request_id);
+        info!(?request, "sending message");
+
+        if live {
+            self.live_requests
+                .lock()
+                .expect("live requests lock poisoned")
+                .insert(request_id, request.clone());
         }
+        Ok((request_id, self.commander.subscribe(request_id, request)?))
+    }
 
-        Ok(())
+    /// Forget a live request and send its cancellation.
+    pub(crate) fn cancel_subscription(
+        &self,
+        request_id: i32,
+        cancel_request: Request,
+    ) -> Result<(), Error> {
+        self.forget_subscription(request_id);
+        self.notify(cancel_request)
     }
 
-    #[instrument(skip(self))]
-    async fn handshake(&self) -> Result<HandshakeAck, Error> {
-        debug!("performing handshake");
-        self.send(Request::Handshake(Handshake {
-            min_version: MIN_VERSION,
-            max_version: MAX_VERSION,
-            option: None,
-        }))
-        .await?;
+    /// Forget a live request which TWS ended, so that it isn't sent again after a reconnect.
+    pub(crate) fn forget_subscription(&self, request_id: i32) {
+        self.live_requests
+            .lock()
+            .expect("live requests lock poisoned")
+            .remove(&request_id);
+    }
+
+    /// Subscribe to a request answered with a single kind of message, picked out by `reply`.
+    fn subscribe_to<T: Send + 'static>(
+        &self,
+        request: Request,
+        cancel_request: fn(i32) -> Request,
+        reply: fn(Response) -> Option<T>,
+    ) -> Result<Subscription<'_, T>, Error> {
+        let (request_id, responses) = self.subscribe(request, true)?;
+
+        let replies = responses.filter_map(move |response| {
+            future::ready(match response {
+                Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                Ok(response) => reply(response).map(Ok),
+                Err(error) => Some(Err(error)),
+            })
+        });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            cancel_request(request_id),
+            replies,
+        ))
+    }
+
+    #[allow(clippy::missing_panics_doc)]
+    fn next_request_id(&self) -> i32 {
+        self.request_id
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |request_id| {
+                Some(request_id + 1)
+            })
+            .unwrap()
+    }
+
+    /// Get a new receiver for the responses which aren't routed to a request: replies keyed
+    /// by the request opcode, order and account updates, warnings and errors without a
+    /// request id. Only the responses received after this call are seen, and a receiver
+    /// lagging more than 1000 responses behind loses the oldest ones.
+    pub fn response_stream(&self) -> async_broadcast::Receiver<Response> {
+        self.response_rx.activate_cloned()
+    }
+
+    /// How long `request` would be held back by the pacing rules if it was sent now.
+    ///
+    /// Every message counts towards the limit of 50 per second, and `ReqHistoricalData` and
+    /// `ReqHistoricalTicks` are queued according to the historical data pacing rules, see
+    /// [`Pacer`].
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn pacing_delay(&self, request: &Request) -> Duration {
+        self.pacer
+            .lock()
+            .expect("pacer lock poisoned")
+            .delay(request, Instant::now())
+    }
+
+    /// Get a new receiver for the changes of the connection.
+    pub fn connection_events(&self) -> async_broadcast::Receiver<ConnectionEvent> {
+        self.event_rx.activate_cloned()
+    }
+
+    /// The current state of the connection, as of the last [`ConnectionEvent`].
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state
+            .lock()
+            .expect("connection state lock poisoned")
+            .clone()
+    }
 
-        let mut stream = Box::pin(self.response_rx.clone().filter_map(|response| async move {
+    /// Stream of the warnings reported by TWS, for any request or none at all.
+    pub fn warnings(&self) -> impl Stream<Item = ErrMsgMsg> {
+        self.response_stream().filter_map(|response| async move {
             match response {
-                Response::HandshakeAck(ack) => Some(ack),
+                Response::ErrMsgMsg(err) if err.is_warning() => Some(err),
                 _ => None,
             }
-        }));
-        let handshake_ack = stream.next().await.ok_or(Error::ResponseChannelClosed)?;
-        debug!(?handshake_ack, "received handshake ack");
-        self.server_version
-            .store(handshake_ack.server_version, Ordering::Relaxed);
-        Ok(handshake_ack)
+        })
     }
 
+    #[allow(clippy::unused_async, clippy::missing_panics_doc)]
     pub async fn managed_accounts(&self) -> Vec<String> {
-        self.managed_accounts.lock().await.clone()
+        self.managed_accounts
+            .lock()
+            .expect("managed accounts lock poisoned")
+            .clone()
     }
 
+    /// The order id the next order will be placed with.
     pub fn next_valid_order_id(&self) -> i32 {
-        self.next_valid_order_id.load(Ordering::Relaxed)
+        self.order_ids.peek()
+    }
+
+    /// Take an order id from the `NEXT_VALID_ID` sequence, for orders sent with
+    /// [`send`](AsyncClient::send).
+    pub fn next_order_id(&self) -> i32 {
+        self.order_ids.next()
+    }
+
+    /// Ask TWS for the next valid order id, e.g. after orders were placed by another client
+    /// with the same id, and return it.
+    /// # Errors
+    /// Returns an error if the request or response channel is closed.
+    #[instrument(skip(self))]
+    pub async fn refresh_order_ids(&self) -> Result<i32, Error> {
+        let responses = self.response_stream();
+        self.send(Request::ReqIds(ReqIds { num_ids: 1 })).await?;
+
+        Box::pin(responses.filter_map(|response| async move {
+            match response {
+                Response::NextValidIdMsg(msg) => Some(msg),
+                _ => None,
+            }
+        }))
+        .next()
+        .await
+        .ok_or(Error::ResponseChannelClosed)?;
+
+        Ok(self.order_ids.peek())
+    }
+
+    /// Place an order with an id taken from the `NEXT_VALID_ID` sequence. The returned handle
+    /// streams the updates of the order and allows to modify or cancel it.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn place_order(
+        &self,
+        contract: Contract,
+        order: Order,
+    ) -> Result<OrderHandle<'_>, Error> {
+        let order_id = self.order_ids.next();
+        let request = Request::PlaceOrder(PlaceOrder {
+            id: order_id,
+            contract: contract.clone(),
+            order,
+        });
+        info!(?request, "sending message");
+
+        let responses = self.commander.subscribe(order_id, request)?;
+
+        Ok(OrderHandle::new(self, order_id, contract, responses))
     }
 
     pub fn server_version(&self) -> i32 {
         self.server_version.load(Ordering::Relaxed)
     }
 
+    /// Request the details of a contract, giving up after the default timeout.
+    /// # Errors
+    /// Returns `Error::Timeout` if no details came in time, or an error if the request
+    /// channel is closed or TWS reports one.
     pub async fn request_contract_details(
         &self,
         message: ReqContractDetails,
     ) -> Result<ContractDetails, Error> {
-        let request_id = self.send(Request::ReqContractDetails(message)).await?;
+        self.request_contract_details_with_timeout(message, self.default_timeout)
+            .await
+    }
 
-        Box::pin(
-            self.response_stream_by_id(Some(request_id))
+    /// Request the details of a contract, giving up after `timeout`. There is no cancel
+    /// message for this request, a late reply is dropped.
+    /// # Errors
+    /// Returns `Error::Timeout` if no details came in time, or an error if the request
+    /// channel is closed or TWS reports one.
+    #[instrument(skip(self))]
+    pub async fn request_contract_details_with_timeout(
+        &self,
+        message: ReqContractDetails,
+        timeout: Option<Duration>,
+    ) -> Result<ContractDetails, Error> {
+        let (_, responses) = self.subscribe(Request::ReqContractDetails(message), false)?;
+
+        let mut details = Box::pin(
+            responses
                 .take_while(|response| {
-                    let is_end = matches!(response, Response::ContractDataEndMsg(_));
+                    let is_end = matches!(response, Ok(Response::ContractDataEndMsg(_)));
                     async move { !is_end }
                 })
                 .filter_map(|response| async move {
                     match response {
-                        Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
-                        Response::ContractDataMsg(msg) => Some(Ok(msg.contract_details)),
-                        _ => {
+                        Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                        Ok(Response::ContractDataMsg(msg)) => Some(Ok(msg.contract_details)),
+                        Ok(response) => {
                             warn!(?response, "unexpected response for request id");
                             None
                         }
+                        Err(error) => Some(Err(error)),
                     }
                 }),
-        )
-        .next()
+        );
+        self.with_timeout(timeout, async {
+            details.next().await.ok_or(Error::ResponseChannelClosed)?
+        })
         .await
-        .unwrap()
     }
 
+    /// Request the account summary, the stream ends with the first full summary.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_account_summary(
         &self,
         message: ReqAccountSummary,
-    ) -> Result<impl Stream<Item = Result<AccountSummaryMsg, Error>> + '_, Error> {
-        let request_id = self.send(Request::ReqAccountSummary(message)).await?;
+    ) -> Result<Subscription<'_, AccountSummaryMsg>, Error> {
+        let (request_id, responses) = self.subscribe(Request::ReqAccountSummary(message), true)?;
 
-        Ok(self
-            .response_stream_by_id(Some(request_id))
+        let stream = responses
             .take_while(|response| {
-                let is_end = matches!(response, Response::AccountSummaryEndMsg(_));
+                let is_end = matches!(response, Ok(Response::AccountSummaryEndMsg(_)));
                 async move { !is_end }
             })
             .filter_map(|response| async move {
                 match response {
-                    Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
-                    Response::AccountSummaryMsg(msg) => Some(Ok(msg)),
-                    _ => None,
+                    Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                    Ok(Response::AccountSummaryMsg(msg)) => Some(Ok(msg)),
+                    Ok(_) => None,
+                    Err(error) => Some(Err(error)),
                 }
-            }))
+            });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelAccountSummary(CancelAccountSummary { req_id: request_id }),
+            stream,
+        )
+        .stops_early())
     }
 
+    /// Subscribe to the values and portfolio of an account, a snapshot is sent first, ending
+    /// with `AcctDownloadEndMsg`, then the updates.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    #[allow(clippy::missing_panics_doc)]
+    pub async fn request_account_updates(
+        &self,
+        acct_code: &str,
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let request_id = self.next_request_id();
+        let request = Request::ReqAccountUpdates(ReqAccountUpdates {
+            subscribe: true,
+            acct_code: acct_code.to_string(),
+        });
+        // The updates aren't keyed by any id, they come through the response stream.
+        let responses = self.response_stream();
+        self.live_requests
+            .lock()
+            .expect("live requests lock poisoned")
+            .insert(request_id, request.clone());
+        self.notify(request)?;
+
+        let stream = responses.filter_map(|response| async move {
+            match response {
+                response @ (Response::AcctValueMsg(_)
+                | Response::PortfolioValueMsg(_)
+                | Response::AcctUpdateTimeMsg(_)
+                | Response::AcctDownloadEndMsg(_)) => Some(Ok(response)),
+                _ => None,
+            }
+        });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::ReqAccountUpdates(ReqAccountUpdates {
+                subscribe: false,
+                acct_code: acct_code.to_string(),
+            }),
+            stream,
+        ))
+    }
+
+    /// Request the positions of an account and/or model, the stream ends once all positions
+    /// have been received.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_positions_multi(
+        &self,
+        message: ReqPositionsMulti,
+    ) -> Result<Subscription<'_, PositionMultiMsg>, Error> {
+        let (request_id, responses) = self.subscribe(Request::ReqPositionsMulti(message), true)?;
+
+        let stream = responses
+            .take_while(|response| {
+                let is_end = matches!(response, Ok(Response::PositionMultiEndMsg(_)));
+                async move { !is_end }
+            })
+            .filter_map(|response| async move {
+                match response {
+                    Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                    Ok(Response::PositionMultiMsg(msg)) => Some(Ok(msg)),
+                    Ok(_) => None,
+                    Err(error) => Some(Err(error)),
+                }
+            });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelPositionsMulti(CancelPositionsMulti { req_id: request_id }),
+            stream,
+        )
+        .stops_early())
+    }
+
+    /// Request the exchanges with market depth, giving up after the default timeout.
+    /// # Errors
+    /// Returns `Error::Timeout` if the exchanges didn't come in time, or an error if the
+    /// request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_market_depth_exchanges(&self) -> Result<MktDepthExchangesMsg, Error> {
+        self.request_market_depth_exchanges_with_timeout(self.default_timeout)
+            .await
+    }
+
+    /// Request the exchanges with market depth, giving up after `timeout`.
+    /// # Errors
+    /// Returns `Error::Timeout` if the exchanges didn't come in time, or an error if the
+    /// request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_market_depth_exchanges_with_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<MktDepthExchangesMsg, Error> {
+        let responses = self.response_stream();
         self.send(Request::ReqMktDepthExchanges(ReqMktDepthExchanges {}))
             .await?;
 
-        Box::pin(self.response_stream().filter_map(|response| async move {
+        let mut exchanges = Box::pin(responses.filter_map(|response| async move {
             match response {
-                Response::MktDepthExchangesMsg(msg) => Some(Ok(msg)),
+                Response::MktDepthExchangesMsg(msg) => Some(msg),
                 _ => None,
             }
-        }))
-        .next()
+        }));
+        self.with_timeout(timeout, async {
+            exchanges.next().await.ok_or(Error::ResponseChannelClosed)
+        })
         .await
-        .ok_or(Error::ResponseChannelClosed)?
     }
 
+    /// Request the open orders placed by this client.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come within the default timeout, or an
+    /// error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_open_orders(&self) -> Result<Vec<OpenOrderMsg>, Error> {
+        self.request_open_orders_with_timeout(self.default_timeout)
+            .await
+    }
+
+    /// Request the open orders placed by this client, giving up after `timeout`.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come in time, or an error if the
+    /// request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_open_orders_with_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<OpenOrderMsg>, Error> {
+        self.request_open_order_snapshot(Request::ReqOpenOrders(ReqOpenOrders {}), timeout)
+            .await
+    }
+
+    /// Request the open orders placed by all clients, including TWS itself.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come within the default timeout, or an
+    /// error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_all_open_orders(&self) -> Result<Vec<OpenOrderMsg>, Error> {
+        self.request_all_open_orders_with_timeout(self.default_timeout)
+            .await
+    }
+
+    /// Request the open orders placed by all clients, including TWS itself, giving up after
+    /// `timeout`.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come in time, or an error if the
+    /// request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_all_open_orders_with_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<OpenOrderMsg>, Error> {
+        self.request_open_order_snapshot(Request::ReqAllOpenOrders(ReqAllOpenOrders {}), timeout)
+            .await
+    }
+
+    async fn request_open_order_snapshot(
+        &self,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<OpenOrderMsg>, Error> {
+        let responses = self.response_stream();
+        self.send(request).await?;
+
+        self.with_timeout(
+            timeout,
+            collect_snapshot(
+                responses,
+                |response| matches!(response, Response::OpenOrderEndMsg(_)),
+                |response| match response {
+                    Response::OpenOrderMsg(msg) => Some(msg),
+                    _ => None,
+                },
+            ),
+        )
+        .await
+    }
+
+    /// Request the orders that were filled or cancelled during the session.
+    /// When `api_only` is set, only orders placed through the API are returned.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come within the default timeout, or an
+    /// error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_completed_orders(
+        &self,
+        api_only: bool,
+    ) -> Result<Vec<CompletedOrderMsg>, Error> {
+        self.request_completed_orders_with_timeout(api_only, self.default_timeout)
+            .await
+    }
+
+    /// Request the orders that were filled or cancelled during the session, giving up after
+    /// `timeout`. When `api_only` is set, only orders placed through the API are returned.
+    /// # Errors
+    /// Returns `Error::Timeout` if the orders didn't come in time, or an error if the
+    /// request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_completed_orders_with_timeout(
+        &self,
+        api_only: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<CompletedOrderMsg>, Error> {
+        let responses = self.response_stream();
+        self.send(Request::ReqCompletedOrders(ReqCompletedOrders { api_only }))
+            .await?;
+
+        self.with_timeout(
+            timeout,
+            collect_snapshot(
+                responses,
+                |response| matches!(response, Response::CompletedOrdersEndMsg(_)),
+                |response| match response {
+                    Response::CompletedOrderMsg(msg) => Some(msg),
+                    _ => None,
+                },
+            ),
+        )
+        .await
+    }
+
+    /// Request the Wall Street Horizon metadata, describing the available event types
+    /// and filters.
+    /// # Errors
+    /// Returns `Error::Timeout` if the metadata didn't come within the default timeout, or an
+    /// error if the request fails or the payload is not valid JSON.
+    #[instrument(skip(self))]
+    pub async fn request_wsh_meta_data(&self) -> Result<serde_json::Value, Error> {
+        self.request_wsh_meta_data_with_timeout(self.default_timeout)
+            .await
+    }
+
+    /// Request the Wall Street Horizon metadata, giving up after `timeout`. The request is
+    /// cancelled when it times out.
+    /// # Errors
+    /// Returns `Error::Timeout` if the metadata didn't come in time, or an error if the
+    /// request fails or the payload is not valid JSON.
+    #[instrument(skip(self))]
+    pub async fn request_wsh_meta_data_with_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value, Error> {
+        let response = self
+            .send_oneshot(
+                Request::ReqWshMetaData(ReqWshMetaData::new()),
+                timeout,
+                |req_id| Request::CancelWshMetaData(CancelWshMetaData { req_id }),
+            )
+            .await?;
+
+        match response {
+            Response::WshMetaDataMsg(msg) => Ok(serde_json::from_str(&msg.data_json)?),
+            Response::ErrMsgMsg(err) => Err(Error::ApiError(Box::new(err))),
+            response => Err(Error::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// Request Wall Street Horizon calendar events (earnings, dividends, ...) for a
+    /// contract or filter.
+    /// # Errors
+    /// Returns `Error::Timeout` if the events didn't come within the default timeout, or an
+    /// error if the request fails or the payload can't be parsed into events.
+    #[instrument(skip(self))]
+    pub async fn request_wsh_event_data(
+        &self,
+        wsh_event_data: WshEventData,
+    ) -> Result<Vec<WshEvent>, Error> {
+        self.request_wsh_event_data_with_timeout(wsh_event_data, self.default_timeout)
+            .await
+    }
+
+    /// Request Wall Street Horizon calendar events, giving up after `timeout`. The request is
+    /// cancelled when it times out.
+    /// # Errors
+    /// Returns `Error::Timeout` if the events didn't come in time, or an error if the request
+    /// fails or the payload can't be parsed into events.
+    #[instrument(skip(self))]
+    pub async fn request_wsh_event_data_with_timeout(
+        &self,
+        wsh_event_data: WshEventData,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<WshEvent>, Error> {
+        let response = self
+            .send_oneshot(
+                Request::ReqWshEventData(ReqWshEventData::new(wsh_event_data)),
+                timeout,
+                |req_id| Request::CancelWshEventData(CancelWshEventData { req_id }),
+            )
+            .await?;
+
+        match response {
+            Response::WshEventDataMsg(msg) => Ok(WshEvent::parse_events(&msg.data_json)?),
+            Response::ErrMsgMsg(err) => Err(Error::ApiError(Box::new(err))),
+            response => Err(Error::UnexpectedResponse(Box::new(response))),
+        }
+    }
+
+    /// Subscribe to the ticks of a contract, a snapshot stream ends with `TickSnapshotEndMsg`.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_market_data(
         &self,
         message: ReqMktData,
-    ) -> Result<impl Stream<Item = Result<Response, Error>> + '_, Error> {
-        let request_id = self.send(Request::ReqMktData(message)).await?;
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let (request_id, responses) = self.subscribe(Request::ReqMktData(message), true)?;
 
-        Ok(self
-            .response_stream_by_id(Some(request_id))
+        let stream = responses
             .take_while(|response| {
-                let is_end = matches!(response, Response::TickSnapshotEndMsg(_));
+                let is_end = matches!(response, Ok(Response::TickSnapshotEndMsg(_)));
                 async move { !is_end }
             })
             .filter_map(|response| async move {
                 match response {
-                    Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
-                    response @ (Response::TickSizeMsg(_)
-                    | Response::MarketDataTypeMsg(_)
-                    | Response::TickPriceMsg(_)
-                    | Response::TickStringMsg(_)
-                    | Response::TickEFPMsg(_)
-                    | Response::TickGenericMsg(_)
-                    | Response::TickOptionComputationMsg(_)) => Some(Ok(response)),
-                    _ => None,
+                    Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                    Ok(
+                        response @ (Response::TickSizeMsg(_)
+                        | Response::MarketDataTypeMsg(_)
+                        | Response::TickPriceMsg(_)
+                        | Response::TickStringMsg(_)
+                        | Response::TickEFPMsg(_)
+                        | Response::TickGenericMsg(_)
+                        | Response::TickOptionComputationMsg(_)),
+                    ) => Some(Ok(response)),
+                    Ok(_) => None,
+                    Err(error) => Some(Err(error)),
                 }
-            }))
+            });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelMktData(CancelMktData { req_id: request_id }),
+            stream,
+        ))
     }
 
+    /// Subscribe to the order book of a contract.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_market_depth(
         &self,
         message: ReqMktDepth,
-    ) -> Result<impl Stream<Item = Result<Response, Error>> + '_, Error> {
-        let request_id = self.send(Request::ReqMktDepth(message)).await?;
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let is_smart_depth = message.is_smart_depth;
+        let (request_id, responses) = self.subscribe(Request::ReqMktDepth(message), true)?;
 
-        Ok(self
-            .response_stream_by_id(Some(request_id))
-            .filter_map(|response| async move {
-                match response {
-                    Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
-                    response @ (Response::MarketDepthL2Msg(_) | Response::MarketDepthMsg(_)) => {
-                        Some(Ok(response))
-                    }
-                    _ => None,
+        let stream = responses.filter_map(|response| async move {
+            match response {
+                Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                Ok(response @ (Response::MarketDepthL2Msg(_) | Response::MarketDepthMsg(_))) => {
+                    Some(Ok(response))
                 }
-            }))
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            }
+        });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelMktDepth(CancelMktDepth {
+                req_id: request_id,
+                is_smart_depth,
+            }),
+            stream,
+        ))
     }
 
+    /// Switch the market data to live, frozen or delayed data.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_market_data_type(
         &self,
@@ -348,6 +992,9 @@ impl AsyncClient {
         Ok(())
     }
 
+    /// Set how much TWS writes to its API log.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn set_server_log_level(&self, log_level: ServerLogLevel) -> Result<(), Error> {
         self.send(Request::SetServerLogLevel(SetServerLogLevel { log_level }))
@@ -355,54 +1002,213 @@ impl AsyncClient {
         Ok(())
     }
 
+    /// Subscribe to the tick-by-tick trades, quotes or midpoints of a contract.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
     #[instrument(skip(self))]
     pub async fn request_tick_by_tick_data(
         &self,
         message: ReqTickByTickData,
-    ) -> Result<impl Stream<Item = Result<Response, Error>> + '_, Error> {
-        let request_id = self.send(Request::ReqTickByTickData(message)).await?;
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let (request_id, responses) = self.subscribe(Request::ReqTickByTickData(message), true)?;
 
-        Ok(self
-            .response_stream_by_id(Some(request_id))
-            .filter_map(|response| async move {
-                match response {
-                    Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
+        let stream = responses.filter_map(|response| async move {
+            match response {
+                Ok(Response::ErrMsgMsg(err)) => api_error(err),
+                Ok(
                     response @ (Response::TickByTickNoneMsg(_)
                     | Response::TickByTickBidAskMsg(_)
                     | Response::TickByTickAllLastMsg(_)
-                    | Response::TickByTickMidPointMsg(_)) => Some(Ok(response)),
-                    _ => None,
-                }
-            }))
+                    | Response::TickByTickMidPointMsg(_)),
+                ) => Some(Ok(response)),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            }
+        });
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelTickByTickData(CancelTickByTickData { req_id: request_id }),
+            stream,
+        ))
+    }
+
+    /// Request 5 second bars, built from the trades, quotes or midpoints as they come in.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_realtime_bars(
+        &self,
+        message: ReqRealtimeBars,
+    ) -> Result<Subscription<'_, RealTimeBarsMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqRealtimeBars(message),
+            |req_id| Request::CancelRealtimeBars(CancelRealtimeBars { req_id }),
+            |response| match response {
+                Response::RealTimeBarsMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+    }
+
+    /// Request the distribution of the traded volume over the prices, the stream ends with
+    /// the first histogram.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_histogram_data(
+        &self,
+        message: ReqHistogramData,
+    ) -> Result<Subscription<'_, HistogramDataMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqHistogramData(message),
+            |req_id| Request::CancelHistogramData(CancelHistogramData { req_id }),
+            |response| match response {
+                Response::HistogramDataMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+        .map(|subscription| subscription.ends_after(|_| true))
     }
 
+    /// Request the daily, realized and unrealized `PnL` of an account and/or model.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_pnl(&self, message: ReqPnl) -> Result<Subscription<'_, PnlMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqPnl(message),
+            |req_id| Request::CancelPnl(CancelPnl { req_id }),
+            |response| match response {
+                Response::PnlMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+    }
+
+    /// Request the `PnL` of a single position.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_pnl_single(
+        &self,
+        message: ReqPnlSingle,
+    ) -> Result<Subscription<'_, PnlSingleMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqPnlSingle(message),
+            |req_id| Request::CancelPnlSingle(CancelPnlSingle { req_id }),
+            |response| match response {
+                Response::PnlSingleMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+    }
+
+    /// Run a market scanner, every message holds the full list of results.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_scanner_subscription(
+        &self,
+        message: ReqScannerSubscription,
+    ) -> Result<Subscription<'_, ScannerDataMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqScannerSubscription(message),
+            |req_id| Request::CancelScannerSubscription(CancelScannerSubscription { req_id }),
+            |response| match response {
+                Response::ScannerDataMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+    }
+
+    /// Request the earliest date for which historical data is available.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[instrument(skip(self))]
+    pub async fn request_head_timestamp(
+        &self,
+        message: ReqHeadTimestamp,
+    ) -> Result<Subscription<'_, HeadTimestampMsg>, Error> {
+        self.subscribe_to(
+            Request::ReqHeadTimestamp(message),
+            |req_id| Request::CancelHeadTimestamp(CancelHeadTimestamp { req_id }),
+            |response| match response {
+                Response::HeadTimestampMsg(msg) => Some(msg),
+                _ => None,
+            },
+        )
+        .map(|subscription| subscription.ends_after(|_| true))
+    }
+
+    /// Request historical bars, giving up after the default timeout, see
+    /// [`request_historical_data_with_timeout`](AsyncClient::request_historical_data_with_timeout).
+    /// # Errors
+    /// Returns `Error::Timeout` if the bars didn't come in time, or an error if the request
+    /// channel is closed or TWS reports one.
     #[instrument(skip(self))]
     pub async fn request_historical_data(
         &self,
         message: ReqHistoricalData,
     ) -> Result<HistoricalDataMsg, Error> {
-        let request_id = self.send(Request::ReqHistoricalData(message)).await?;
+        self.request_historical_data_with_timeout(message, self.default_timeout)
+            .await
+    }
 
-        Box::pin(self
-            .response_stream_by_id(Some(request_id))
-            .filter_map(|response| async move {
-                match response {
-                    Response::ErrMsgMsg(err) => Some(Err(Error::ApiError(err))),
-                    Response::HistoricalDataMsg(msg) => Some(Ok(msg)),
-                    _ => None,
-                }
-            }))
-            .try_next()
-            .await?
-            .ok_or(Error::ResponseChannelClosed)
+    /// Request historical bars, giving up after `timeout`, which starts once the pacing rules
+    /// let the request through. The request is cancelled when it times out.
+    /// # Errors
+    /// Returns `Error::Timeout` if the bars didn't come in time, or an error if the request
+    /// channel is closed or TWS reports one.
+    #[instrument(skip(self))]
+    pub async fn request_historical_data_with_timeout(
+        &self,
+        message: ReqHistoricalData,
+        timeout: Option<Duration>,
+    ) -> Result<HistoricalDataMsg, Error> {
+        let response = self
+            .send_oneshot(Request::ReqHistoricalData(message), timeout, |req_id| {
+                Request::CancelHistoricalData(CancelHistoricalData { req_id })
+            })
+            .await?;
+
+        match response {
+            Response::HistoricalDataMsg(msg) => Ok(msg),
+            Response::ErrMsgMsg(err) => Err(Error::ApiError(Box::new(err))),
+            response => Err(Error::UnexpectedResponse(Box::new(response))),
+        }
     }
 }
 
+/// Collect the messages of a snapshot, picked out by `item`, until the end marker.
+/// # Errors
+/// Returns `Error::ResponseChannelClosed` if the responses end before the end marker, e.g.
+/// when the connection is lost, rather than a partial snapshot.
+async fn collect_snapshot<T>(
+    responses: impl Stream<Item = Response>,
+    is_end: fn(&Response) -> bool,
+    item: fn(Response) -> Option<T>,
+) -> Result<Vec<T>, Error> {
+    let mut responses = Box::pin(responses);
+    let mut items = Vec::new();
+    while let Some(response) = responses.next().await {
+        if is_end(&response) {
+            return Ok(items);
+        }
+        items.extend(item(response));
+    }
+    Err(Error::ResponseChannelClosed)
+}
+
+/// Runtime hooks the client needs, implemented by the transports.
 pub trait SpawnTask {
     type JoinHandle<T>;
+    type Sleep: Future<Output = ()> + Send + 'static;
 
     fn spawn_task<F, T>(name: &str, future: F) -> Self::JoinHandle<T>
     where
         F: Future<Output = T> + Send + 'static,
         T: Send + 'static;
+
+    fn sleep(duration: Duration) -> Self::Sleep;
 }
@@ -0,0 +1,742 @@
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use bytes::BytesMut;
+
+use crate::{
+    domain::{Contract, ContractDetails, Order},
+    message::{
+        message_codec::Codec,
+        request::{
+            CacelOrder, CancelHistoricalData, CancelMktData, CancelRealtimeBars,
+            CancelTickByTickData, Handshake, PlaceOrder, ReqContractDetails, ReqHistoricalData,
+            ReqMktData, ReqRealtimeBars, ReqTickByTickData, StartApi,
+        },
+        response::{ErrMsgMsg, HandshakeAck, HistoricalDataMsg, RealTimeBarsMsg},
+        Request, Response,
+    },
+    order_ids::{OrderIdAllocator, FIRST_REQUEST_ID},
+    routing::{api_error, order_route_id, route_id},
+    ConnectOptions, Error,
+};
+
+/// How long the one-shot requests wait for their reply, unless set otherwise with
+/// [`BlockingClient::set_default_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const READ_BUFFER_LEN: usize = 8192;
+
+/// A client which blocks the calling thread instead of needing an async runtime.
+///
+/// A reader thread decodes the responses and routes them by request id over channels, the
+/// streaming requests are read as iterators.
+pub struct BlockingClient {
+    /// The socket and the codec are locked in that order, so that requests are written in
+    /// the order they were encoded. The codec is released before writing, the reader thread
+    /// needs it to decode while a write blocks.
+    writer: Mutex<TcpStream>,
+    codec: Arc<Mutex<Codec>>,
+    routes: Arc<Mutex<Routes>>,
+    reader: Option<JoinHandle<()>>,
+
+    request_id: AtomicI32,
+    order_ids: Arc<OrderIdAllocator>,
+    managed_accounts: Arc<Mutex<Vec<String>>>,
+    server_version: i32,
+    default_timeout: Option<Duration>,
+}
+
+/// Where the reader thread sends the responses.
+#[derive(Default)]
+struct Routes {
+    by_id: HashMap<i32, mpsc::Sender<Response>>,
+    /// Receivers of the responses which aren't routed to a request.
+    listeners: Vec<mpsc::Sender<Response>>,
+    execution_order_ids: HashMap<String, i32>,
+}
+
+impl Routes {
+    fn forward(&mut self, response: Response) {
+        // Order updates are broadcast as well, like with the `AsyncClient`.
+        if let Some(order_id) = order_route_id(&response, &mut self.execution_order_ids) {
+            self.reply_to(order_id, response.clone());
+        }
+
+        let response = match route_id(&response) {
+            Some(request_id) => match self.reply_to(request_id, response) {
+                Some(response) => response,
+                None => return,
+            },
+            None => response,
+        };
+        self.listeners
+            .retain(|listener| listener.send(response.clone()).is_ok());
+    }
+
+    /// Send the response to the request, returns it back if there is no such route.
+    fn reply_to(&mut self, request_id: i32, response: Response) -> Option<Response> {
+        let Some(route) = self.by_id.get(&request_id) else {
+            return Some(response);
+        };
+        if route.send(response).is_err() {
+            self.by_id.remove(&request_id);
+        }
+        None
+    }
+}
+
+impl BlockingClient {
+    /// Connect to TWS and start the API.
+    /// # Errors
+    /// Returns an error if the connection, the handshake or initial start API request fails.
+    pub fn connect(addr: SocketAddr, client_id: i32) -> Result<Self, Error> {
+        Self::connect_with_options(addr, &ConnectOptions::new(client_id))
+    }
+
+    /// Connect to TWS and start the API with the options of the handshake.
+    /// # Errors
+    /// Returns `Error::Redirected` if the Gateway asks to connect to another address, or an
+    /// error if the connection, the handshake or initial start API request fails.
+    pub fn connect_with_options(addr: SocketAddr, options: &ConnectOptions) -> Result<Self, Error> {
+        info!(%addr, "connecting");
+        let mut stream = TcpStream::connect_timeout(&addr, options.connect_timeout)?;
+        stream.set_nodelay(true)?;
+        stream.write_all(b"API\0")?;
+
+        let mut codec = Codec::default();
+        let mut buf = BytesMut::new();
+        stream.write_all(&encode_request(
+            &mut codec,
+            &Request::Handshake(Handshake {
+                min_version: options.min_version,
+                max_version: options.max_version,
+                option: options.connect_options.clone(),
+            }),
+        )?)?;
+        let handshake_ack = loop {
+            match read_response(&mut stream, &mut codec, &mut buf)? {
+                Response::HandshakeAck(ack) => break ack,
+                response => warn!(?response, "unexpected response before handshake ack"),
+            }
+        };
+        debug!(?handshake_ack, "received handshake ack");
+        let HandshakeAck {
+            server_version,
+            redirect,
+            ..
+        } = handshake_ack;
+        if let Some(address) = redirect {
+            return Err(Error::Redirected(address));
+        }
+
+        let codec = Arc::new(Mutex::new(codec));
+        let routes = Arc::new(Mutex::new(Routes::default()));
+        let order_ids = Arc::new(OrderIdAllocator::default());
+        let managed_accounts = Arc::new(Mutex::new(Vec::new()));
+
+        // Listen before starting the API, so that the replies can't be missed.
+        let (started_tx, started_rx) = mpsc::channel();
+        lock(&routes).listeners.push(started_tx);
+
+        let reader = {
+            let stream = stream.try_clone()?;
+            let codec = codec.clone();
+            let routes = routes.clone();
+            let order_ids = order_ids.clone();
+            let managed_accounts = managed_accounts.clone();
+            thread::Builder::new()
+                .name("ib_tws reader".to_string())
+                .spawn(move || {
+                    read_responses(stream, buf, &codec, &routes, &order_ids, &managed_accounts);
+                })?
+        };
+
+        let client = BlockingClient {
+            writer: Mutex::new(stream),
+            codec,
+            routes,
+            reader: Some(reader),
+            request_id: AtomicI32::new(FIRST_REQUEST_ID),
+            order_ids,
+            managed_accounts,
+            server_version,
+            default_timeout: Some(DEFAULT_TIMEOUT),
+        };
+        client.send(&Request::StartApi(StartApi {
+            client_id: options.client_id,
+            optional_capabilities: options.optional_capabilities.clone(),
+        }))?;
+
+        let (mut managed_accounts, mut next_valid_id) = (false, false);
+        while !(managed_accounts && next_valid_id) {
+            match started_rx.recv_timeout(options.connect_timeout) {
+                Ok(Response::ManagedAcctsMsg(_)) => managed_accounts = true,
+                Ok(Response::NextValidIdMsg(_)) => next_valid_id = true,
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => return Err(Error::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(Error::ConnectionClosed),
+            }
+        }
+        debug!(
+            server_version,
+            next_valid_order_id = client.order_ids.peek(),
+            "client started"
+        );
+
+        Ok(client)
+    }
+
+    #[must_use]
+    pub fn server_version(&self) -> i32 {
+        self.server_version
+    }
+
+    /// The timeout of the one-shot requests which aren't given one, `None` waits forever.
+    #[must_use]
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    #[must_use]
+    pub fn managed_accounts(&self) -> Vec<String> {
+        lock(&self.managed_accounts).clone()
+    }
+
+    /// Take the next order id from the `NEXT_VALID_ID` sequence.
+    pub fn next_order_id(&self) -> i32 {
+        self.order_ids.next()
+    }
+
+    fn next_request_id(&self) -> i32 {
+        self.request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Send a request without waiting for any reply.
+    /// # Errors
+    /// Returns an error if the request can't be encoded or written.
+    pub fn send(&self, request: &Request) -> Result<(), Error> {
+        info!(?request, "sending message");
+        let mut writer = lock(&self.writer);
+        let buf = encode_request(&mut lock(&self.codec), request)?;
+        writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Receive the responses which aren't routed to a request, from now on.
+    #[must_use]
+    pub fn responses(&self) -> mpsc::Receiver<Response> {
+        let (tx, rx) = mpsc::channel();
+        lock(&self.routes).listeners.push(tx);
+        rx
+    }
+
+    /// Receive the warnings reported by TWS, for any request or none at all.
+    pub fn warnings(&self) -> impl Iterator<Item = ErrMsgMsg> {
+        self.responses()
+            .into_iter()
+            .filter_map(|response| match response {
+                Response::ErrMsgMsg(err) if err.is_warning() => Some(err),
+                _ => None,
+            })
+    }
+
+    /// Send a request with a new request id and route its replies to the returned receiver.
+    fn subscribe(&self, mut request: Request) -> Result<(i32, mpsc::Receiver<Response>), Error> {
+        let request_id = self.next_request_id();
+        request.set_request_id(request_id);
+        let rx = self.route(request_id);
+        self.send(&request)?;
+        Ok((request_id, rx))
+    }
+
+    fn route(&self, id: i32) -> mpsc::Receiver<Response> {
+        let (tx, rx) = mpsc::channel();
+        lock(&self.routes).by_id.insert(id, tx);
+        rx
+    }
+
+    fn unroute(&self, id: i32) {
+        lock(&self.routes).by_id.remove(&id);
+    }
+
+    /// Wait for a reply until the timeout elapses.
+    fn recv(rx: &mpsc::Receiver<Response>, timeout: Option<Duration>) -> Result<Response, Error> {
+        match timeout {
+            Some(timeout) => rx.recv_timeout(timeout).map_err(|error| match error {
+                RecvTimeoutError::Timeout => {
+                    warn!(?timeout, "request timed out");
+                    Error::Timeout
+                }
+                RecvTimeoutError::Disconnected => Error::ConnectionClosed,
+            }),
+            None => rx.recv().map_err(|_| Error::ConnectionClosed),
+        }
+    }
+
+    /// Request the details of a contract.
+    /// # Errors
+    /// Returns `Error::Timeout` if no details came in time, or an error if the connection is
+    /// closed or TWS reports one.
+    pub fn request_contract_details(
+        &self,
+        message: ReqContractDetails,
+    ) -> Result<ContractDetails, Error> {
+        self.request_contract_details_with_timeout(message, self.default_timeout)
+    }
+
+    /// Request the details of a contract, giving up after `timeout`.
+    /// # Errors
+    /// Returns `Error::Timeout` if no details came in time, or an error if the connection is
+    /// closed or TWS reports one.
+    pub fn request_contract_details_with_timeout(
+        &self,
+        message: ReqContractDetails,
+        timeout: Option<Duration>,
+    ) -> Result<ContractDetails, Error> {
+        let (request_id, rx) = self.subscribe(Request::ReqContractDetails(message))?;
+        let result = loop {
+            match Self::recv(&rx, timeout) {
+                Ok(Response::ContractDataMsg(msg)) => break Ok(msg.contract_details),
+                Ok(Response::ContractDataEndMsg(_)) => break Err(Error::ResponseChannelClosed),
+                Ok(Response::ErrMsgMsg(err)) => {
+                    if let Some(Err(error)) = api_error::<()>(err) {
+                        break Err(error);
+                    }
+                }
+                Ok(response) => warn!(?response, "unexpected response for request id"),
+                Err(error) => break Err(error),
+            }
+        };
+        self.unroute(request_id);
+        result
+    }
+
+    /// Request historical bars.
+    /// # Errors
+    /// Returns `Error::Timeout` if the bars didn't come in time, or an error if the
+    /// connection is closed or TWS reports one.
+    pub fn request_historical_data(
+        &self,
+        message: ReqHistoricalData,
+    ) -> Result<HistoricalDataMsg, Error> {
+        self.request_historical_data_with_timeout(message, self.default_timeout)
+    }
+
+    /// Request historical bars, giving up after `timeout`. The request is cancelled when it
+    /// times out.
+    /// # Errors
+    /// Returns `Error::Timeout` if the bars didn't come in time, or an error if the
+    /// connection is closed or TWS reports one.
+    pub fn request_historical_data_with_timeout(
+        &self,
+        message: ReqHistoricalData,
+        timeout: Option<Duration>,
+    ) -> Result<HistoricalDataMsg, Error> {
+        let (request_id, rx) = self.subscribe(Request::ReqHistoricalData(message))?;
+        let result = loop {
+            match Self::recv(&rx, timeout) {
+                Ok(Response::HistoricalDataMsg(msg)) => break Ok(msg),
+                Ok(Response::ErrMsgMsg(err)) => {
+                    if let Some(Err(error)) = api_error::<()>(err) {
+                        break Err(error);
+                    }
+                }
+                Ok(response) => break Err(Error::UnexpectedResponse(Box::new(response))),
+                Err(Error::Timeout) => {
+                    let cancel =
+                        Request::CancelHistoricalData(CancelHistoricalData { req_id: request_id });
+                    if let Err(error) = self.send(&cancel) {
+                        debug!(request_id, %error, "failed to cancel historical data");
+                    }
+                    break Err(Error::Timeout);
+                }
+                Err(error) => break Err(error),
+            }
+        };
+        self.unroute(request_id);
+        result
+    }
+
+    /// Request market data, the iterator yields the tick messages. A snapshot ends with
+    /// `TickSnapshotEndMsg`.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn request_market_data(
+        &self,
+        message: ReqMktData,
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let (request_id, rx) = self.subscribe(Request::ReqMktData(message))?;
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelMktData(CancelMktData { req_id: request_id }),
+            rx,
+            |response| match response {
+                Response::TickSnapshotEndMsg(_) => ControlFlow::Break(()),
+                Response::ErrMsgMsg(err) => ControlFlow::Continue(api_error(err)),
+                response @ (Response::TickSizeMsg(_)
+                | Response::MarketDataTypeMsg(_)
+                | Response::TickPriceMsg(_)
+                | Response::TickStringMsg(_)
+                | Response::TickEFPMsg(_)
+                | Response::TickGenericMsg(_)
+                | Response::TickOptionComputationMsg(_)) => {
+                    ControlFlow::Continue(Some(Ok(response)))
+                }
+                _ => ControlFlow::Continue(None),
+            },
+        ))
+    }
+
+    /// Request tick-by-tick data.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn request_tick_by_tick_data(
+        &self,
+        message: ReqTickByTickData,
+    ) -> Result<Subscription<'_, Response>, Error> {
+        let (request_id, rx) = self.subscribe(Request::ReqTickByTickData(message))?;
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelTickByTickData(CancelTickByTickData { req_id: request_id }),
+            rx,
+            |response| match response {
+                Response::ErrMsgMsg(err) => ControlFlow::Continue(api_error(err)),
+                response @ (Response::TickByTickNoneMsg(_)
+                | Response::TickByTickBidAskMsg(_)
+                | Response::TickByTickAllLastMsg(_)
+                | Response::TickByTickMidPointMsg(_)) => ControlFlow::Continue(Some(Ok(response))),
+                _ => ControlFlow::Continue(None),
+            },
+        ))
+    }
+
+    /// Request 5 second real time bars.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn request_realtime_bars(
+        &self,
+        message: ReqRealtimeBars,
+    ) -> Result<Subscription<'_, RealTimeBarsMsg>, Error> {
+        let (request_id, rx) = self.subscribe(Request::ReqRealtimeBars(message))?;
+        Ok(Subscription::new(
+            self,
+            request_id,
+            Request::CancelRealtimeBars(CancelRealtimeBars { req_id: request_id }),
+            rx,
+            |response| match response {
+                Response::ErrMsgMsg(err) => ControlFlow::Continue(api_error(err)),
+                Response::RealTimeBarsMsg(msg) => ControlFlow::Continue(Some(Ok(msg))),
+                _ => ControlFlow::Continue(None),
+            },
+        ))
+    }
+
+    /// Place an order with an id taken from the `NEXT_VALID_ID` sequence. The returned handle
+    /// iterates over the updates of the order and allows to modify or cancel it.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn place_order(&self, contract: Contract, order: Order) -> Result<OrderHandle<'_>, Error> {
+        let order_id = self.order_ids.next();
+        let responses = self.route(order_id);
+        self.send(&Request::PlaceOrder(PlaceOrder {
+            id: order_id,
+            contract: contract.clone(),
+            order,
+        }))?;
+
+        Ok(OrderHandle {
+            client: self,
+            order_id,
+            contract,
+            responses,
+        })
+    }
+}
+
+impl Drop for BlockingClient {
+    fn drop(&mut self) {
+        // The reader thread sees the end of the stream and exits.
+        let _ = lock(&self.writer).shutdown(Shutdown::Both);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+impl fmt::Debug for BlockingClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingClient")
+            .field("server_version", &self.server_version)
+            .field("request_id", &self.request_id)
+            .field("order_ids", &self.order_ids)
+            .field("default_timeout", &self.default_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The replies to a streaming request of the [`BlockingClient`], dropping it sends the
+/// matching cancel request.
+pub struct Subscription<'a, T> {
+    client: &'a BlockingClient,
+    request_id: i32,
+    cancel_request: Option<Request>,
+    responses: mpsc::Receiver<Response>,
+    reply: Reply<T>,
+    done: bool,
+}
+
+/// Picks the items out of the replies to a subscription, `Break` ends the iterator.
+type Reply<T> = fn(Response) -> ControlFlow<(), Option<Result<T, Error>>>;
+
+impl<'a, T> Subscription<'a, T> {
+    fn new(
+        client: &'a BlockingClient,
+        request_id: i32,
+        cancel_request: Request,
+        responses: mpsc::Receiver<Response>,
+        reply: Reply<T>,
+    ) -> Self {
+        Subscription {
+            client,
+            request_id,
+            cancel_request: Some(cancel_request),
+            responses,
+            reply,
+            done: false,
+        }
+    }
+
+    #[must_use]
+    pub fn request_id(&self) -> i32 {
+        self.request_id
+    }
+
+    /// Cancel the request.
+    /// # Errors
+    /// Returns an error if the cancel request can't be written.
+    pub fn cancel(mut self) -> Result<(), Error> {
+        match self.cancel_request.take() {
+            Some(request) => {
+                self.client.unroute(self.request_id);
+                self.client.send(&request)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Iterator for Subscription<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let Ok(response) = self.responses.recv() else {
+                self.done = true;
+                break;
+            };
+            match (self.reply)(response) {
+                ControlFlow::Continue(Some(item)) => return Some(item),
+                ControlFlow::Continue(None) => {}
+                ControlFlow::Break(()) => self.done = true,
+            }
+        }
+        None
+    }
+}
+
+impl<T> Drop for Subscription<'_, T> {
+    fn drop(&mut self) {
+        self.client.unroute(self.request_id);
+        if let Some(request) = self.cancel_request.take() {
+            if let Err(error) = self.client.send(&request) {
+                debug!(request_id = self.request_id, %error, "failed to cancel subscription");
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Subscription<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("request_id", &self.request_id)
+            .field("cancel_request", &self.cancel_request)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An order placed with [`BlockingClient::place_order`], iterating over the
+/// `OrderStatusMsg`, `OpenOrderMsg`, `ExecutionDataMsg` and `CommissionReportMsg` responses
+/// and errors for the order id.
+#[derive(Debug)]
+pub struct OrderHandle<'a> {
+    client: &'a BlockingClient,
+    order_id: i32,
+    contract: Contract,
+    responses: mpsc::Receiver<Response>,
+}
+
+impl OrderHandle<'_> {
+    #[must_use]
+    pub fn order_id(&self) -> i32 {
+        self.order_id
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Send the order again with the same id, TWS applies the changes to the live order.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn modify(&self, order: Order) -> Result<(), Error> {
+        self.client.send(&Request::PlaceOrder(PlaceOrder {
+            id: self.order_id,
+            contract: self.contract.clone(),
+            order,
+        }))
+    }
+
+    /// Request the cancellation of the order, the outcome is reported by the iterator.
+    /// # Errors
+    /// Returns an error if the request can't be written.
+    pub fn cancel(&self) -> Result<(), Error> {
+        self.client.send(&Request::CancelOrder(CacelOrder {
+            id: self.order_id,
+            manual_order_cancel_time: String::new(),
+        }))
+    }
+}
+
+impl Iterator for OrderHandle<'_> {
+    type Item = Result<Response, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match self.responses.recv().ok()? {
+                Response::ErrMsgMsg(err) => api_error(err),
+                response @ (Response::OrderStatusMsg(_)
+                | Response::OpenOrderMsg(_)
+                | Response::ExecutionDataMsg(_)
+                | Response::CommissionReportMsg(_)) => Some(Ok(response)),
+                response => {
+                    warn!(?response, "unexpected response for order id");
+                    None
+                }
+            };
+            if item.is_some() {
+                return item;
+            }
+        }
+    }
+}
+
+impl Drop for OrderHandle<'_> {
+    fn drop(&mut self) {
+        self.client.unroute(self.order_id);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn encode_request(codec: &mut Codec, request: &Request) -> Result<BytesMut, Error> {
+    let mut buf = BytesMut::new();
+    codec.encode(request, &mut buf)?;
+    Ok(buf)
+}
+
+/// Read from the socket until a whole response was decoded.
+fn read_response(
+    stream: &mut TcpStream,
+    codec: &mut Codec,
+    buf: &mut BytesMut,
+) -> Result<Response, Error> {
+    let mut chunk = [0; READ_BUFFER_LEN];
+    loop {
+        if let Some(response) = codec.decode(buf)? {
+            return Ok(response);
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) => return Err(Error::ConnectionClosed),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+/// Body of the reader thread, until the socket is closed. Dropping the routes afterwards
+/// ends the iterators and fails the pending requests with `Error::ConnectionClosed`.
+fn read_responses(
+    mut stream: TcpStream,
+    mut buf: BytesMut,
+    codec: &Mutex<Codec>,
+    routes: &Mutex<Routes>,
+    order_ids: &OrderIdAllocator,
+    managed_accounts: &Mutex<Vec<String>>,
+) {
+    let mut chunk = [0; READ_BUFFER_LEN];
+    loop {
+        loop {
+            let decoded = lock(codec).decode(&mut buf);
+            let response = match decoded {
+                Ok(Some(response)) => response,
+                Ok(None) => break,
+                Err(error) => {
+                    error!(%error, "failed to decode response");
+                    return close(routes);
+                }
+            };
+            match response {
+                Response::NextValidIdMsg(ref msg) => order_ids.update(msg.order_id),
+                Response::ManagedAcctsMsg(ref msg) => {
+                    *lock(managed_accounts) = msg.accounts.split(',').map(String::from).collect();
+                }
+                _ => {}
+            }
+            lock(routes).forward(response);
+        }
+
+        match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => {
+                warn!(%error, "failed to read from socket");
+                break;
+            }
+        }
+    }
+    close(routes);
+}
+
+fn close(routes: &Mutex<Routes>) {
+    info!("connection closed");
+    let mut routes = lock(routes);
+    routes.by_id.clear();
+    routes.listeners.clear();
+}
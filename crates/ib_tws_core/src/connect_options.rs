@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use crate::message::constants::{MAX_VERSION, MIN_VERSION};
+
+/// How long the transports wait for the socket to connect by default.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options of the handshake and `StartApi` request sent when connecting to TWS.
+///
+/// ```
+/// # use ib_tws_core::ConnectOptions;
+/// let options = ConnectOptions::new(1).connect_options("+PACEAPI");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub client_id: i32,
+    pub optional_capabilities: String,
+    pub connect_options: Option<String>,
+    pub min_version: i32,
+    pub max_version: i32,
+    pub connect_timeout: Duration,
+}
+
+impl ConnectOptions {
+    #[must_use]
+    pub fn new(client_id: i32) -> Self {
+        ConnectOptions {
+            client_id,
+            optional_capabilities: String::new(),
+            connect_options: None,
+            min_version: MIN_VERSION,
+            max_version: MAX_VERSION,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+
+    /// Capabilities sent with `StartApi`.
+    #[must_use]
+    pub fn optional_capabilities(mut self, optional_capabilities: impl Into<String>) -> Self {
+        self.optional_capabilities = optional_capabilities.into();
+        self
+    }
+
+    /// Options appended to the handshake, e.g. `+PACEAPI` to have TWS pace the requests
+    /// instead of rejecting them.
+    #[must_use]
+    pub fn connect_options(mut self, connect_options: impl Into<String>) -> Self {
+        self.connect_options = Some(connect_options.into());
+        self
+    }
+
+    /// The range of server versions offered in the handshake, clamped to the versions this
+    /// crate supports.
+    #[must_use]
+    pub fn versions(mut self, min_version: i32, max_version: i32) -> Self {
+        self.min_version = min_version.max(MIN_VERSION);
+        self.max_version = max_version.min(MAX_VERSION);
+        self
+    }
+
+    /// How long to wait for the socket to connect.
+    #[must_use]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+}
@@ -0,0 +1,620 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_broadcast::TrySendError;
+use futures::{
+    future::{self, BoxFuture, Either},
+    Sink, SinkExt, Stream, StreamExt, TryStreamExt,
+};
+
+use crate::{
+    message::{
+        commander::{Command, CommandReceiver},
+        context::Context,
+        dispatcher::Dispatcher,
+        request::{Handshake, StartApi},
+        response::{ErrMsgMsg, HandshakeAck},
+        Request, Response,
+    },
+    order_ids::OrderIdAllocator,
+    pacing::Pacer,
+    routing::{order_route_id, route_id},
+    ConnectOptions, Error, SpawnTask,
+};
+
+/// Delay before the first reconnection attempt, doubled after every failed attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_mins(1);
+/// Responses forwarded between two prunings of the routes whose receiver is gone.
+const PRUNE_INTERVAL: usize = 1000;
+
+/// Changes of the connection to TWS, and of the connection between TWS and the IB servers,
+/// see [`AsyncClient::connection_events`](crate::AsyncClient::connection_events).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The handshake is done and the API started.
+    Connected { server_version: i32 },
+    /// The connection to TWS was lost, a reconnecting client is trying to connect again.
+    Disconnected,
+    /// The connection was established again and the live subscriptions were sent again.
+    Reconnected,
+    /// TWS closed the socket.
+    SocketClosed,
+    /// TWS lost its connection to the IB servers (1100).
+    ConnectivityLost,
+    /// TWS is connected to the IB servers again, but the market data subscriptions were lost
+    /// and have to be requested again (1101).
+    ConnectivityRestoredDataLost,
+    /// TWS is connected to the IB servers again and the data is maintained (1102).
+    ConnectivityRestoredDataMaintained,
+    /// A data farm is connected, or inactive but available on demand (2104, 2106, 2107, 2108,
+    /// 2158).
+    FarmUp { kind: FarmKind, name: String },
+    /// The connection to a data farm is broken (2103, 2105, 2157).
+    FarmDown { kind: FarmKind, name: String },
+}
+
+impl ConnectionEvent {
+    /// The event reported by a TWS notice, `None` for the other error messages.
+    #[must_use]
+    pub fn from_err_msg(err: &ErrMsgMsg) -> Option<Self> {
+        let farm_name = || {
+            err.error_message
+                .rsplit_once(':')
+                .map_or("", |(_, name)| name)
+                .trim()
+                .to_string()
+        };
+        let event = match err.error_code {
+            1100 => ConnectionEvent::ConnectivityLost,
+            1101 => ConnectionEvent::ConnectivityRestoredDataLost,
+            1102 => ConnectionEvent::ConnectivityRestoredDataMaintained,
+            2103 => ConnectionEvent::FarmDown {
+                kind: FarmKind::MarketData,
+                name: farm_name(),
+            },
+            2104 | 2108 => ConnectionEvent::FarmUp {
+                kind: FarmKind::MarketData,
+                name: farm_name(),
+            },
+            2105 => ConnectionEvent::FarmDown {
+                kind: FarmKind::HistoricalData,
+                name: farm_name(),
+            },
+            2106 | 2107 => ConnectionEvent::FarmUp {
+                kind: FarmKind::HistoricalData,
+                name: farm_name(),
+            },
+            2157 => ConnectionEvent::FarmDown {
+                kind: FarmKind::SecDef,
+                name: farm_name(),
+            },
+            2158 => ConnectionEvent::FarmUp {
+                kind: FarmKind::SecDef,
+                name: farm_name(),
+            },
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// The data farms TWS reports the status of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FarmKind {
+    MarketData,
+    /// Historical market data service (HMDS).
+    HistoricalData,
+    /// Security definitions.
+    SecDef,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FarmState {
+    pub kind: FarmKind,
+    pub up: bool,
+}
+
+/// Snapshot of the connection, see
+/// [`AsyncClient::connection_state`](crate::AsyncClient::connection_state).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionState {
+    /// Whether the socket to TWS is open and the API started.
+    pub connected: bool,
+    /// Whether TWS is connected to the IB servers, as last reported with 1100, 1101 or 1102.
+    /// Assumed while connected until told otherwise.
+    pub ib_connected: bool,
+    /// Whether the market data subscriptions were lost when TWS last reconnected to the IB
+    /// servers (1101).
+    pub data_lost: bool,
+    /// Last reported state of the data farms, by farm name.
+    pub farms: BTreeMap<String, FarmState>,
+}
+
+impl ConnectionState {
+    fn apply(&mut self, event: &ConnectionEvent) {
+        match event {
+            ConnectionEvent::Connected { .. } => {
+                self.connected = true;
+                self.ib_connected = true;
+                self.data_lost = false;
+                self.farms.clear();
+            }
+            ConnectionEvent::Disconnected | ConnectionEvent::SocketClosed => {
+                self.connected = false;
+            }
+            ConnectionEvent::Reconnected => {}
+            ConnectionEvent::ConnectivityLost => self.ib_connected = false,
+            ConnectionEvent::ConnectivityRestoredDataLost => {
+                self.ib_connected = true;
+                self.data_lost = true;
+            }
+            ConnectionEvent::ConnectivityRestoredDataMaintained => {
+                self.ib_connected = true;
+                self.data_lost = false;
+            }
+            ConnectionEvent::FarmUp { kind, name } => {
+                self.farms.insert(
+                    name.clone(),
+                    FarmState {
+                        kind: *kind,
+                        up: true,
+                    },
+                );
+            }
+            ConnectionEvent::FarmDown { kind, name } => {
+                self.farms.insert(
+                    name.clone(),
+                    FarmState {
+                        kind: *kind,
+                        up: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Whether every data farm reported so far is up.
+    #[must_use]
+    pub fn farms_up(&self) -> bool {
+        self.farms.values().all(|farm| farm.up)
+    }
+}
+
+pub(crate) type SharedDispatcher = Arc<Mutex<Dispatcher<i32, Response, Error>>>;
+
+/// Opens a new transport for a reconnecting client.
+pub(crate) type Connect<T> = Box<dyn FnMut() -> BoxFuture<'static, io::Result<T>> + Send>;
+
+/// State shared between the client and the task driving the connection.
+#[derive(Debug, Clone)]
+pub(crate) struct Connection {
+    pub(crate) options: ConnectOptions,
+    pub(crate) dispatcher: SharedDispatcher,
+    pub(crate) order_ids: Arc<OrderIdAllocator>,
+    pub(crate) managed_accounts: Arc<Mutex<Vec<String>>>,
+    pub(crate) server_version: Arc<AtomicI32>,
+    /// Streaming requests which haven't been cancelled, sent again after a reconnect.
+    pub(crate) live_requests: Arc<Mutex<BTreeMap<i32, Request>>>,
+    pub(crate) response_tx: async_broadcast::Sender<Response>,
+    pub(crate) event_tx: async_broadcast::Sender<ConnectionEvent>,
+    pub(crate) state: Arc<Mutex<ConnectionState>>,
+    pub(crate) pacer: Arc<Mutex<Pacer>>,
+}
+
+/// What woke up the connection task.
+#[allow(clippy::large_enum_variant)]
+enum Next {
+    Command(Option<Command<i32, Request, Response, Error>>),
+    Response(Option<io::Result<Response>>),
+    /// A delayed historical request is due.
+    Release,
+}
+
+impl Connection {
+    /// Drive an established connection until the client is dropped, or the connection is
+    /// lost and can't be opened again.
+    pub(crate) async fn run<T>(
+        self,
+        mut transport: Pin<Box<T>>,
+        handshake_ack: HandshakeAck,
+        mut connect: Option<Connect<T>>,
+        mut command_rx: CommandReceiver<i32, Request, Response, Error>,
+    ) -> Result<(), Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask
+            + Send,
+    {
+        self.emit(ConnectionEvent::Connected {
+            server_version: handshake_ack.server_version,
+        });
+        let mut replayed = HashSet::new();
+        let mut execution_order_ids = HashMap::new();
+        // Historical requests held back by the pacing rules, kept across reconnects.
+        let mut delayed = VecDeque::new();
+        let result = loop {
+            let result = self
+                .serve(
+                    &mut transport,
+                    &mut command_rx,
+                    &mut replayed,
+                    &mut execution_order_ids,
+                    &mut delayed,
+                )
+                .await;
+
+            if let Err(Error::ConnectionClosed) = result {
+                self.emit(ConnectionEvent::SocketClosed);
+            }
+            self.emit(ConnectionEvent::Disconnected);
+            let (error, connect) = match (result, connect.as_mut()) {
+                (Ok(()), _) => break Ok(()),
+                (Err(error), None) => break Err(error),
+                (Err(error), Some(connect)) => (error, connect),
+            };
+            warn!(%error, "connection lost");
+
+            let Some((new_transport, handshake_ack)) = self.reconnect::<T>(connect).await else {
+                break Ok(());
+            };
+            transport = new_transport;
+            self.emit(ConnectionEvent::Connected {
+                server_version: handshake_ack.server_version,
+            });
+            replayed = self.replay(&mut transport).await;
+            self.emit(ConnectionEvent::Reconnected);
+        };
+
+        // Drop every route, so that pending requests see the end of their stream.
+        *self.dispatcher.lock().expect("dispatcher lock poisoned") = Dispatcher::new();
+        result
+    }
+
+    /// Perform the handshake and start the API. The managed accounts and next valid id which
+    /// follow are picked up while serving the connection.
+    /// # Errors
+    /// Returns `Error::Redirected` if the Gateway asks to connect to another address.
+    #[instrument(skip_all)]
+    pub(crate) async fn establish<T>(
+        &self,
+        transport: &mut Pin<Box<T>>,
+    ) -> Result<HandshakeAck, Error>
+    where
+        T: Sink<Request, Error = io::Error> + Stream<Item = Result<Response, io::Error>>,
+    {
+        debug!("performing handshake");
+        transport
+            .send(Request::Handshake(Handshake {
+                min_version: self.options.min_version,
+                max_version: self.options.max_version,
+                option: self.options.connect_options.clone(),
+            }))
+            .await?;
+
+        let handshake_ack = loop {
+            match transport.try_next().await? {
+                Some(Response::HandshakeAck(ack)) => break ack,
+                Some(response) => warn!(?response, "unexpected response before handshake ack"),
+                None => return Err(Error::ConnectionClosed),
+            }
+        };
+        debug!(?handshake_ack, "received handshake ack");
+        if let Some(address) = handshake_ack.redirect {
+            info!(%address, "redirected");
+            return Err(Error::Redirected(address));
+        }
+        self.server_version
+            .store(handshake_ack.server_version, Ordering::Relaxed);
+
+        debug!("requesting start api");
+        transport
+            .send(Request::StartApi(StartApi {
+                client_id: self.options.client_id,
+                optional_capabilities: self.options.optional_capabilities.clone(),
+            }))
+            .await?;
+
+        Ok(handshake_ack)
+    }
+
+    /// Open and establish a new connection, waiting longer after each failed attempt. Gives
+    /// up once the client is gone. A redirect counts as a failed attempt, `connect` has to
+    /// open the transport to the new address.
+    async fn reconnect<T>(&self, connect: &mut Connect<T>) -> Option<(Pin<Box<T>>, HandshakeAck)>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask,
+    {
+        let mut delay = RECONNECT_DELAY;
+        loop {
+            T::sleep(delay).await;
+            if self.response_tx.is_closed() {
+                return None;
+            }
+
+            info!("reconnecting");
+            let result = match connect().await {
+                Ok(transport) => {
+                    let mut transport = Box::pin(transport);
+                    self.establish(&mut transport)
+                        .await
+                        .map(|handshake_ack| (transport, handshake_ack))
+                }
+                Err(error) => Err(error.into()),
+            };
+            match result {
+                Ok(established) => return Some(established),
+                Err(error) => warn!(%error, ?delay, "failed to reconnect"),
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Send the live requests again with their original ids, the routes to their streams are
+    /// still registered. Returns the ids sent, so that a request still queued from before the
+    /// reconnect isn't sent twice.
+    async fn replay<T>(&self, transport: &mut Pin<Box<T>>) -> HashSet<i32>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask,
+    {
+        let requests: Vec<_> = {
+            let live_requests = self
+                .live_requests
+                .lock()
+                .expect("live requests lock poisoned");
+            live_requests
+                .iter()
+                .map(|(id, request)| (*id, request.clone()))
+                .collect()
+        };
+
+        let mut replayed = HashSet::new();
+        for (request_id, request) in requests {
+            match self.write(transport, request).await {
+                Ok(()) => {
+                    replayed.insert(request_id);
+                }
+                // Only this subscription is lost, e.g. TWS was downgraded meanwhile.
+                Err(error @ Error::Encode(_)) => self.reject(Some(request_id), error),
+                // Errors surface when the transport is flushed in `serve`.
+                Err(_) => break,
+            }
+        }
+        info!(count = replayed.len(), "replayed live requests");
+        replayed
+    }
+
+    async fn serve<T>(
+        &self,
+        transport: &mut Pin<Box<T>>,
+        command_rx: &mut CommandReceiver<i32, Request, Response, Error>,
+        replayed: &mut HashSet<i32>,
+        execution_order_ids: &mut HashMap<String, i32>,
+        delayed: &mut VecDeque<(Instant, Option<i32>, Request)>,
+    ) -> Result<(), Error>
+    where
+        T: Sink<Request, Error = io::Error>
+            + Stream<Item = Result<Response, io::Error>>
+            + SpawnTask,
+    {
+        transport.flush().await?;
+        loop {
+            let release = match delayed.front() {
+                Some((at, _, _)) => Either::Left(Box::pin(T::sleep(
+                    at.saturating_duration_since(Instant::now()),
+                ))),
+                None => Either::Right(future::pending()),
+            };
+            let next =
+                match future::select(future::select(command_rx.next(), transport.next()), release)
+                    .await
+                {
+                    Either::Left((Either::Left((command, _)), _)) => Next::Command(command),
+                    Either::Left((Either::Right((response, _)), _)) => Next::Response(response),
+                    Either::Right(((), _)) => Next::Release,
+                };
+
+            match next {
+                Next::Command(Some((request, reply))) => {
+                    let mut request_id = None;
+                    let mut already_sent = false;
+                    if let Some((id, reply)) = reply {
+                        already_sent = replayed.remove(&id);
+                        request_id = Some(id);
+                        self.dispatcher
+                            .lock()
+                            .expect("dispatcher lock poisoned")
+                            .register(id, reply);
+                    }
+                    if already_sent {
+                        continue;
+                    }
+                    if let Err(error) = Self::encode(&request, self.server_version()) {
+                        self.reject(request_id, error);
+                        continue;
+                    }
+                    if Pacer::is_historical(&request) {
+                        let now = Instant::now();
+                        let at = self
+                            .pacer
+                            .lock()
+                            .expect("pacer lock poisoned")
+                            .schedule(&request, now);
+                        if at > now || !delayed.is_empty() {
+                            delayed.push_back((at, request_id, request));
+                            continue;
+                        }
+                    }
+                    self.write_or_reject(transport, request_id, request).await?;
+                    transport.flush().await?;
+                }
+                Next::Command(None) => return Ok(()),
+                Next::Response(Some(response)) => self.forward(response?, execution_order_ids)?,
+                Next::Response(None) => return Err(Error::ConnectionClosed),
+                Next::Release => {
+                    let now = Instant::now();
+                    while delayed.front().is_some_and(|(at, _, _)| *at <= now) {
+                        if let Some((_, request_id, request)) = delayed.pop_front() {
+                            self.write_or_reject(transport, request_id, request).await?;
+                        }
+                    }
+                    transport.flush().await?;
+                }
+            }
+        }
+    }
+
+    fn server_version(&self) -> i32 {
+        self.server_version.load(Ordering::Relaxed)
+    }
+
+    /// Encode a request the way the transport will, to tell a request the server version
+    /// doesn't support apart from a failing transport.
+    pub(crate) fn encode(request: &Request, server_version: i32) -> Result<(), Error> {
+        let mut ctx = Context::new();
+        ctx.set_server_version(server_version);
+        ctx.encode_message(request).map(drop).map_err(Error::Encode)
+    }
+
+    /// Fail the consumer of a request which can't be sent, leaving the connection up.
+    fn reject(&self, request_id: Option<i32>, error: Error) {
+        warn!(?request_id, %error, "dropping a request which can't be encoded");
+        let Some(request_id) = request_id else {
+            return;
+        };
+        self.live_requests
+            .lock()
+            .expect("live requests lock poisoned")
+            .remove(&request_id);
+        let mut dispatcher = self.dispatcher.lock().expect("dispatcher lock poisoned");
+        let _ = dispatcher.reply_to(&request_id, Err(error));
+        // End the stream after the error.
+        dispatcher.unregister_stream(&request_id);
+    }
+
+    /// Write a request, failing only its consumer if it can't be encoded.
+    async fn write_or_reject<T>(
+        &self,
+        transport: &mut Pin<Box<T>>,
+        request_id: Option<i32>,
+        request: Request,
+    ) -> Result<(), Error>
+    where
+        T: Sink<Request, Error = io::Error> + SpawnTask,
+    {
+        match self.write(transport, request).await {
+            Err(error @ Error::Encode(_)) => {
+                self.reject(request_id, error);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Write a request, once the message rate allows it. The transport is flushed before
+    /// waiting, but not after writing.
+    /// # Errors
+    /// Returns `Error::Encode` without writing anything if the server version doesn't
+    /// support the request.
+    async fn write<T>(&self, transport: &mut Pin<Box<T>>, request: Request) -> Result<(), Error>
+    where
+        T: Sink<Request, Error = io::Error> + SpawnTask,
+    {
+        Self::encode(&request, self.server_version())?;
+        let delay = self
+            .pacer
+            .lock()
+            .expect("pacer lock poisoned")
+            .message_delay(Instant::now());
+        if !delay.is_zero() {
+            debug!(?delay, "pacing messages");
+            transport.flush().await?;
+            T::sleep(delay).await;
+        }
+        self.pacer
+            .lock()
+            .expect("pacer lock poisoned")
+            .record_message(Instant::now());
+        transport.feed(request).await?;
+        Ok(())
+    }
+
+    fn forward(
+        &self,
+        message: Response,
+        execution_order_ids: &mut HashMap<String, i32>,
+    ) -> Result<(), Error> {
+        self.dispatcher
+            .lock()
+            .expect("dispatcher lock poisoned")
+            .prune_every(PRUNE_INTERVAL);
+
+        match message {
+            Response::NextValidIdMsg(ref msg) => self.order_ids.update(msg.order_id),
+            Response::ErrMsgMsg(ref err) if err.id == -1 => {
+                if let Some(event) = ConnectionEvent::from_err_msg(err) {
+                    self.emit(event);
+                }
+            }
+            Response::ManagedAcctsMsg(ref msg) => {
+                let accounts = msg.accounts.split(',').map(String::from).collect();
+                info!(?accounts, "updating managed accounts");
+                *self
+                    .managed_accounts
+                    .lock()
+                    .expect("managed accounts lock poisoned") = accounts;
+            }
+            _ => {}
+        }
+
+        // Order updates are broadcast as well, the open order snapshots rely on them.
+        if let Some(order_id) = order_route_id(&message, execution_order_ids) {
+            let _ = self
+                .dispatcher
+                .lock()
+                .expect("dispatcher lock poisoned")
+                .reply_to(&order_id, Ok(message.clone()));
+        }
+
+        let message = match route_id(&message) {
+            Some(request_id) => match self
+                .dispatcher
+                .lock()
+                .expect("dispatcher lock poisoned")
+                .reply_to(&request_id, Ok(message))
+            {
+                Ok(()) | Err(Err(_)) => return Ok(()),
+                Err(Ok(message)) => message,
+            },
+            None => message,
+        };
+
+        // Nobody listening or a lagging listener must not hold up the routed responses.
+        if let Err(TrySendError::Closed(_)) = self.response_tx.try_broadcast(message) {
+            return Err(Error::ResponseChannelClosed);
+        }
+        Ok(())
+    }
+
+    fn emit(&self, event: ConnectionEvent) {
+        info!(?event, "connection event");
+        self.state
+            .lock()
+            .expect("connection state lock poisoned")
+            .apply(&event);
+        let _ = self.event_tx.try_broadcast(event);
+    }
+}
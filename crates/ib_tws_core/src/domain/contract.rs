@@ -1,6 +1,8 @@
 use std::default::Default;
 use std::io;
 
+use rust_decimal::Decimal;
+
 use crate::domain::tag_value::TagValue;
 
 #[derive(Debug, Clone, Default)]
@@ -62,6 +64,9 @@ pub struct Contract {
     pub combo_legs_descrip: String,
     // received in open order version 14 and up for all combos
     pub combo_legs: Vec<ComboLeg>,
+
+    pub description: String,
+    pub issuer_id: String,
 }
 
 impl Contract {
@@ -110,6 +115,10 @@ pub struct ContractDetails {
     pub market_rule_ids: String,
     pub real_expiration_date: String,
     pub last_trade_time: String,
+    pub stock_type: String,
+    pub min_size: Decimal,
+    pub size_increment: Decimal,
+    pub suggested_size_increment: Decimal,
 
     // BOND values
     pub cusip: String,
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// Classification of the codes carried by TWS error messages.
+///
+/// See <https://interactivebrokers.github.io/tws-api/message_codes.html> for the full list,
+/// codes which don't fall into one of the known ranges are kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TwsErrorCode {
+    /// Connection to TWS or between TWS and the IB servers lost, restored or refused
+    /// (502, 504, 507, 1100-1102, 1300).
+    Connectivity(i32),
+    /// Informational notices, the request they refer to is still live
+    /// (399, 2100-2169, 10167).
+    Warning(i32),
+    /// Too many messages or requests in a given time (100, 420).
+    PacingViolation(i32),
+    /// Missing market data subscription (354, 10089, 10090, 10168, 10186, 10197).
+    NoMarketDataPermission(i32),
+    /// Order rejected, cancelled or not modifiable (103-163 range, 201-203, 10147-10149).
+    OrderRejection(i32),
+    /// No security definition found for the request (200).
+    ContractNotFound(i32),
+    /// Historical market data service errors, including query pacing (162, 165, 166, 366, 386).
+    HistoricalData(i32),
+    /// Errors raised by the client while encoding or sending a request (501-599 without the
+    /// connectivity codes).
+    Client(i32),
+    Other(i32),
+}
+
+impl TwsErrorCode {
+    #[must_use]
+    pub fn code(self) -> i32 {
+        match self {
+            TwsErrorCode::Connectivity(code)
+            | TwsErrorCode::Warning(code)
+            | TwsErrorCode::PacingViolation(code)
+            | TwsErrorCode::NoMarketDataPermission(code)
+            | TwsErrorCode::OrderRejection(code)
+            | TwsErrorCode::ContractNotFound(code)
+            | TwsErrorCode::HistoricalData(code)
+            | TwsErrorCode::Client(code)
+            | TwsErrorCode::Other(code) => code,
+        }
+    }
+
+    /// Warnings don't end the request they are reported for.
+    #[must_use]
+    pub fn is_warning(self) -> bool {
+        matches!(self, TwsErrorCode::Warning(_))
+    }
+}
+
+impl From<i32> for TwsErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            502 | 504 | 507 | 1100..=1102 | 1300 => TwsErrorCode::Connectivity(code),
+            399 | 2100..=2169 | 10167 => TwsErrorCode::Warning(code),
+            100 | 420 => TwsErrorCode::PacingViolation(code),
+            354 | 10089 | 10090 | 10168 | 10186 | 10197 => {
+                TwsErrorCode::NoMarketDataPermission(code)
+            }
+            162 | 165 | 166 | 366 | 386 => TwsErrorCode::HistoricalData(code),
+            200 => TwsErrorCode::ContractNotFound(code),
+            103..=163 | 201..=203 | 10147..=10149 => TwsErrorCode::OrderRejection(code),
+            501..=599 => TwsErrorCode::Client(code),
+            _ => TwsErrorCode::Other(code),
+        }
+    }
+}
+
+impl fmt::Display for TwsErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let category = match self {
+            TwsErrorCode::Connectivity(_) => "connectivity",
+            TwsErrorCode::Warning(_) => "warning",
+            TwsErrorCode::PacingViolation(_) => "pacing violation",
+            TwsErrorCode::NoMarketDataPermission(_) => "no market data permission",
+            TwsErrorCode::OrderRejection(_) => "order rejection",
+            TwsErrorCode::ContractNotFound(_) => "contract not found",
+            TwsErrorCode::HistoricalData(_) => "historical data",
+            TwsErrorCode::Client(_) => "client",
+            TwsErrorCode::Other(_) => "other",
+        };
+        write!(f, "{} ({})", category, self.code())
+    }
+}
+
+/// Structured reason of an order reject, sent along with the error message from
+/// `MIN_SERVER_VER_ADVANCED_ORDER_REJECT` on.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdvancedOrderReject {
+    #[serde(default, alias = "rejectReason", alias = "RejectReason")]
+    pub reject_reason: Option<String>,
+    #[serde(default, alias = "rejectType", alias = "RejectType")]
+    pub reject_type: Option<String>,
+    /// Ways to override the reject when resubmitting the order.
+    #[serde(default, alias = "Options")]
+    pub options: Vec<AdvancedOrderRejectOption>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdvancedOrderRejectOption {
+    #[serde(default, alias = "Code", deserialize_with = "string_or_number")]
+    pub code: String,
+    #[serde(default, alias = "Text", alias = "description")]
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AdvancedOrderReject {
+    /// Whether the reject can be overridden by resubmitting with `advanced_error_override`.
+    #[must_use]
+    pub fn is_overridable(&self) -> bool {
+        self.options.iter().any(|option| !option.code.is_empty())
+    }
+
+    /// The value to set as `Order::advanced_error_override` to accept all offered overrides.
+    #[must_use]
+    pub fn override_codes(&self) -> String {
+        self.options
+            .iter()
+            .filter(|option| !option.code.is_empty())
+            .map(|option| option.code.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
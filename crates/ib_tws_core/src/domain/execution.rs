@@ -1,6 +1,8 @@
 use std::default::Default;
 use std::io;
 
+use rust_decimal::Decimal;
+
 #[derive(Debug, Clone, Default)]
 pub struct OrderState {
     pub status: String,
@@ -18,6 +20,8 @@ pub struct OrderState {
     pub max_commission: f64,
     pub commission_currency: String,
     pub warning_text: String,
+    pub completed_time: String,
+    pub completed_status: String,
 }
 
 #[derive(Debug, Clone)]
@@ -63,11 +67,11 @@ pub struct Execution {
     pub acct_number: String,
     pub exchange: String,
     pub side: String,
-    pub shares: f64,
+    pub shares: Decimal,
     pub price: f64,
     pub perm_id: i32,
     pub liquidation: i32,
-    pub cum_qty: f64,
+    pub cum_qty: Decimal,
     pub avg_price: f64,
     pub order_ref: String,
     pub ev_rule: String,
@@ -98,11 +102,11 @@ impl Default for Execution {
             acct_number: "".to_string(),
             exchange: "".to_string(),
             side: "".to_string(),
-            shares: 0.0,
+            shares: Decimal::ZERO,
             price: 0.0,
             perm_id: 0,
             liquidation: 0,
-            cum_qty: 0.0,
+            cum_qty: Decimal::ZERO,
             avg_price: 0.0,
             order_ref: "".to_string(),
             ev_rule: "".to_string(),
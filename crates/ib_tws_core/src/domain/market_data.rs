@@ -113,11 +113,18 @@ impl baseline::bar::Volume for Bar {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct HistoricalSession {
+    pub start_date_time: String,
+    pub end_date_time: String,
+    pub ref_date: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct HistoricalTick {
     pub time: i64,
     pub price: f64,
-    pub size: i64,
+    pub size: Decimal,
 }
 
 #[derive(Debug, Clone)]
@@ -126,8 +133,8 @@ pub struct HistoricalTickBidAsk {
     pub mask: i32,
     pub price_bid: f64,
     pub price_ask: f64,
-    pub size_bid: i64,
-    pub size_ask: i64,
+    pub size_bid: Decimal,
+    pub size_ask: Decimal,
 }
 
 #[derive(Debug, Clone)]
@@ -135,7 +142,7 @@ pub struct HistoricalTickLast {
     pub time: i64,
     pub mask: i32,
     pub price: f64,
-    pub size: i64,
+    pub size: Decimal,
     pub exchange: String,
     pub special_conditions: String,
 }
@@ -143,7 +150,7 @@ pub struct HistoricalTickLast {
 #[derive(Debug, Clone)]
 pub struct HistogramEntry {
     pub price: f64,
-    pub size: i64,
+    pub size: Decimal,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -570,3 +577,28 @@ pub enum GenericTick {
     RealtimeHistoricalVolatility = 411,
     IbDividends = 456
 }
+
+impl TryFrom<i32> for GenericTick {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            100 => Ok(Self::OptionVolume),
+            101 => Ok(Self::OptionOpenInterest),
+            104 => Ok(Self::HistoricalVolatility),
+            105 => Ok(Self::AverageOptionVolume),
+            106 => Ok(Self::OptionImpliedVolatility),
+            162 => Ok(Self::IndexFuturePremium),
+            165 => Ok(Self::MiscellaneousStats),
+            221 => Ok(Self::MarkPrice),
+            225 => Ok(Self::AuctionValues),
+            233 => Ok(Self::RtVolume),
+            236 => Ok(Self::Shortable),
+            256 => Ok(Self::Inventory),
+            258 => Ok(Self::FundamentalRatios),
+            411 => Ok(Self::RealtimeHistoricalVolatility),
+            456 => Ok(Self::IbDividends),
+            _ => Err(()),
+        }
+    }
+}
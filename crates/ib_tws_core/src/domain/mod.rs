@@ -5,9 +5,10 @@ pub use self::condition::{
 pub use self::contract::{
     ComboLeg, Contract, ContractDescription, ContractDetails, DeltaNeutralContract,
 };
+pub use self::error_code::{AdvancedOrderReject, AdvancedOrderRejectOption, TwsErrorCode};
 pub use self::execution::{CommissionReport, Execution, ExecutionFilter, Liquidities, OrderState};
 pub use self::market_data::{
-    Bar, DepthMktDataDescription, HistogramEntry, HistoricalTick, HistoricalTickBidAsk,
+    Bar, DepthMktDataDescription, HistogramEntry, HistoricalSession, HistoricalTick, HistoricalTickBidAsk,
     HistoricalTickLast, TickAttr, TickByTick, TickType,
 };
 pub use self::misc::{FamilyCode, PriceIncrement};
@@ -16,9 +17,11 @@ pub use self::order::{Order, OrderComboLeg, OrderStatus, OrderType};
 pub use self::scanner::ScannerSubscription;
 pub use self::soft_dollar_tier::SoftDollarTier;
 pub use self::tag_value::TagValue;
+pub use self::wsh::{WshEvent, WshEventData};
 
 pub mod condition;
 pub mod contract;
+pub mod error_code;
 pub mod execution;
 pub mod market_data;
 pub mod misc;
@@ -27,3 +30,4 @@ pub mod order;
 pub mod scanner;
 pub mod soft_dollar_tier;
 pub mod tag_value;
+pub mod wsh;
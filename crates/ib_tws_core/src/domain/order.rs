@@ -2,6 +2,8 @@ use std::fmt;
 use std::str::FromStr;
 use std::{f64, i32};
 
+use rust_decimal::Decimal;
+
 use crate::domain::condition::OrderCondition;
 use crate::domain::soft_dollar_tier::SoftDollarTier;
 use crate::domain::tag_value::TagValue;
@@ -53,6 +55,7 @@ pub enum OrderType {
     MTL,
     PASSV_REL,
     PEG_BENCH,
+    PEG_BEST,
     PEG_MID,
     PEG_MKT,
     PEG_PRIM,
@@ -99,6 +102,7 @@ impl FromStr for OrderType {
             "MTL" => Ok(OrderType::MTL),
             "PASSV REL" => Ok(OrderType::PASSV_REL),
             "PEG BENCH" => Ok(OrderType::PEG_BENCH),
+            "PEG BEST" => Ok(OrderType::PEG_BEST),
             "PEG MID" => Ok(OrderType::PEG_MID),
             "PEG MKT" => Ok(OrderType::PEG_MKT),
             "PEG PRIM" => Ok(OrderType::PEG_PRIM),
@@ -147,6 +151,7 @@ impl fmt::Display for OrderType {
             OrderType::MTL => write!(f, "MTL"),
             OrderType::PASSV_REL => write!(f, "PASSV REL"),
             OrderType::PEG_BENCH => write!(f, "PEG BENCH"),
+            OrderType::PEG_BEST => write!(f, "PEG BEST"),
             OrderType::PEG_MID => write!(f, "PEG MID"),
             OrderType::PEG_MKT => write!(f, "PEG MKT"),
             OrderType::PEG_PRIM => write!(f, "PEG PRIM"),
@@ -188,8 +193,8 @@ pub struct Order {
 
     // primary attributes
     pub action: String,
-    pub total_quantity: f64,
-    pub display_size: i32,
+    pub total_quantity: Decimal,
+    pub display_size: Decimal,
     pub order_type: String,
     pub lmt_price: f64,
     pub aux_price: f64,
@@ -210,7 +215,7 @@ pub struct Order {
     pub percent_offset: f64,
     pub trailing_percent: f64,
     pub trail_stop_price: f64,
-    pub min_qty: i32,
+    pub min_qty: Decimal,
     pub good_after_time: String,
     pub good_till_date: String,
     pub oca_group: String,
@@ -350,6 +355,32 @@ pub struct Order {
 
     // don't use auto price for hedge
     pub dont_use_auto_price_for_hedge: bool,
+
+    pub is_oms_container: bool,
+    pub discretionary_up_to_limit_price: bool,
+    pub use_price_mgmt_algo: Option<bool>,
+    pub duration: i32,
+    pub post_to_ats: i32,
+    pub auto_cancel_parent: bool,
+
+    pub advanced_error_override: String,
+    pub manual_order_time: String,
+
+    // PEG BEST / PEG MID
+    pub min_trade_qty: i32,
+    pub min_compete_size: i32,
+    pub compete_against_best_offset: f64,
+    pub mid_offset_at_whole: f64,
+    pub mid_offset_at_half: f64,
+
+    // completed orders
+    pub auto_cancel_date: String,
+    pub filled_quantity: Decimal,
+    pub ref_futures_con_id: i32,
+    pub shareholder: String,
+    pub imbalance_only: bool,
+    pub route_marketable_to_bbo: bool,
+    pub parent_perm_id: i64,
 }
 
 impl Default for Order {
@@ -362,8 +393,8 @@ impl Default for Order {
 
             // primary attributes
             action: "BUY".to_string(),
-            total_quantity: 0.0,
-            display_size: 0,
+            total_quantity: Decimal::ZERO,
+            display_size: Decimal::ZERO,
             order_type: "LMT".to_string(),
             lmt_price: f64::MAX,
             aux_price: f64::MAX,
@@ -384,7 +415,7 @@ impl Default for Order {
             percent_offset: f64::MAX,
             trailing_percent: f64::MAX,
             trail_stop_price: f64::MAX,
-            min_qty: i32::MAX,
+            min_qty: Decimal::MAX,
             good_after_time: "".to_string(), // FORMAT: 20060505 08:00:00 EST
             good_till_date: "".to_string(),  // FORMAT: 20060505 08:00:00 EST or 20060505
             oca_group: "".to_string(),       // one cancels all group name
@@ -524,6 +555,42 @@ impl Default for Order {
 
             // don't use auto price for hedge
             dont_use_auto_price_for_hedge: false,
+
+            is_oms_container: false,
+            discretionary_up_to_limit_price: false,
+            use_price_mgmt_algo: None,
+            duration: i32::MAX,
+            post_to_ats: i32::MAX,
+            auto_cancel_parent: false,
+
+            advanced_error_override: "".to_string(),
+            manual_order_time: "".to_string(),
+
+            // PEG BEST / PEG MID
+            min_trade_qty: i32::MAX,
+            min_compete_size: i32::MAX,
+            compete_against_best_offset: f64::MAX,
+            mid_offset_at_whole: f64::MAX,
+            mid_offset_at_half: f64::MAX,
+
+            // completed orders
+            auto_cancel_date: "".to_string(),
+            filled_quantity: Decimal::MAX,
+            ref_futures_con_id: i32::MAX,
+            shareholder: "".to_string(),
+            imbalance_only: false,
+            route_marketable_to_bbo: false,
+            parent_perm_id: i64::MAX,
         }
     }
 }
+
+impl Order {
+    /// Sentinel for `compete_against_best_offset`: compete against the best offset up to the mid.
+    pub const COMPETE_AGAINST_BEST_OFFSET_UP_TO_MID: f64 = f64::INFINITY;
+
+    #[must_use]
+    pub fn is_compete_against_best_offset_up_to_mid(&self) -> bool {
+        self.compete_against_best_offset == Self::COMPETE_AGAINST_BEST_OFFSET_UP_TO_MID
+    }
+}
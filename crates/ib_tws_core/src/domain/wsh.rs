@@ -0,0 +1,98 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Parameters of a Wall Street Horizon event data request.
+///
+/// Either `con_id` or `filter` should be set. The filter, watchlist/portfolio/competitor
+/// flags require `MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS`, the date range and total limit
+/// require `MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS_DATE`.
+#[derive(Debug, Clone)]
+pub struct WshEventData {
+    pub con_id: i32,
+    pub filter: String,
+    pub fill_watchlist: bool,
+    pub fill_portfolio: bool,
+    pub fill_competitors: bool,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_limit: i32,
+}
+
+impl WshEventData {
+    #[must_use]
+    pub fn by_con_id(con_id: i32) -> Self {
+        WshEventData {
+            con_id,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn by_filter(filter: &str) -> Self {
+        WshEventData {
+            filter: filter.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn has_filters(&self) -> bool {
+        !self.filter.is_empty()
+            || self.fill_watchlist
+            || self.fill_portfolio
+            || self.fill_competitors
+    }
+
+    pub(crate) fn has_date_range(&self) -> bool {
+        !self.start_date.is_empty() || !self.end_date.is_empty() || self.total_limit != i32::MAX
+    }
+}
+
+impl Default for WshEventData {
+    fn default() -> Self {
+        WshEventData {
+            con_id: i32::MAX,
+            filter: String::new(),
+            fill_watchlist: false,
+            fill_portfolio: false,
+            fill_competitors: false,
+            start_date: String::new(),
+            end_date: String::new(),
+            total_limit: i32::MAX,
+        }
+    }
+}
+
+/// A single calendar event (earnings date, dividend, ...) from a WSH event data payload.
+///
+/// The event specific attributes are left in `data`, any other top level attribute
+/// ends up in `extra`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WshEvent {
+    #[serde(default, rename = "conid")]
+    pub con_id: Option<i32>,
+    #[serde(default)]
+    pub event_type: String,
+    #[serde(default)]
+    pub event_key: Option<String>,
+    #[serde(default)]
+    pub index_date_type: Option<String>,
+    #[serde(default)]
+    pub index_date: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl WshEvent {
+    /// Parse the JSON payload of a `WSH_EVENT_DATA` message, which is either a list of
+    /// events or a single event.
+    /// # Errors
+    /// Returns an error if the payload is not valid JSON or doesn't describe events.
+    pub fn parse_events(json: &str) -> Result<Vec<WshEvent>, serde_json::Error> {
+        match serde_json::from_str(json)? {
+            Value::Array(events) => events.into_iter().map(serde_json::from_value).collect(),
+            event => Ok(vec![serde_json::from_value(event)?]),
+        }
+    }
+}
@@ -8,6 +8,13 @@ extern crate tracing;
 
 pub mod domain;
 pub mod message;
+pub mod order_ids;
+
+mod connect_options;
+pub use connect_options::{ConnectOptions, DEFAULT_CONNECT_TIMEOUT};
+pub mod pacing;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod routing;
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
@@ -15,14 +22,54 @@ pub enum Error {
     RequestChannelClosed,
     #[error("response channel closed")]
     ResponseChannelClosed,
+    #[error("connection closed")]
+    ConnectionClosed,
+    #[error("request timed out")]
+    Timeout,
+    #[error("redirected to {0}")]
+    Redirected(String),
     #[error("transport io error: {0}")]
     TransportIo(#[from] std::io::Error),
+    /// The request isn't supported by the server version, nothing was sent.
+    #[error("request can't be encoded: {0}")]
+    Encode(#[source] std::io::Error),
     #[error("api error: {0:?}")]
-    ApiError(message::response::ErrMsgMsg),
+    ApiError(Box<message::response::ErrMsgMsg>),
+    #[error("unexpected response: {0:?}")]
+    UnexpectedResponse(Box<message::Response>),
+    #[error("invalid json payload: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+impl Error {
+    /// The classified code of an API error, if this is one.
+    #[must_use]
+    pub fn api_error_code(&self) -> Option<domain::TwsErrorCode> {
+        match self {
+            Error::ApiError(err) => Some(err.code()),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(feature = "async")]
 mod async_client;
 #[cfg(feature = "async")]
-pub use async_client::{SpawnTask, AsyncClient};
+pub use async_client::{SpawnTask, AsyncClient, DEFAULT_TIMEOUT};
+#[cfg(feature = "async")]
+mod connection;
+#[cfg(feature = "async")]
+pub use connection::{ConnectionEvent, ConnectionState, FarmKind, FarmState};
+#[cfg(feature = "async")]
+mod order_handle;
+#[cfg(feature = "async")]
+pub use order_handle::OrderHandle;
+#[cfg(feature = "async")]
+mod subscription;
+#[cfg(feature = "async")]
+pub use subscription::Subscription;
 
+#[cfg(feature = "blocking")]
+pub mod blocking_client;
+#[cfg(feature = "blocking")]
+pub use blocking_client::BlockingClient;
@@ -1,6 +1,8 @@
 use std::io;
 
 use bytes::BytesMut;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 
 use super::constants::*;
 use super::context::{Context, DispatchId};
@@ -63,9 +65,9 @@ pub fn decode_portfolio_value_msg(
     }
 
     let position = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     let market_price = buf.read_double()?;
@@ -341,3 +343,246 @@ pub fn decode_account_update_multi_end_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_account_updates(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let subscribe = buf.read_bool()?;
+    let acct_code = buf.read_string()?;
+
+    Ok(Request::ReqAccountUpdates(ReqAccountUpdates {
+        subscribe,
+        acct_code,
+    }))
+}
+
+pub fn encode_portfolio_value_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PortfolioValueMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 8;
+
+    buf.push_int(PORTFOLIO_VALUE);
+    buf.push_int(VERSION);
+    buf.push_int(msg.contract.con_id);
+    buf.push_string(&msg.contract.symbol);
+    buf.push_string(&msg.contract.sec_type);
+    buf.push_string(&msg.contract.last_trade_date_or_contract_month);
+    buf.push_double(msg.contract.strike);
+    buf.push_string(&msg.contract.right);
+    buf.push_string(&msg.contract.multiplier);
+    buf.push_string(&msg.contract.primary_exch);
+    buf.push_string(&msg.contract.currency);
+    buf.push_string(&msg.contract.local_symbol);
+    buf.push_string(&msg.contract.trading_class);
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_decimal(msg.position);
+    } else {
+        buf.push_int(msg.position.to_i32().unwrap_or_default());
+    }
+    buf.push_double(msg.market_price);
+    buf.push_double(msg.market_value);
+    buf.push_double(msg.average_cost);
+    buf.push_double(msg.unrealized_pnl);
+    buf.push_double(msg.realized_pnl);
+    buf.push_string(&msg.account_name);
+
+    Ok(())
+}
+
+pub fn encode_acct_update_time_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AcctUpdateTimeMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCT_UPDATE_TIME);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.time_stamp);
+
+    Ok(())
+}
+
+pub fn encode_acct_value_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AcctValueMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 2;
+
+    buf.push_int(ACCT_VALUE);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.key);
+    buf.push_string(&msg.val);
+    buf.push_string(&msg.cur);
+    buf.push_string(&msg.account_name);
+
+    Ok(())
+}
+
+pub fn encode_acct_download_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AcctDownloadEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCT_DOWNLOAD_END);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.account_name);
+
+    Ok(())
+}
+
+pub fn decode_req_account_summary(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+    let group = buf.read_string()?;
+    let tags = buf.read_string()?;
+
+    Ok(Request::ReqAccountSummary(ReqAccountSummary {
+        req_id,
+        group,
+        tags,
+    }))
+}
+
+pub fn decode_cancel_account_summary(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelAccountSummary(CancelAccountSummary {
+        req_id,
+    }))
+}
+
+pub fn encode_account_summary_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AccountSummaryMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCOUNT_SUMMARY);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.account);
+    buf.push_string(&msg.tag);
+    buf.push_string(&msg.value);
+    buf.push_string(&msg.currency);
+
+    Ok(())
+}
+
+pub fn encode_account_summary_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AccountSummaryEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCOUNT_SUMMARY_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn decode_req_managed_accts(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqManagedAccts(ReqManagedAccts {}))
+}
+
+pub fn decode_cancel_account_updates_multi(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelAccountUpdatesMulti(
+        CancelAccountUpdatesMulti { req_id },
+    ))
+}
+
+pub fn encode_managed_accts_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ManagedAcctsMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(MANAGED_ACCTS);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.accounts);
+
+    Ok(())
+}
+
+pub fn decode_req_account_updates_multi(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+    let account = buf.read_string()?;
+    let model_code = buf.read_string()?;
+    let ledger_and_nlv = buf.read_bool()?;
+
+    Ok(Request::ReqAccountUpdatesMulti(ReqAccountUpdatesMulti {
+        req_id,
+        account,
+        model_code,
+        ledger_and_nlv,
+    }))
+}
+
+pub fn encode_account_update_multi_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AccountUpdateMultiMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCOUNT_UPDATE_MULTI);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.account);
+    buf.push_string(&msg.model_code);
+    buf.push_string(&msg.key);
+    buf.push_string(&msg.value);
+    buf.push_string(&msg.currency);
+
+    Ok(())
+}
+
+pub fn encode_account_update_multi_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &AccountUpdateMultiEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(ACCOUNT_UPDATE_MULTI_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
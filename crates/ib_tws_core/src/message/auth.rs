@@ -141,3 +141,122 @@ pub fn decode_verify_completed_msg(
         OPCODE_VERIFY_REQUEST,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_verify_request(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let api_name = buf.read_string()?;
+    let api_version = buf.read_string()?;
+
+    // only sent by clients with extra auth
+    Ok(Request::VerifyRequest(VerifyRequest {
+        api_name,
+        api_version,
+        extra_auth: true,
+    }))
+}
+
+pub fn decode_verify_message(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let api_data = buf.read_string()?;
+
+    Ok(Request::VerifyMessage(VerifyMessage { api_data }))
+}
+
+pub fn decode_verify_and_auth_request(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let api_name = buf.read_string()?;
+    let api_version = buf.read_string()?;
+    let opaque_is_vkey = buf.read_string()?;
+
+    Ok(Request::VerfyAndAuthRequest(VerfyAndAuthRequest {
+        api_name,
+        api_version,
+        opaque_is_vkey,
+        extra_auth: true,
+    }))
+}
+
+pub fn decode_verify_and_auth_message(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let api_data = buf.read_string()?;
+    let xyz_response = buf.read_string()?;
+
+    Ok(Request::VerifyAndAuthMessage(VerifyAndAuthMessage {
+        api_data,
+        xyz_response,
+    }))
+}
+
+pub fn encode_verify_and_auth_completed_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &VerifyAndAuthCompletedMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(VERIFY_AND_AUTH_COMPLETED);
+    buf.push_int(VERSION);
+    buf.push_string(if msg.is_successful { "true" } else { "false" });
+    buf.push_string(&msg.error_text);
+
+    Ok(())
+}
+
+pub fn encode_verify_and_auth_message_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &VerifyAndAuthMessageMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(VERIFY_AND_AUTH_MESSAGE_API);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.api_data);
+    buf.push_string(&msg.xyz_challenge);
+
+    Ok(())
+}
+
+pub fn encode_verify_message_api_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &VerifyMessageApiMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(VERIFY_MESSAGE_API);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.api_data);
+
+    Ok(())
+}
+
+pub fn encode_verify_completed_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &VerifyCompletedMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(VERIFY_COMPLETED);
+    buf.push_int(VERSION);
+    buf.push_string(if msg.is_successful { "true" } else { "false" });
+    buf.push_string(&msg.error_text);
+
+    Ok(())
+}
@@ -58,3 +58,42 @@ pub fn decode_news_bulletins_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_news_bulletins(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let all_msgs = buf.read_bool()?;
+
+    Ok(Request::ReqNewsBulletins(ReqNewsBulletins { all_msgs }))
+}
+
+pub fn decode_cancel_news_bulletins(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::CancelNewsBulletins(CancelNewsBulletins {}))
+}
+
+pub fn encode_news_bulletins_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &NewsBulletinsMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(NEWS_BULLETINS);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.msg_type);
+    buf.push_string(&msg.message);
+    buf.push_string(&msg.originating_exch);
+
+    Ok(())
+}
@@ -1,13 +1,4 @@
-use std::{
-    pin::{self, Pin},
-    task::{Context, Poll},
-};
-
-use futures::{
-    channel::{mpsc, oneshot},
-    future::Either,
-    stream, Future, FutureExt, Stream, StreamExt, TryFutureExt, TryStreamExt,
-};
+use futures::channel::{mpsc, oneshot};
 
 #[derive(Debug, Clone, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
@@ -20,6 +11,7 @@ pub enum Error {
 }
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+// I: Request id
 // T: Request
 // R: Response
 // E: Error
@@ -32,37 +24,44 @@ pub enum ReplySender<R, E> {
     Stream(UnboundedReplySender<R, E>),
 }
 
-pub type Command<T, R, E> = (T, ReplySender<R, E>);
+/// A request, along with the id and channel its replies should be routed to. The route has to
+/// be registered before the request is written, so that no reply can arrive ahead of it.
+pub type Command<I, T, R, E> = (T, Option<(I, ReplySender<R, E>)>);
 
-pub type CommandSender<T, R, E> = mpsc::UnboundedSender<Command<T, R, E>>;
-pub type CommandReceiver<T, R, E> = mpsc::UnboundedReceiver<Command<T, R, E>>;
+pub type CommandSender<I, T, R, E> = mpsc::UnboundedSender<Command<I, T, R, E>>;
+pub type CommandReceiver<I, T, R, E> = mpsc::UnboundedReceiver<Command<I, T, R, E>>;
 
 #[derive(Debug)]
-pub struct Commander<T, R, E>(pub CommandSender<T, R, E>);
+pub struct Commander<I, T, R, E>(pub CommandSender<I, T, R, E>);
+
+impl<I, T, R, E> Commander<I, T, R, E> {
+    /// Send a request without waiting for any reply.
+    pub fn notify(&self, req: T) -> Result<()> {
+        self.0
+            .unbounded_send((req, None))
+            .map_err(|_| Error::FailedToSendNewRequest)
+    }
 
-impl<T, R, E> Commander<T, R, E> {
-    pub async fn send(&self, req: T) -> Result<Result<R, E>> {
+    /// Send a request and wait for the first reply with `id`.
+    pub async fn send(&self, id: I, req: T) -> Result<Result<R, E>> {
         let (tx, rx) = oneshot::channel();
-        match self.0.unbounded_send((req, ReplySender::Oneshot(tx))) {
-            Ok(()) => Either::Left(rx.map_err(|e| Error::FailedToReceiveOneshotResponse)),
-            Err(e) => Either::Right(async { Err(Error::FailedToSendNewRequest) }),
-        }
-        .await
+        self.0
+            .unbounded_send((req, Some((id, ReplySender::Oneshot(tx)))))
+            .map_err(|_| Error::FailedToSendNewRequest)?;
+        rx.await.map_err(|_| Error::FailedToReceiveOneshotResponse)
     }
 
-    pub fn subscribe(&self, req: T) -> impl Stream<Item = Result<Result<R, E>>> {
+    /// Send a request and receive every reply with `id`, until the receiver is dropped.
+    pub fn subscribe(&self, id: I, req: T) -> Result<mpsc::UnboundedReceiver<Result<R, E>>> {
         let (tx, rx) = mpsc::unbounded();
-
-        match self.0.unbounded_send((req, ReplySender::Stream(tx))) {
-            Ok(()) => Either::Left(rx.map(|o| Ok(o))),
-            Err(e) => Either::Right(stream::once(async {
-                Err(Error::FailedToSubscribeNewRequest)
-            })),
-        }
+        self.0
+            .unbounded_send((req, Some((id, ReplySender::Stream(tx)))))
+            .map_err(|_| Error::FailedToSubscribeNewRequest)?;
+        Ok(rx)
     }
 }
 
-impl<T, R, E> Clone for Commander<T, R, E> {
+impl<I, T, R, E> Clone for Commander<I, T, R, E> {
     fn clone(&self) -> Self {
         Commander(self.0.clone())
     }
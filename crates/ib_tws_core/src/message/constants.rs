@@ -76,7 +76,9 @@ pub const MIN_SERVER_VER_BOND_ISSUERID: i32 = 176;
 
 pub const MIN_VERSION: i32 = MIN_SERVER_VER_MKT_DEPTH_PRIM_EXCHANGE;
 // envelope encoding, applicable to useV100Plus mode only
-pub const MAX_VERSION: i32 = MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT;
+// ipo prices, instrument timezone and hmds market data in shares change field contents only;
+// historical tick and histogram sizes are decimals, fractional for crypto aggregated trades
+pub const MAX_VERSION: i32 = MIN_SERVER_VER_BOND_ISSUERID;
 
 // command id
 pub const REQ_MKT_DATA: i32 = 1;
@@ -153,6 +155,12 @@ pub const CANCEL_PNL_SINGLE: i32 = 95;
 pub const REQ_HISTORICAL_TICKS: i32 = 96;
 pub const REQ_TICK_BY_TICK_DATA: i32 = 97;
 pub const CANCEL_TICK_BY_TICK_DATA: i32 = 98;
+pub const REQ_COMPLETED_ORDERS: i32 = 99;
+pub const REQ_WSH_META_DATA: i32 = 100;
+pub const CANCEL_WSH_META_DATA: i32 = 101;
+pub const REQ_WSH_EVENT_DATA: i32 = 102;
+pub const CANCEL_WSH_EVENT_DATA: i32 = 103;
+pub const REQ_USER_INFO: i32 = 104;
 
 pub const API_HEAD: &[u8] = b"API\0";
 pub const MIN_SERVER_VERSION: i32 = 100;
@@ -234,6 +242,14 @@ pub const HISTORICAL_TICKS: i32 = 96;
 pub const HISTORICAL_TICKS_BID_ASK: i32 = 97;
 pub const HISTORICAL_TICKS_LAST: i32 = 98;
 pub const TICK_BY_TICK: i32 = 99;
+pub const ORDER_BOUND: i32 = 100;
+pub const COMPLETED_ORDER: i32 = 101;
+pub const COMPLETED_ORDERS_END: i32 = 102;
+pub const REPLACE_FA_END: i32 = 103;
+pub const WSH_META_DATA: i32 = 104;
+pub const WSH_EVENT_DATA: i32 = 105;
+pub const HISTORICAL_SCHEDULE: i32 = 106;
+pub const USER_INFO: i32 = 107;
 
 pub const MAX_MSG_LENGTH: usize = 0xff_ffff;
 pub const REDIRECT_MSG_ID: i32 = -1;
@@ -266,6 +282,7 @@ pub const OPCODE_REQ_FAMILY_CODES: i32 = 80;
 pub const OPCODE_REQ_MKT_DEPTH_EXCHANGES: i32 = 82;
 pub const OPCODE_REQ_NEWS_PROVIDERS: i32 = 85;
 pub const OPCODE_REQ_MARKET_RULE: i32 = 91;
+pub const OPCODE_REQ_COMPLETED_ORDERS: i32 = 99;
 //TODO
 pub const OPCODE_DISPLAY_GROUP_UPDATED_MSG: i32 = 100;
 pub const OPCODE_ERR: i32 = 101;
@@ -33,7 +33,8 @@ use super::request::*;
 use super::reroute::*;
 use super::response::*;
 use super::scanner::*;
-use super::wire::TwsWireDecoder;
+use super::wsh::*;
+use super::wire::{TwsWireDecoder, TwsWireEncoder};
 
 #[derive(Debug)]
 pub enum DispatchId {
@@ -50,6 +51,8 @@ pub struct Context {
     pub extra_auth: bool,
     pub next_valid_id: i32,
     pub accounts: String,
+    /// Skip frames that cannot be decoded instead of failing, see `decode_message`.
+    pub tolerant: bool,
 }
 
 impl Context {
@@ -60,6 +63,7 @@ impl Context {
             extra_auth: false,
             next_valid_id: -1,
             accounts: "".to_string(),
+            tolerant: false,
         }
     }
 
@@ -83,6 +87,14 @@ impl Context {
         self.extra_auth = extra_auth
     }
 
+    pub fn is_tolerant(&self) -> bool {
+        self.tolerant
+    }
+
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.tolerant = tolerant
+    }
+
     pub fn register_request(&mut self, request: &Request) {}
 
     pub fn process_response(&mut self, response: &Response) {}
@@ -243,19 +255,59 @@ impl Context {
             Request::CancelTickByTickData(ref req) => {
                 encode_cancel_tick_by_tick_data(self, &mut buf, req)
             }
+            Request::ReqCompletedOrders(ref req) => {
+                encode_req_completed_orders(self, &mut buf, req)
+            }
+            Request::ReqWshMetaData(ref req) => encode_req_wsh_meta_data(self, &mut buf, req),
+            Request::CancelWshMetaData(ref req) => {
+                encode_cancel_wsh_meta_data(self, &mut buf, req)
+            }
+            Request::ReqWshEventData(ref req) => encode_req_wsh_event_data(self, &mut buf, req),
+            Request::CancelWshEventData(ref req) => {
+                encode_cancel_wsh_event_data(self, &mut buf, req)
+            }
+            Request::ReqUserInfo(ref req) => encode_req_user_info(self, &mut buf, req),
         };
 
         //println!("request {:?}\n buf:{:?}", request, buf);
         match request {
             Ok(_) => Ok(buf),
-            Err(_) => Err(io::Error::new(io::ErrorKind::Other, "encoder error")),
+            Err(error) => Err(io::Error::new(io::ErrorKind::InvalidInput, error)),
         }
     }
 
+    /// Decode a single frame.
+    ///
+    /// In tolerant mode, a frame with an unknown message id or fields that cannot be decoded
+    /// is returned as `Response::UnknownMsg` holding its raw fields, so that the connection
+    /// survives messages we don't support yet.
+    /// # Errors
+    /// Returns an error naming the message id if the frame cannot be decoded outside of
+    /// tolerant mode, or if the handshake ack is malformed.
     #[instrument(skip(self, buf), err)]
     pub fn decode_message(&mut self, buf: &mut BytesMut) -> Result<Response, io::Error> {
-        let (response, _id) = self.parse_message(buf)?;
-        Ok(response)
+        if !self.tolerant || self.server_version < 0 {
+            let (response, _id) = self.parse_message(buf)?;
+            return Ok(response);
+        }
+
+        let mut frame = buf.clone();
+        match self.parse_message(buf) {
+            Ok((response, _id)) => Ok(response),
+            Err(error) => {
+                let mut fields = Vec::new();
+                while !frame.is_empty() {
+                    match TwsWireDecoder::split(&mut frame) {
+                        Ok(field) => fields.push(String::from_utf8_lossy(&field).into_owned()),
+                        Err(_) => break,
+                    }
+                }
+                *buf = frame;
+                let msg_id = fields.first().and_then(|id| id.parse().ok()).unwrap_or(-1);
+                warn!(msg_id, %error, ?fields, "skipping undecodable message");
+                Ok(Response::UnknownMsg(UnknownMsg { msg_id, fields }))
+            }
+        }
     }
 
     /*pub fn parse_message_warp(&mut self, buf: &mut BytesMut) -> Result<(Response, i32), io::Error> {
@@ -278,12 +330,24 @@ impl Context {
         } else {
             let msg_id = buf.read_int()?;
             //println!("msg_id: {}", msg_id);
+            self.parse_message_body(msg_id, buf).map_err(|error| {
+                io::Error::new(error.kind(), format!("msg id {}: {}", msg_id, error))
+            })
+        }
+    }
+
+    fn parse_message_body(
+        &mut self,
+        msg_id: i32,
+        buf: &mut BytesMut,
+    ) -> Result<(Response, i32), io::Error> {
+        {
             let result = match msg_id {
                 TICK_PRICE => decode_tick_price_msg(self, buf)?,
                 TICK_SIZE => decode_tick_size_msg(self, buf)?,
                 ORDER_STATUS => decode_order_status_msg(self, buf)?,
                 ERR_MSG => decode_err_msg(self, buf)?,
-                OPEN_ORDER => decode_open_order_end_msg(self, buf)?,
+                OPEN_ORDER => decode_open_order_msg(self, buf)?,
                 ACCT_VALUE => decode_acct_value_msg(self, buf)?,
                 PORTFOLIO_VALUE => decode_portfolio_value_msg(self, buf)?,
                 ACCT_UPDATE_TIME => decode_acct_update_time_msg(self, buf)?,
@@ -358,7 +422,7 @@ impl Context {
                 HISTORICAL_NEWS => decode_historical_news_msg(self, buf)?,
                 HISTORICAL_NEWS_END => decode_historical_news_end_msg(self, buf)?,
                 HEAD_TIMESTAMP => decode_head_timestamp_msg(self, buf)?,
-                HISTOGRAM_DATA => decode_historical_data_msg(self, buf)?,
+                HISTOGRAM_DATA => decode_histogram_data_msg(self, buf)?,
                 HISTORICAL_DATA_UPDATE => decode_historical_data_update_msg(self, buf)?,
                 REROUTE_MKT_DATA_REQ => decode_reroute_mkt_data_req(self, buf)?,
                 REROUTE_MKT_DEPTH_REQ => decode_reroute_mkt_depth_req(self, buf)?,
@@ -369,6 +433,13 @@ impl Context {
                 HISTORICAL_TICKS_BID_ASK => decode_historical_ticks_bid_ask(self, buf)?,
                 HISTORICAL_TICKS_LAST => decode_historical_ticks_last(self, buf)?,
                 TICK_BY_TICK => decode_tick_by_tick_msg(self, buf)?,
+                COMPLETED_ORDER => decode_completed_order_msg(self, buf)?,
+                COMPLETED_ORDERS_END => decode_completed_orders_end_msg(self, buf)?,
+                WSH_META_DATA => decode_wsh_meta_data_msg(self, buf)?,
+                WSH_EVENT_DATA => decode_wsh_event_data_msg(self, buf)?,
+                REPLACE_FA_END => decode_replace_fa_end_msg(self, buf)?,
+                HISTORICAL_SCHEDULE => decode_historical_schedule_msg(self, buf)?,
+                USER_INFO => decode_user_info_msg(self, buf)?,
                 _ => {
                     return Err(io::Error::new(
                         io::ErrorKind::InvalidData,
@@ -379,4 +450,412 @@ impl Context {
             Ok(result)
         }
     }
+
+    /// Decode a single frame sent by a client, the server side counterpart of `decode_message`.
+    ///
+    /// Until the server version is set by encoding a `Response::HandshakeAck`, the frame is
+    /// expected to hold the handshake, the `API\0` prefix already stripped.
+    /// # Errors
+    /// Returns an error naming the message id if the frame cannot be decoded.
+    #[instrument(skip(self, buf), err)]
+    pub fn decode_request(&mut self, buf: &mut BytesMut) -> Result<Request, io::Error> {
+        if self.server_version < 0 {
+            return Ok(Request::Handshake(decode_handshake(self, buf)?));
+        }
+
+        let msg_id = buf.read_int()?;
+        self.parse_request_body(msg_id, buf).map_err(|error| {
+            io::Error::new(error.kind(), format!("msg id {}: {}", msg_id, error))
+        })
+    }
+
+    fn parse_request_body(&mut self, msg_id: i32, buf: &mut BytesMut) -> Result<Request, io::Error> {
+        let request = match msg_id {
+            START_API => decode_start_api(self, buf)?,
+            REQ_MKT_DATA => decode_req_mkt_data(self, buf)?,
+            CANCEL_MKT_DATA => decode_cancel_mkt_data(self, buf)?,
+            PLACE_ORDER => decode_place_order(self, buf)?,
+            CANCEL_ORDER => decode_cancel_order(self, buf)?,
+            REQ_OPEN_ORDERS => decode_req_open_orders(self, buf)?,
+            REQ_ACCOUNT_DATA => decode_req_account_updates(self, buf)?,
+            REQ_EXECUTIONS => decode_req_executions(self, buf)?,
+            REQ_IDS => decode_req_ids(self, buf)?,
+            REQ_CONTRACT_DATA => decode_req_contract_details(self, buf)?,
+            REQ_MKT_DEPTH => decode_req_mkt_depth(self, buf)?,
+            CANCEL_MKT_DEPTH => decode_cancel_mkt_depth(self, buf)?,
+            REQ_NEWS_BULLETINS => decode_req_news_bulletins(self, buf)?,
+            CANCEL_NEWS_BULLETINS => decode_cancel_news_bulletins(self, buf)?,
+            SET_SERVER_LOGLEVEL => decode_set_server_log_level(self, buf)?,
+            REQ_AUTO_OPEN_ORDERS => decode_req_auto_open_orders(self, buf)?,
+            REQ_ALL_OPEN_ORDERS => decode_req_all_open_orders(self, buf)?,
+            REQ_MANAGED_ACCTS => decode_req_managed_accts(self, buf)?,
+            REQ_FA => decode_request_fa(self, buf)?,
+            REPLACE_FA => decode_replace_fa(self, buf)?,
+            REQ_HISTORICAL_DATA => decode_req_historical_data(self, buf)?,
+            EXERCISE_OPTIONS => decode_exercise_options(self, buf)?,
+            REQ_SCANNER_SUBSCRIPTION => decode_req_scanner_subscription(self, buf)?,
+            CANCEL_SCANNER_SUBSCRIPTION => decode_cancel_scanner_subscription(self, buf)?,
+            REQ_SCANNER_PARAMETERS => decode_req_scanner_parameters(self, buf)?,
+            CANCEL_HISTORICAL_DATA => decode_cancel_historical_data(self, buf)?,
+            REQ_CURRENT_TIME => decode_req_current_time(self, buf)?,
+            REQ_REAL_TIME_BARS => decode_req_realtime_bars(self, buf)?,
+            CANCEL_REAL_TIME_BARS => decode_cancel_realtime_bars(self, buf)?,
+            REQ_FUNDAMENTAL_DATA => decode_req_fundamental_data(self, buf)?,
+            CANCEL_FUNDAMENTAL_DATA => decode_cancel_fundamental_data(self, buf)?,
+            REQ_CALC_IMPLIED_VOLAT => decode_calculate_implied_volatility(self, buf)?,
+            REQ_CALC_OPTION_PRICE => decode_calculate_option_price(self, buf)?,
+            CANCEL_CALC_IMPLIED_VOLAT => decode_cancel_calculate_implied_volatility(self, buf)?,
+            CANCEL_CALC_OPTION_PRICE => decode_cancel_calculate_option_price(self, buf)?,
+            REQ_GLOBAL_CANCEL => decode_req_global_cancel(self, buf)?,
+            REQ_MARKET_DATA_TYPE => decode_req_market_data_type(self, buf)?,
+            REQ_POSITIONS => decode_req_positions(self, buf)?,
+            REQ_ACCOUNT_SUMMARY => decode_req_account_summary(self, buf)?,
+            CANCEL_ACCOUNT_SUMMARY => decode_cancel_account_summary(self, buf)?,
+            CANCEL_POSITIONS => decode_cancel_positions(self, buf)?,
+            VERIFY_REQUEST => decode_verify_request(self, buf)?,
+            VERIFY_MESSAGE => decode_verify_message(self, buf)?,
+            QUERY_DISPLAY_GROUPS => decode_query_display_groups(self, buf)?,
+            SUBSCRIBE_TO_GROUP_EVENTS => decode_subscribe_to_group_event(self, buf)?,
+            UPDATE_DISPLAY_GROUP => decode_update_display_group(self, buf)?,
+            UNSUBSCRIBE_FROM_GROUP_EVENTS => decode_unsubscribe_from_group_events(self, buf)?,
+            VERIFY_AND_AUTH_REQUEST => decode_verify_and_auth_request(self, buf)?,
+            VERIFY_AND_AUTH_MESSAGE => decode_verify_and_auth_message(self, buf)?,
+            REQ_POSITIONS_MULTI => decode_req_positions_multi(self, buf)?,
+            CANCEL_POSITIONS_MULTI => decode_cancel_positions_multi(self, buf)?,
+            REQ_ACCOUNT_UPDATES_MULTI => decode_req_account_updates_multi(self, buf)?,
+            CANCEL_ACCOUNT_UPDATES_MULTI => decode_cancel_account_updates_multi(self, buf)?,
+            REQ_SEC_DEF_OPT_PARAMS => decode_req_sec_def_opt_params(self, buf)?,
+            REQ_SOFT_DOLLAR_TIERS => decode_req_soft_dollar_tiers(self, buf)?,
+            REQ_FAMILY_CODES => decode_req_family_codes(self, buf)?,
+            REQ_MATCHING_SYMBOLS => decode_matching_symbol(self, buf)?,
+            REQ_MKT_DEPTH_EXCHANGES => decode_req_mkt_depth_exchanges(self, buf)?,
+            REQ_SMART_COMPONENTS => decode_req_smart_components(self, buf)?,
+            REQ_NEWS_ARTICLE => decode_req_news_article(self, buf)?,
+            REQ_NEWS_PROVIDERS => decode_req_news_provider(self, buf)?,
+            REQ_HISTORICAL_NEWS => decode_req_historical_news(self, buf)?,
+            REQ_HEAD_TIMESTAMP => decode_req_head_timestamp(self, buf)?,
+            REQ_HISTOGRAM_DATA => decode_req_histogram_data(self, buf)?,
+            CANCEL_HISTOGRAM_DATA => decode_cancel_histogram_data(self, buf)?,
+            CANCEL_HEAD_TIMESTAMP => decode_cancel_head_timestamp(self, buf)?,
+            REQ_MARKET_RULE => decode_req_market_rule(self, buf)?,
+            REQ_PNL => decode_req_pnl(self, buf)?,
+            CANCEL_PNL => decode_cancel_pnl(self, buf)?,
+            REQ_PNL_SINGLE => decode_req_pnl_single(self, buf)?,
+            CANCEL_PNL_SINGLE => decode_cancel_pnl_single(self, buf)?,
+            REQ_HISTORICAL_TICKS => decode_req_historical_ticks(self, buf)?,
+            REQ_TICK_BY_TICK_DATA => decode_req_tick_by_tick_data(self, buf)?,
+            CANCEL_TICK_BY_TICK_DATA => decode_cancel_tick_by_tick_data(self, buf)?,
+            REQ_COMPLETED_ORDERS => decode_req_completed_orders(self, buf)?,
+            REQ_WSH_META_DATA => decode_req_wsh_meta_data(self, buf)?,
+            CANCEL_WSH_META_DATA => decode_cancel_wsh_meta_data(self, buf)?,
+            REQ_WSH_EVENT_DATA => decode_req_wsh_event_data(self, buf)?,
+            CANCEL_WSH_EVENT_DATA => decode_cancel_wsh_event_data(self, buf)?,
+            REQ_USER_INFO => decode_req_user_info(self, buf)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown message id {}", msg_id),
+                ))
+            }
+        };
+        Ok(request)
+    }
+
+    /// Encode a response as sent by a server of `server_version`, the server side
+    /// counterpart of `encode_message`.
+    ///
+    /// Encoding a `Response::HandshakeAck` sets the server version used by the following
+    /// frames. A `Response::UnknownMsg` is written back as its raw fields.
+    /// # Errors
+    /// Returns an error if the response cannot be sent to a server of this version.
+    pub fn encode_response(&mut self, response: &Response) -> Result<BytesMut, io::Error> {
+        let mut buf = BytesMut::new();
+        let result = match response {
+            Response::HandshakeAck(ref msg) => {
+                let result = encode_handshake_ack(self, &mut buf, msg);
+                self.server_version = msg.server_version;
+                result
+            }
+            Response::HistoricalTickLastMsg(ref msg) => {
+                encode_historical_ticks_last(self, &mut buf, msg)
+            }
+            Response::HistoricalTickBidAskMsg(ref msg) => {
+                encode_historical_ticks_bid_ask(self, &mut buf, msg)
+            }
+            Response::HistoricalTicksMsg(ref msg) => encode_historical_ticks(self, &mut buf, msg),
+            Response::MarketRule(ref msg) => encode_market_rule(self, &mut buf, msg),
+            Response::RerouteMktDepthReq(ref msg) => {
+                encode_reroute_mkt_depth_req(self, &mut buf, msg)
+            }
+            Response::RerouteMktDataReq(ref msg) => encode_reroute_mkt_data_req(self, &mut buf, msg),
+            Response::HistoricalDataUpdateMsg(ref msg) => {
+                encode_historical_data_update_msg(self, &mut buf, msg)
+            }
+            Response::PnlSingleMsg(ref msg) => encode_pnl_single_msg(self, &mut buf, msg),
+            Response::PnlMsg(ref msg) => encode_pnl_msg(self, &mut buf, msg),
+            Response::HistogramDataMsg(ref msg) => encode_histogram_data_msg(self, &mut buf, msg),
+            Response::HistoricalNewsEndMsg(ref msg) => {
+                encode_historical_news_end_msg(self, &mut buf, msg)
+            }
+            Response::HistoricalNewsMsg(ref msg) => encode_historical_news_msg(self, &mut buf, msg),
+            Response::NewsArticleMsg(ref msg) => encode_news_article_msg(self, &mut buf, msg),
+            Response::NewsProviderMsg(ref msg) => encode_news_providers_msg(self, &mut buf, msg),
+            Response::TickNewsMsg(ref msg) => encode_tick_news_msg(self, &mut buf, msg),
+            Response::HeadTimestampMsg(ref msg) => encode_head_timestamp_msg(self, &mut buf, msg),
+            Response::MktDepthExchangesMsg(ref msg) => {
+                encode_mkt_depth_exchanges_msg(self, &mut buf, msg)
+            }
+            Response::SymbolSamplesMsg(ref msg) => encode_symbol_sample_msg(self, &mut buf, msg),
+            Response::FamilyCodesMsg(ref msg) => encode_family_codes_msg(self, &mut buf, msg),
+            Response::SoftDollarTiersMsg(ref msg) => {
+                encode_soft_dollar_tiers_msg(self, &mut buf, msg)
+            }
+            Response::SecurityDefinitionOptionalParameterEndMsg(ref msg) => {
+                encode_security_definition_optional_parameter_end_msg(self, &mut buf, msg)
+            }
+            Response::SecurityDefinitionOptionalParameterMsg(ref msg) => {
+                encode_security_definition_optional_parameter_msg(self, &mut buf, msg)
+            }
+            Response::VerifyAndAuthCompletedMsg(ref msg) => {
+                encode_verify_and_auth_completed_msg(self, &mut buf, msg)
+            }
+            Response::VerifyAndAuthMessageMsg(ref msg) => {
+                encode_verify_and_auth_message_msg(self, &mut buf, msg)
+            }
+            Response::DisplayGroupUpdatedMsg(ref msg) => {
+                encode_display_group_updated_msg(self, &mut buf, msg)
+            }
+            Response::DisplayGroupListMsg(ref msg) => {
+                encode_display_group_list_msg(self, &mut buf, msg)
+            }
+            Response::VerifyCompletedMsg(ref msg) => encode_verify_completed_msg(self, &mut buf, msg),
+            Response::VerifyMessageApiMsg(ref msg) => {
+                encode_verify_message_api_msg(self, &mut buf, msg)
+            }
+            Response::CommissionReportMsg(ref msg) => {
+                encode_commission_report_msg(self, &mut buf, msg)
+            }
+            Response::MarketDataTypeMsg(ref msg) => encode_market_data_type_msg(self, &mut buf, msg),
+            Response::TickSnapshotEndMsg(ref msg) => {
+                encode_tick_snapshot_end_msg(self, &mut buf, msg)
+            }
+            Response::DeltaNeutralValidationMsg(ref msg) => {
+                encode_delta_neutral_validation_msg(self, &mut buf, msg)
+            }
+            Response::ExecutionDataEndMsg(ref msg) => {
+                encode_execution_data_end_msg(self, &mut buf, msg)
+            }
+            Response::AcctDownloadEndMsg(ref msg) => {
+                encode_acct_download_end_msg(self, &mut buf, msg)
+            }
+            Response::OpenOrderEndMsg(ref msg) => encode_open_order_end_msg(self, &mut buf, msg),
+            Response::ContractDataEndMsg(ref msg) => {
+                encode_contract_data_end_msg(self, &mut buf, msg)
+            }
+            Response::FundamentalDataMsg(ref msg) => {
+                encode_fundamental_data_msg(self, &mut buf, msg)
+            }
+            Response::RealTimeBarsMsg(ref msg) => encode_realtime_bars_msg(self, &mut buf, msg),
+            Response::CurrentTimeMsg(ref msg) => encode_current_time_msg(self, &mut buf, msg),
+            Response::ScannerDataMsg(ref msg) => encode_scanner_data_msg(self, &mut buf, msg),
+            Response::ScannerParametersMsg(ref msg) => {
+                encode_scanner_parameters_msg(self, &mut buf, msg)
+            }
+            Response::HistoricalDataMsg(ref msg) => encode_historical_data_msg(self, &mut buf, msg),
+            Response::ReceiveFaMsg(ref msg) => encode_receive_fa_msg(self, &mut buf, msg),
+            Response::ManagedAcctsMsg(ref msg) => {
+                self.accounts = msg.accounts.clone();
+                encode_managed_accts_msg(self, &mut buf, msg)
+            }
+            Response::NewsBulletinsMsg(ref msg) => encode_news_bulletins_msg(self, &mut buf, msg),
+            Response::MarketDepthL2Msg(ref msg) => encode_market_depth_l2_msg(self, &mut buf, msg),
+            Response::MarketDepthMsg(ref msg) => encode_market_depth_msg(self, &mut buf, msg),
+            Response::ExecutionDataMsg(ref msg) => encode_execution_data_msg(self, &mut buf, msg),
+            Response::BondContractDataMsg(ref msg) => {
+                encode_bond_contract_data_msg(self, &mut buf, msg)
+            }
+            Response::ContractDataMsg(ref msg) => encode_contract_data_msg(self, &mut buf, msg),
+            Response::NextValidIdMsg(ref msg) => {
+                self.next_valid_id = msg.order_id;
+                encode_next_valid_id_msg(self, &mut buf, msg)
+            }
+            Response::OpenOrderMsg(ref msg) => encode_open_order_msg(self, &mut buf, msg),
+            Response::ErrMsgMsg(ref msg) => encode_err_msg(self, &mut buf, msg),
+            Response::AcctUpdateTimeMsg(ref msg) => encode_acct_update_time_msg(self, &mut buf, msg),
+            Response::PortfolioValueMsg(ref msg) => encode_portfolio_value_msg(self, &mut buf, msg),
+            Response::AcctValueMsg(ref msg) => encode_acct_value_msg(self, &mut buf, msg),
+            Response::OrderStatusMsg(ref msg) => encode_order_status_msg(self, &mut buf, msg),
+            Response::TickEFPMsg(ref msg) => encode_tick_efp_msg(self, &mut buf, msg),
+            Response::TickStringMsg(ref msg) => encode_tick_string_msg(self, &mut buf, msg),
+            Response::TickGenericMsg(ref msg) => encode_tick_generic_msg(self, &mut buf, msg),
+            Response::TickOptionComputationMsg(ref msg) => {
+                encode_tick_option_computation_msg(self, &mut buf, msg)
+            }
+            Response::AccountSummaryEndMsg(ref msg) => {
+                encode_account_summary_end_msg(self, &mut buf, msg)
+            }
+            Response::AccountSummaryMsg(ref msg) => encode_account_summary_msg(self, &mut buf, msg),
+            Response::PositionEndMsg(ref msg) => encode_position_end_msg(self, &mut buf, msg),
+            Response::PositionMsg(ref msg) => encode_position_msg(self, &mut buf, msg),
+            Response::TickSizeMsg(ref msg) => encode_tick_size_msg(self, &mut buf, msg),
+            Response::TickPriceMsg(ref msg) => encode_tick_price_msg(self, &mut buf, msg),
+            Response::PositionMultiMsg(ref msg) => encode_position_multi_msg(self, &mut buf, msg),
+            Response::PositionMultiEndMsg(ref msg) => {
+                encode_position_multi_end_msg(self, &mut buf, msg)
+            }
+            Response::AccountUpdateMultiMsg(ref msg) => {
+                encode_account_update_multi_msg(self, &mut buf, msg)
+            }
+            Response::AccountUpdateMultiEndMsg(ref msg) => {
+                encode_account_update_multi_end_msg(self, &mut buf, msg)
+            }
+            Response::TickReqParamsMsg(ref msg) => encode_tick_req_params_msg(self, &mut buf, msg),
+            Response::TickByTickAllLastMsg(ref msg) => {
+                encode_tick_by_tick_all_last_msg(self, &mut buf, msg)
+            }
+            Response::TickByTickBidAskMsg(ref msg) => {
+                encode_tick_by_tick_bid_ask_msg(self, &mut buf, msg)
+            }
+            Response::TickByTickMidPointMsg(ref msg) => {
+                encode_tick_by_tick_mid_point_msg(self, &mut buf, msg)
+            }
+            Response::TickByTickNoneMsg(ref msg) => {
+                encode_tick_by_tick_none_msg(self, &mut buf, msg)
+            }
+            Response::SmartComponentsMsg(ref msg) => {
+                encode_smart_components_msg(self, &mut buf, msg)
+            }
+            Response::CompletedOrderMsg(ref msg) => encode_completed_order_msg(self, &mut buf, msg),
+            Response::CompletedOrdersEndMsg(ref msg) => {
+                encode_completed_orders_end_msg(self, &mut buf, msg)
+            }
+            Response::WshMetaDataMsg(ref msg) => encode_wsh_meta_data_msg(self, &mut buf, msg),
+            Response::WshEventDataMsg(ref msg) => encode_wsh_event_data_msg(self, &mut buf, msg),
+            Response::ReplaceFaEndMsg(ref msg) => encode_replace_fa_end_msg(self, &mut buf, msg),
+            Response::HistoricalScheduleMsg(ref msg) => {
+                encode_historical_schedule_msg(self, &mut buf, msg)
+            }
+            Response::UserInfoMsg(ref msg) => encode_user_info_msg(self, &mut buf, msg),
+            Response::UnknownMsg(ref msg) => {
+                for field in &msg.fields {
+                    buf.push_string(field);
+                }
+                Ok(())
+            }
+        };
+
+        match result {
+            Ok(()) => Ok(buf),
+            Err(error) => Err(io::Error::new(io::ErrorKind::Other, error.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    fn frame(fields: &[&str]) -> BytesMut {
+        let mut buf = BytesMut::new();
+        for field in fields {
+            buf.extend_from_slice(field.as_bytes());
+            buf.extend_from_slice(b"\0");
+        }
+        buf
+    }
+
+    fn context(tolerant: bool) -> Context {
+        let mut ctx = Context::new();
+        ctx.set_server_version(MAX_VERSION);
+        ctx.set_tolerant(tolerant);
+        ctx
+    }
+
+    /// A tick price cut off after its tick type.
+    const TRUNCATED_TICK_PRICE: &[&str] = &["1", "6", "5", "1"];
+
+    #[test]
+    fn tolerant_unknown_message_id() {
+        let response = context(true)
+            .decode_message(&mut frame(&["9999", "a", "b"]))
+            .unwrap();
+        match response {
+            Response::UnknownMsg(msg) => {
+                assert_eq!(msg.msg_id, 9999);
+                assert_eq!(msg.fields, ["9999", "a", "b"]);
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+
+    #[test]
+    fn tolerant_truncated_message() {
+        let response = context(true)
+            .decode_message(&mut frame(TRUNCATED_TICK_PRICE))
+            .unwrap();
+        match response {
+            Response::UnknownMsg(msg) => {
+                assert_eq!(msg.msg_id, TICK_PRICE);
+                assert_eq!(msg.fields, TRUNCATED_TICK_PRICE);
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+
+    #[test]
+    fn tolerant_mode_decodes_known_messages() {
+        let mut error = frame(&["4", "2", "5", "200", "No security definition", ""]);
+        let response = context(true).decode_message(&mut error).unwrap();
+        assert!(
+            matches!(response, Response::ErrMsgMsg(ref err) if err.id == 5 && err.error_code == 200),
+            "{response:?}"
+        );
+    }
+
+    #[test]
+    fn strict_unknown_message_id() {
+        let error = context(false)
+            .decode_message(&mut frame(&["9999", "a", "b"]))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("msg id 9999"), "{error}");
+    }
+
+    #[test]
+    fn fractional_historical_tick_sizes() {
+        let mut ticks = frame(&[
+            "97", "1", "1", "0", "0", "185.5", "185.75", "0.0005", "12.25", "1",
+        ]);
+        match context(false).decode_message(&mut ticks).unwrap() {
+            Response::HistoricalTickBidAskMsg(msg) => {
+                assert_eq!(msg.ticks[0].size_bid, Decimal::new(5, 4));
+                assert_eq!(msg.ticks[0].size_ask, Decimal::new(1225, 2));
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+
+        let mut ticks = frame(&["98", "1", "1", "0", "0", "185.5", "0.25", "PAXOS", "", "1"]);
+        match context(false).decode_message(&mut ticks).unwrap() {
+            Response::HistoricalTickLastMsg(msg) => {
+                assert_eq!(msg.ticks[0].size, Decimal::new(25, 2));
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+
+        let mut histogram = frame(&["89", "1", "1", "185.5", "1.5"]);
+        match context(false).decode_message(&mut histogram).unwrap() {
+            Response::HistogramDataMsg(msg) => assert_eq!(msg.items[0].size, Decimal::new(15, 1)),
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+
+    #[test]
+    fn strict_truncated_message() {
+        let error = context(false)
+            .decode_message(&mut frame(TRUNCATED_TICK_PRICE))
+            .unwrap_err();
+        assert!(error.to_string().contains("msg id 1"), "{error}");
+    }
 }
@@ -15,7 +15,7 @@ use crate::domain::*;
 
 //  IBApi.EWrapper.contractDetails. Once all contracts have been delivered the IBApi.EWrapper.contractDetailsEnd
 pub fn encode_req_contract_details(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
     req: &ReqContractDetails,
 ) -> Result<DispatchId, EncodeError> {
@@ -33,6 +33,10 @@ pub fn encode_req_contract_details(
     buf.push_string(&req.contract.sec_id_type);
     buf.push_string(&req.contract.sec_id);
 
+    if ctx.server_version() >= MIN_SERVER_VER_BOND_ISSUERID {
+        buf.push_string(&req.contract.issuer_id);
+    }
+
     Ok(DispatchId::Oneshot(req.req_id))
 }
 
@@ -52,7 +56,11 @@ pub fn decode_bond_contract_data_msg(
     ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
-    let version = buf.read_int()?;
+    let version = if ctx.server_version() < MIN_SERVER_VER_SIZE_RULES {
+        buf.read_int()?
+    } else {
+        6
+    };
 
     let req_id = if version >= 3 { buf.read_int()? } else { -1 };
 
@@ -64,6 +72,7 @@ pub fn decode_bond_contract_data_msg(
     contract_details.coupon = buf.read_double()?;
     read_last_trade_date(buf, &mut contract_details, true)?;
     contract_details.issue_date = buf.read_string()?;
+    contract_details.ratings = buf.read_string()?;
     contract_details.bond_type = buf.read_string()?;
     contract_details.coupon_type = buf.read_string()?;
     contract_details.convertible = buf.read_bool()?;
@@ -76,7 +85,9 @@ pub fn decode_bond_contract_data_msg(
     contract_details.contract.trading_class = buf.read_string()?;
     contract_details.contract.con_id = buf.read_int()?;
     contract_details.min_tick = buf.read_double()?;
-    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER {
+    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
         contract_details.md_size_multiplier = buf.read_int()?;
     }
     contract_details.order_types = buf.read_string()?;
@@ -108,6 +119,9 @@ pub fn decode_bond_contract_data_msg(
     if ctx.server_version() >= MIN_SERVER_VER_MARKET_RULES {
         contract_details.market_rule_ids = buf.read_string()?;
     }
+    if ctx.server_version() >= MIN_SERVER_VER_SIZE_RULES {
+        read_size_rules(buf, &mut contract_details)?;
+    }
 
     Ok((
         Response::BondContractDataMsg(BondContractDataMsg {
@@ -122,7 +136,11 @@ pub fn decode_contract_data_msg(
     ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
-    let version = buf.read_int()?;
+    let version = if ctx.server_version() < MIN_SERVER_VER_SIZE_RULES {
+        buf.read_int()?
+    } else {
+        8
+    };
 
     let req_id = if version >= 3 { buf.read_int()? } else { -1 };
 
@@ -139,7 +157,9 @@ pub fn decode_contract_data_msg(
     contract_details.contract.trading_class = buf.read_string()?;
     contract_details.contract.con_id = buf.read_int()?;
     contract_details.min_tick = buf.read_double()?;
-    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER {
+    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
         contract_details.md_size_multiplier = buf.read_int()?;
     }
     contract_details.contract.multiplier = buf.read_string()?;
@@ -189,6 +209,18 @@ pub fn decode_contract_data_msg(
     if ctx.server_version() >= MIN_SERVER_VER_REAL_EXPIRATION_DATE {
         contract_details.real_expiration_date = buf.read_string()?;
     }
+    if ctx.server_version() >= MIN_SERVER_VER_STOCK_TYPE {
+        contract_details.stock_type = buf.read_string()?;
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
+        // size min tick, superseded by the size rules
+        let _size_min_tick = buf.read_decimal()?;
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_SIZE_RULES {
+        read_size_rules(buf, &mut contract_details)?;
+    }
 
     Ok((
         Response::ContractDataMsg(ContractDataMsg {
@@ -199,6 +231,13 @@ pub fn decode_contract_data_msg(
     ))
 }
 
+fn read_size_rules(buf: &mut BytesMut, contract_details: &mut ContractDetails) -> Result<(), io::Error> {
+    contract_details.min_size = buf.read_decimal()?;
+    contract_details.size_increment = buf.read_decimal()?;
+    contract_details.suggested_size_increment = buf.read_decimal()?;
+    Ok(())
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 pub fn encode_matching_symbol(
     _ctx: &mut Context,
@@ -213,7 +252,7 @@ pub fn encode_matching_symbol(
 }
 
 pub fn decode_symbol_sample_msg(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
     let req_id = buf.read_int()?;
@@ -235,6 +274,10 @@ pub fn decode_symbol_sample_msg(
         for _ in 0..types_count {
             derivative_sec_types.push(buf.read_string()?);
         }
+        if ctx.server_version() >= MIN_SERVER_VER_BOND_ISSUERID {
+            contract.description = buf.read_string()?;
+            contract.issuer_id = buf.read_string()?;
+        }
         let description = ContractDescription {
             contract,
             derivative_sec_types,
@@ -360,3 +403,308 @@ pub fn decode_delta_neutral_validation_msg(
 }
 
 // helper
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_contract_details(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let mut contract = decode_contract(buf)?;
+    contract.include_expired = buf.read_bool()?;
+    contract.sec_id_type = buf.read_string()?;
+    contract.sec_id = buf.read_string()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_BOND_ISSUERID {
+        contract.issuer_id = buf.read_string()?;
+    }
+
+    Ok(Request::ReqContractDetails(ReqContractDetails {
+        req_id,
+        contract,
+    }))
+}
+
+pub fn encode_contract_data_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ContractDataEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(CONTRACT_DATA_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn encode_bond_contract_data_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &BondContractDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 6;
+
+    let contract_details = &msg.contract_details;
+
+    buf.push_int(BOND_CONTRACT_DATA);
+    if ctx.server_version() < MIN_SERVER_VER_SIZE_RULES {
+        buf.push_int(VERSION);
+    }
+    buf.push_int(msg.req_id);
+
+    buf.push_string(&contract_details.contract.symbol);
+    buf.push_string(&contract_details.contract.sec_type);
+    buf.push_string(&contract_details.cusip);
+    buf.push_double(contract_details.coupon);
+    write_last_trade_date(buf, contract_details, true);
+    buf.push_string(&contract_details.issue_date);
+    buf.push_string(&contract_details.ratings);
+    buf.push_string(&contract_details.bond_type);
+    buf.push_string(&contract_details.coupon_type);
+    buf.push_bool(contract_details.convertible);
+    buf.push_bool(contract_details.callable);
+    buf.push_bool(contract_details.putable);
+    buf.push_string(&contract_details.desc_append);
+    buf.push_string(&contract_details.contract.exchange);
+    buf.push_string(&contract_details.contract.currency);
+    buf.push_string(&contract_details.market_name);
+    buf.push_string(&contract_details.contract.trading_class);
+    buf.push_int(contract_details.contract.con_id);
+    buf.push_double(contract_details.min_tick);
+    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
+        buf.push_int(contract_details.md_size_multiplier);
+    }
+    buf.push_string(&contract_details.order_types);
+    buf.push_string(&contract_details.valid_exchanges);
+    buf.push_string(&contract_details.next_option_date);
+    buf.push_string(&contract_details.next_option_type);
+    buf.push_bool(contract_details.next_option_partial);
+    buf.push_string(&contract_details.notes);
+    buf.push_string(&contract_details.long_name);
+    buf.push_string(&contract_details.ev_rule);
+    buf.push_double(contract_details.ev_multiplier);
+    write_sec_id_list(buf, contract_details);
+    if ctx.server_version() >= MIN_SERVER_VER_AGG_GROUP {
+        buf.push_int(contract_details.agg_group);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_MARKET_RULES {
+        buf.push_string(&contract_details.market_rule_ids);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_SIZE_RULES {
+        write_size_rules(buf, contract_details);
+    }
+
+    Ok(())
+}
+
+pub fn encode_contract_data_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ContractDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 8;
+
+    let contract_details = &msg.contract_details;
+
+    buf.push_int(CONTRACT_DATA);
+    if ctx.server_version() < MIN_SERVER_VER_SIZE_RULES {
+        buf.push_int(VERSION);
+    }
+    buf.push_int(msg.req_id);
+
+    buf.push_string(&contract_details.contract.symbol);
+    buf.push_string(&contract_details.contract.sec_type);
+    write_last_trade_date(buf, contract_details, false);
+    buf.push_double(contract_details.contract.strike);
+    buf.push_string(&contract_details.contract.right);
+    buf.push_string(&contract_details.contract.exchange);
+    buf.push_string(&contract_details.contract.currency);
+    buf.push_string(&contract_details.contract.local_symbol);
+    buf.push_string(&contract_details.market_name);
+    buf.push_string(&contract_details.contract.trading_class);
+    buf.push_int(contract_details.contract.con_id);
+    buf.push_double(contract_details.min_tick);
+    if ctx.server_version() >= MIN_SERVER_VER_MD_SIZE_MULTIPLIER
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
+        buf.push_int(contract_details.md_size_multiplier);
+    }
+    buf.push_string(&contract_details.contract.multiplier);
+    buf.push_string(&contract_details.order_types);
+    buf.push_string(&contract_details.valid_exchanges);
+    buf.push_int(contract_details.price_magnifier);
+    buf.push_int(contract_details.under_con_id);
+    buf.push_string(&contract_details.long_name);
+    buf.push_string(&contract_details.contract.primary_exch);
+    buf.push_string(&contract_details.contract_month);
+    buf.push_string(&contract_details.industry);
+    buf.push_string(&contract_details.category);
+    buf.push_string(&contract_details.sub_category);
+    buf.push_string(&contract_details.timezone_id);
+    buf.push_string(&contract_details.trading_hours);
+    buf.push_string(&contract_details.liquid_hours);
+    buf.push_string(&contract_details.ev_rule);
+    buf.push_double(contract_details.ev_multiplier);
+    write_sec_id_list(buf, contract_details);
+    if ctx.server_version() >= MIN_SERVER_VER_AGG_GROUP {
+        buf.push_int(contract_details.agg_group);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_UNDERLYING_INFO {
+        buf.push_string(&contract_details.under_symbol);
+        buf.push_string(&contract_details.under_sec_type);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_MARKET_RULES {
+        buf.push_string(&contract_details.market_rule_ids);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_REAL_EXPIRATION_DATE {
+        buf.push_string(&contract_details.real_expiration_date);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_STOCK_TYPE {
+        buf.push_string(&contract_details.stock_type);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT
+        && ctx.server_version() < MIN_SERVER_VER_SIZE_RULES
+    {
+        // size min tick, superseded by the size rules
+        buf.push_decimal(contract_details.min_size);
+    }
+    if ctx.server_version() >= MIN_SERVER_VER_SIZE_RULES {
+        write_size_rules(buf, contract_details);
+    }
+
+    Ok(())
+}
+
+fn write_sec_id_list(buf: &mut BytesMut, contract_details: &ContractDetails) {
+    buf.push_int(contract_details.sec_id_list.len() as i32);
+    for tag_value in &contract_details.sec_id_list {
+        buf.push_string(&tag_value.tag);
+        buf.push_string(&tag_value.value);
+    }
+}
+
+fn write_size_rules(buf: &mut BytesMut, contract_details: &ContractDetails) {
+    buf.push_decimal(contract_details.min_size);
+    buf.push_decimal(contract_details.size_increment);
+    buf.push_decimal(contract_details.suggested_size_increment);
+}
+
+pub fn decode_matching_symbol(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let pattern = buf.read_string()?;
+
+    Ok(Request::MatchingSymbol(MatchingSymbol { req_id, pattern }))
+}
+
+pub fn encode_symbol_sample_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &SymbolSamplesMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(SYMBOL_SAMPLES);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.contract_descriptions.len() as i32);
+
+    for description in &msg.contract_descriptions {
+        let contract = &description.contract;
+        buf.push_int(contract.con_id);
+        buf.push_string(&contract.symbol);
+        buf.push_string(&contract.sec_type);
+        buf.push_string(&contract.primary_exch);
+        buf.push_string(&contract.currency);
+
+        buf.push_int(description.derivative_sec_types.len() as i32);
+        for sec_type in &description.derivative_sec_types {
+            buf.push_string(sec_type);
+        }
+        if ctx.server_version() >= MIN_SERVER_VER_BOND_ISSUERID {
+            buf.push_string(&contract.description);
+            buf.push_string(&contract.issuer_id);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn decode_req_sec_def_opt_params(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let underlying_symbol = buf.read_string()?;
+    let fut_fop_exchange = buf.read_string()?;
+    let underlying_sec_type = buf.read_string()?;
+    let underlying_con_id = buf.read_int()?;
+
+    Ok(Request::ReqSecDefOptParams(ReqSecDefOptParams {
+        req_id,
+        underlying_symbol,
+        fut_fop_exchange,
+        underlying_sec_type,
+        underlying_con_id,
+    }))
+}
+
+pub fn encode_security_definition_optional_parameter_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &SecurityDefinitionOptionalParameterEndMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(SECURITY_DEFINITION_OPTION_PARAMETER_END);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn encode_security_definition_optional_parameter_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &SecurityDefinitionOptionalParameterMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(SECURITY_DEFINITION_OPTION_PARAMETER);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.exchange);
+    buf.push_int(msg.underlying_con_id);
+    buf.push_string(&msg.trading_class);
+    buf.push_string(&msg.multiplier);
+
+    buf.push_int(msg.expirations.len() as i32);
+    for expiration in &msg.expirations {
+        buf.push_string(expiration);
+    }
+
+    buf.push_int(msg.strikes.len() as i32);
+    for strike in &msg.strikes {
+        buf.push_double(strike.into_inner());
+    }
+
+    Ok(())
+}
+
+pub fn encode_delta_neutral_validation_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &DeltaNeutralValidationMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(DELTA_NEUTRAL_VALIDATION);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.delta_neutral_contract.con_id);
+    buf.push_double(msg.delta_neutral_contract.delta);
+    buf.push_double(msg.delta_neutral_contract.price);
+
+    Ok(())
+}
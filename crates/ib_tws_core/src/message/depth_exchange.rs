@@ -78,3 +78,35 @@ pub fn decode_mkt_depth_exchanges_msg(
         OPCODE_REQ_MKT_DEPTH_EXCHANGES,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_mkt_depth_exchanges(
+    _ctx: &mut Context,
+    _buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    Ok(Request::ReqMktDepthExchanges(ReqMktDepthExchanges {}))
+}
+
+pub fn encode_mkt_depth_exchanges_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &MktDepthExchangesMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(MKT_DEPTH_EXCHANGES);
+    buf.push_int(msg.depth_mkt_data_descriptions.len() as i32);
+    for desc in &msg.depth_mkt_data_descriptions {
+        buf.push_string(&desc.exchange);
+        buf.push_string(&desc.sec_type);
+        if ctx.server_version() >= MIN_SERVER_VER_SERVICE_DATA_TYPE {
+            buf.push_string(&desc.listing_exch);
+            buf.push_string(&desc.service_data_type);
+            buf.push_int_max(desc.agg_group);
+        } else {
+            buf.push_bool(desc.service_data_type == "Deep2");
+        }
+    }
+
+    Ok(())
+}
@@ -1,14 +1,22 @@
-//use super::identifiable::Identifiable;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
 
 use futures::channel::{mpsc, oneshot};
 
+use super::commander::ReplySender;
+
+/// Routes replies to the channel registered for their id.
+///
+/// Stream routes stay registered until their receiver is dropped, oneshot routes are removed
+/// by the first reply. Routes whose receiver is gone are dropped by the next reply for them,
+/// or by `prune_every`.
 #[derive(Debug)]
 pub struct Dispatcher<I: Hash + Eq, R, E> {
     oneshot_reply: HashMap<I, oneshot::Sender<Result<R, E>>>,
     stream_reply: HashMap<I, mpsc::UnboundedSender<Result<R, E>>>,
+    /// Calls of `prune_every` since the last pruning.
+    since_prune: usize,
 }
 
 impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
@@ -16,6 +24,18 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         Dispatcher {
             oneshot_reply: HashMap::new(),
             stream_reply: HashMap::new(),
+            since_prune: 0,
+        }
+    }
+
+    pub(crate) fn register(&mut self, id: I, reply: ReplySender<R, E>) {
+        match reply {
+            ReplySender::Oneshot(tx) => {
+                self.register_oneshot(id, tx);
+            }
+            ReplySender::Stream(tx) => {
+                self.register_stream(id, tx);
+            }
         }
     }
 
@@ -37,6 +57,19 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         self.stream_reply.insert(id, tx)
     }
 
+    /// Drop the routes whose receiver is gone, once every `interval` calls. A route is also
+    /// dropped when a reply can't be delivered, this catches the ones no reply comes for,
+    /// e.g. requests which timed out.
+    pub(crate) fn prune_every(&mut self, interval: usize) {
+        self.since_prune += 1;
+        if self.since_prune < interval {
+            return;
+        }
+        self.since_prune = 0;
+        self.oneshot_reply.retain(|_, tx| !tx.is_canceled());
+        self.stream_reply.retain(|_, tx| !tx.is_closed());
+    }
+
     #[inline(always)]
     pub(crate) fn container_in_oneshot(&self, id: &I) -> bool {
         self.oneshot_reply.contains_key(id)
@@ -47,11 +80,6 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         self.stream_reply.contains_key(id)
     }
 
-    #[inline(always)]
-    pub(crate) fn unregister_oneshot(&mut self, id: &I) -> Option<oneshot::Sender<Result<R, E>>> {
-        self.oneshot_reply.remove(id)
-    }
-
     #[inline(always)]
     pub(crate) fn unregister_stream(
         &mut self,
@@ -60,15 +88,20 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         self.stream_reply.remove(id)
     }
 
+    /// Send a reply to the stream registered for `id`. The route is removed if the receiving
+    /// end is gone, and the reply handed back.
     pub(crate) fn reply_to_stream(
-        &self,
+        &mut self,
         id: &I,
         response: Result<R, E>,
     ) -> Result<(), Result<R, E>> {
         let ret = self.stream_reply.get(id);
         if let Some(tx) = ret {
             match tx.unbounded_send(response) {
-                Err(v) => return Err(v.into_inner()),
+                Err(v) => {
+                    self.unregister_stream(id);
+                    return Err(v.into_inner());
+                }
                 _ => return Ok(()),
             }
         }
@@ -80,7 +113,7 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         id: &I,
         response: Result<R, E>,
     ) -> Result<(), Result<R, E>> {
-        let ret = self.oneshot_reply.remove(&id);
+        let ret = self.oneshot_reply.remove(id);
         if let Some(tx) = ret {
             match tx.send(response) {
                 Err(v) => return Err(v),
@@ -90,6 +123,7 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         Err(response)
     }
 
+    /// Route a reply to the channel registered for `id`, handing it back if there is none.
     pub(crate) fn reply_to(&mut self, id: &I, response: Result<R, E>) -> Result<(), Result<R, E>> {
         if self.container_in_stream(id) {
             return self.reply_to_stream(id, response);
@@ -102,3 +136,30 @@ impl<I: Hash + Eq, R, E> Dispatcher<I, R, E> {
         Err(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_routes_are_pruned() {
+        let mut dispatcher = Dispatcher::<i32, (), ()>::new();
+        let (oneshot_tx, oneshot_rx) = oneshot::channel();
+        dispatcher.register_oneshot(1, oneshot_tx);
+        let (stream_tx, stream_rx) = mpsc::unbounded();
+        dispatcher.register_stream(2, stream_tx);
+        let (live_tx, _live_rx) = mpsc::unbounded();
+        dispatcher.register_stream(3, live_tx);
+        drop(oneshot_rx);
+        drop(stream_rx);
+
+        dispatcher.prune_every(2);
+        assert!(dispatcher.container_in_oneshot(&1));
+        assert!(dispatcher.container_in_stream(&2));
+
+        dispatcher.prune_every(2);
+        assert!(!dispatcher.container_in_oneshot(&1));
+        assert!(!dispatcher.container_in_stream(&2));
+        assert!(dispatcher.container_in_stream(&3));
+    }
+}
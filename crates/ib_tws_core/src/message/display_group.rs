@@ -111,3 +111,86 @@ pub fn encode_update_display_group(
 
     Ok(DispatchId::Oneshot(req.req_id))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_query_display_groups(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::QueryDisplayGroups(QueryDisplayGroups { req_id }))
+}
+
+pub fn encode_display_group_list_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &DisplayGroupListMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(DISPLAY_GROUP_LIST);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.groups);
+
+    Ok(())
+}
+
+pub fn decode_subscribe_to_group_event(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+    let group_id = buf.read_int()?;
+
+    Ok(Request::SubscribeToGroupEvents(SubscribeToGroupEvents {
+        req_id,
+        group_id,
+    }))
+}
+
+pub fn decode_unsubscribe_from_group_events(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::UnsubscribeFromGroupEvents(
+        UbsubscribeFromGroupEvents { req_id },
+    ))
+}
+
+pub fn encode_display_group_updated_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &DisplayGroupUpdatedMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(DISPLAY_GROUP_UPDATED);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.contract_info);
+
+    Ok(())
+}
+
+pub fn decode_update_display_group(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+    let contract_info = buf.read_string()?;
+
+    Ok(Request::UpdateDisplayGroup(UpdateDisplayGroup {
+        req_id,
+        contract_info,
+    }))
+}
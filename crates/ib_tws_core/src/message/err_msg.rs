@@ -4,14 +4,15 @@ use bytes::BytesMut;
 
 use super::constants::*;
 use super::context::Context;
+use super::error::EncodeError;
 use super::response::*;
-use super::wire::TwsWireDecoder;
+use super::wire::{TwsWireDecoder, TwsWireEncoder};
 
 // 	id: the request identifier which generated the error. Note: -1 will indicate a notification and not true error condition.
 // error_code: 	the code identifying the error.
 // error_message: error's description.
 pub fn decode_err_msg(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
     let version = buf.read_int()?;
@@ -22,6 +23,8 @@ pub fn decode_err_msg(
                 id: -1,
                 error_code: -1,
                 error_message: msg,
+                advanced_order_reject_json: String::new(),
+                advanced_order_reject: None,
             }),
             -1,
         ))
@@ -29,13 +32,48 @@ pub fn decode_err_msg(
         let id = buf.read_int()?;
         let error_code = buf.read_int()?;
         let error_message = buf.read_string()?;
+        let advanced_order_reject_json =
+            if ctx.server_version() >= MIN_SERVER_VER_ADVANCED_ORDER_REJECT {
+                buf.read_string()?
+            } else {
+                String::new()
+            };
+        let advanced_order_reject = if advanced_order_reject_json.is_empty() {
+            None
+        } else {
+            // a malformed reject shouldn't cost us the error itself
+            serde_json::from_str(&advanced_order_reject_json)
+                .map_err(|error| warn!(%error, "cannot parse advanced order reject"))
+                .ok()
+        };
         Ok((
             Response::ErrMsgMsg(ErrMsgMsg {
                 id,
                 error_code,
                 error_message,
+                advanced_order_reject_json,
+                advanced_order_reject,
             }),
             OPCODE_ERR,
         ))
     }
 }
+
+pub fn encode_err_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ErrMsgMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 2;
+
+    buf.push_int(ERR_MSG);
+    buf.push_int(VERSION);
+    buf.push_int(msg.id);
+    buf.push_int(msg.error_code);
+    buf.push_string(&msg.error_message);
+    if ctx.server_version() >= MIN_SERVER_VER_ADVANCED_ORDER_REJECT {
+        buf.push_string(&msg.advanced_order_reject_json);
+    }
+
+    Ok(())
+}
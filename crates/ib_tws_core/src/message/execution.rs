@@ -1,6 +1,8 @@
 use std::io;
 
 use bytes::BytesMut;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 
 use super::constants::*;
 use super::context::{Context, DispatchId};
@@ -48,10 +50,8 @@ pub fn decode_commission_report_msg(
     let yield_value = buf.read_double()?;
     let yield_redemption_date = buf.read_int()?;
 
-    // TODO:
-    let req_id = ctx.get_req_id(&exec_id);
-    assert!(req_id.is_some());
-    let req_id = req_id.unwrap();
+    // commission reports of fills no request asked for, e.g. while trading, have no req_id
+    let req_id = ctx.get_req_id(&exec_id).unwrap_or(-1);
     Ok((
         Response::CommissionReportMsg(CommissionReportMsg {
             report: CommissionReport {
@@ -122,9 +122,9 @@ pub fn decode_execution_data_msg(
     exec.side = buf.read_string()?;
 
     exec.shares = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     exec.price = buf.read_double()?;
@@ -138,7 +138,7 @@ pub fn decode_execution_data_msg(
         exec.liquidation = buf.read_int()?;
     }
     if version >= 6 {
-        exec.cum_qty = buf.read_double()?;
+        exec.cum_qty = buf.read_decimal()?;
         exec.avg_price = buf.read_double()?;
     }
     if version >= 8 {
@@ -155,6 +155,7 @@ pub fn decode_execution_data_msg(
     if ctx.server_version() >= MIN_SERVER_VER_LAST_LIQUIDITY {
         exec.last_liquidity = Liquidities::from_code(buf.read_int()?)?;
     }
+    ctx.register(req_id, &exec.exec_id);
 
     Ok((
         Response::ExecutionDataMsg(ExecutionDataMsg {
@@ -165,3 +166,122 @@ pub fn decode_execution_data_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_executions(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    let req_id = buf.read_int()?;
+
+    let filter = ExecutionFilter {
+        client_id: buf.read_int()?,
+        acct_code: buf.read_string()?,
+        time: buf.read_string()?,
+        symbol: buf.read_string()?,
+        sec_type: buf.read_string()?,
+        exchange: buf.read_string()?,
+        side: buf.read_string()?,
+    };
+
+    Ok(Request::ReqExecutions(ReqExecutions { req_id, filter }))
+}
+
+pub fn encode_commission_report_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &CommissionReportMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(COMMISSION_REPORT);
+    buf.push_int(VERSION);
+
+    buf.push_string(&msg.report.exec_id);
+    buf.push_double(msg.report.commission);
+    buf.push_string(&msg.report.currency);
+    buf.push_double(msg.report.realized_pnl);
+    buf.push_double(msg.report.yield_value);
+    buf.push_int(msg.report.yield_redemption_date);
+
+    Ok(())
+}
+
+pub fn encode_execution_data_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ExecutionDataEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(EXECUTION_DATA_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn encode_execution_data_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ExecutionDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 10;
+
+    buf.push_int(EXECUTION_DATA);
+    if ctx.server_version() < MIN_SERVER_VER_LAST_LIQUIDITY {
+        buf.push_int(VERSION);
+    }
+
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.exec.order_id);
+
+    // write contract fields
+    buf.push_int(msg.contract.con_id);
+    buf.push_string(&msg.contract.symbol);
+    buf.push_string(&msg.contract.sec_type);
+    buf.push_string(&msg.contract.last_trade_date_or_contract_month);
+    buf.push_double(msg.contract.strike);
+    buf.push_string(&msg.contract.right);
+    buf.push_string(&msg.contract.multiplier);
+    buf.push_string(&msg.contract.exchange);
+    buf.push_string(&msg.contract.currency);
+    buf.push_string(&msg.contract.local_symbol);
+    buf.push_string(&msg.contract.trading_class);
+
+    let exec = &msg.exec;
+    buf.push_string(&exec.exec_id);
+    buf.push_string(&exec.time);
+    buf.push_string(&exec.acct_number);
+    buf.push_string(&exec.exchange);
+    buf.push_string(&exec.side);
+
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_decimal(exec.shares);
+    } else {
+        buf.push_int(exec.shares.to_i32().unwrap_or_default());
+    }
+
+    buf.push_double(exec.price);
+    buf.push_int(exec.perm_id);
+    buf.push_int(exec.client_id);
+    buf.push_int(exec.liquidation);
+    buf.push_decimal(exec.cum_qty);
+    buf.push_double(exec.avg_price);
+    buf.push_string(&exec.order_ref);
+    buf.push_string(&exec.ev_rule);
+    buf.push_double(exec.ev_multiplier);
+    if ctx.server_version() >= MIN_SERVER_VER_MODELS_SUPPORT {
+        buf.push_string(&exec.model_code);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_LAST_LIQUIDITY {
+        buf.push_int(exec.last_liquidity.clone() as i32);
+    }
+
+    Ok(())
+}
@@ -24,7 +24,7 @@ pub fn encode_request_fa(
 }
 
 pub fn encode_replace_fa(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
     req: &ReplaceFA,
 ) -> Result<DispatchId, EncodeError> {
@@ -35,6 +35,10 @@ pub fn encode_replace_fa(
     buf.push_int(req.fa_data_type);
     buf.push_string(&req.xml);
 
+    if ctx.server_version() >= MIN_SERVER_VER_REPLACE_FA_END {
+        buf.push_int(req.req_id);
+    }
+
     Ok(DispatchId::Global(OPCODE_REPLACE_FA))
 }
 
@@ -52,3 +56,77 @@ pub fn decode_receive_fa_msg(
         OPCODE_REQUEST_FA,
     ))
 }
+
+pub fn decode_replace_fa_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let req_id = buf.read_int()?;
+    let text = buf.read_string()?;
+
+    Ok((
+        Response::ReplaceFaEndMsg(ReplaceFaEndMsg { req_id, text }),
+        req_id,
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_request_fa(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let fa_data_type = buf.read_int()?;
+
+    Ok(Request::RequestFA(RequestFA { fa_data_type }))
+}
+
+pub fn decode_replace_fa(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let fa_data_type = buf.read_int()?;
+    let xml = buf.read_string()?;
+
+    let req_id = if ctx.server_version() >= MIN_SERVER_VER_REPLACE_FA_END {
+        buf.read_int()?
+    } else {
+        0
+    };
+
+    Ok(Request::ReplaceFA(ReplaceFA {
+        req_id,
+        fa_data_type,
+        xml,
+    }))
+}
+
+pub fn encode_receive_fa_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ReceiveFaMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(RECEIVE_FA);
+    buf.push_int(VERSION);
+    buf.push_int(msg.fa_data_type);
+    buf.push_string(&msg.xml);
+
+    Ok(())
+}
+
+pub fn encode_replace_fa_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ReplaceFaEndMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(REPLACE_FA_END);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.text);
+
+    Ok(())
+}
@@ -46,3 +46,30 @@ pub fn decode_family_codes_msg(
         OPCODE_REQ_FAMILY_CODES,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_family_codes(
+    ctx: &mut Context,
+    _buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    Ok(Request::ReqFamilyCodes(ReqFamilyCodes {
+        server_version: ctx.server_version(),
+    }))
+}
+
+pub fn encode_family_codes_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &FamilyCodesMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(FAMILY_CODES);
+    buf.push_int(msg.family_codes.len() as i32);
+    for family in &msg.family_codes {
+        buf.push_string(&family.account_id);
+        buf.push_string(&family.family_code);
+    }
+
+    Ok(())
+}
@@ -8,6 +8,7 @@ use super::error::EncodeError;
 use super::request::*;
 use super::response::*;
 use super::wire::{TwsWireDecoder, TwsWireEncoder};
+use crate::domain::Contract;
 
 pub fn encode_req_fundamental_data(
     _ctx: &mut Context,
@@ -60,3 +61,59 @@ pub fn decode_fundamental_data_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_fundamental_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let mut contract: Contract = Default::default();
+    contract.con_id = buf.read_int()?;
+
+    contract.symbol = buf.read_string()?;
+    contract.sec_type = buf.read_string()?;
+    contract.exchange = buf.read_string()?;
+    contract.primary_exch = buf.read_string()?;
+    contract.currency = buf.read_string()?;
+    contract.local_symbol = buf.read_string()?;
+
+    let report_type = buf.read_string()?;
+
+    Ok(Request::ReqFundamentalData(ReqFundamentalData {
+        req_id,
+        contract,
+        report_type,
+    }))
+}
+
+pub fn decode_cancel_fundamental_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelFundamentalData(CancelFundamentalData {
+        req_id,
+    }))
+}
+
+pub fn encode_fundamental_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &FundamentalDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(FUNDAMENTAL_DATA);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.data);
+
+    Ok(())
+}
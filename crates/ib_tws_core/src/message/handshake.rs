@@ -11,7 +11,6 @@ use super::wire::{TwsWireDecoder, TwsWireEncoder};
 
 const NEXT_VALID_ID: i32 = 9;
 const MANAGED_ACCTS: i32 = 15;
-pub const START_API: i32 = 71;
 
 #[derive(Debug)]
 pub enum Message {
@@ -38,11 +37,20 @@ pub fn decode_handshake_ack(
     buf: &mut BytesMut,
 ) -> Result<HandshakeAck, io::Error> {
     let server_version = buf.read_int()?;
-    let addr_or_time = buf.read_string()?;
+    if server_version == -1 {
+        let redirect = buf.read_string()?;
+        return Ok(HandshakeAck {
+            server_version,
+            server_time: None,
+            redirect: Some(redirect),
+        });
+    }
+    let server_time = buf.read_string()?;
 
     Ok(HandshakeAck {
         server_version,
-        addr_or_time,
+        server_time: Some(server_time),
+        redirect: None,
     })
 }
 
@@ -61,3 +69,50 @@ pub fn encode_start_api(
 
     Ok(DispatchId::Global(OPCODE_START_API))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_handshake(_ctx: &mut Context, buf: &mut BytesMut) -> Result<Handshake, io::Error> {
+    let s = buf.read_string()?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid handshake {:?}", s));
+
+    let (versions, option) = match s.split_once(' ') {
+        Some((versions, option)) => (versions, Some(option.to_string())),
+        None => (s.as_str(), None),
+    };
+    // a single version stands for both ends of the range
+    let versions = versions.strip_prefix('v').ok_or_else(invalid)?;
+    let (min_version, max_version) = versions.split_once("..").unwrap_or((versions, versions));
+
+    Ok(Handshake {
+        min_version: min_version.parse().map_err(|_| invalid())?,
+        max_version: max_version.parse().map_err(|_| invalid())?,
+        option,
+    })
+}
+
+pub fn encode_handshake_ack(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    ack: &HandshakeAck,
+) -> Result<(), EncodeError> {
+    buf.push_int(ack.server_version);
+    match ack.redirect {
+        Some(ref redirect) => buf.push_string(redirect),
+        None => buf.push_string(ack.server_time.as_deref().unwrap_or_default()),
+    }
+
+    Ok(())
+}
+
+pub fn decode_start_api(_ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let client_id = buf.read_int()?;
+    let optional_capabilities = buf.read_string()?;
+
+    Ok(Request::StartApi(StartApi {
+        client_id,
+        optional_capabilities,
+    }))
+}
@@ -63,3 +63,49 @@ pub fn decode_head_timestamp_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_head_timestamp(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    let mut contract = decode_contract(buf)?;
+    contract.include_expired = buf.read_bool()?;
+
+    let use_rth = buf.read_int()?;
+    let what_to_show = buf.read_string()?;
+    let format_date = buf.read_int()?;
+
+    Ok(Request::ReqHeadTimestamp(ReqHeadTimestamp {
+        req_id,
+        contract,
+        what_to_show,
+        use_rth,
+        format_date,
+    }))
+}
+
+pub fn decode_cancel_head_timestamp(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelHeadTimestamp(CancelHeadTimestamp { req_id }))
+}
+
+pub fn encode_head_timestamp_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HeadTimestampMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HEAD_TIMESTAMP);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.head_time_stamp);
+
+    Ok(())
+}
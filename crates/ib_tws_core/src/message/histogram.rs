@@ -55,7 +55,7 @@ pub fn decode_histogram_data_msg(
 
     for _ in 0..n {
         let price = buf.read_double()?;
-        let size = buf.read_long()?;
+        let size = buf.read_decimal()?;
         items.push(HistogramEntry { price, size })
     }
 
@@ -64,3 +64,50 @@ pub fn decode_histogram_data_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_histogram_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let mut contract = decode_contract(buf)?;
+    contract.include_expired = buf.read_bool()?;
+    let use_rth = buf.read_bool()?;
+    let time_period = buf.read_string()?;
+
+    Ok(Request::ReqHistogramData(ReqHistogramData {
+        req_id,
+        contract,
+        use_rth,
+        time_period,
+    }))
+}
+
+pub fn decode_cancel_histogram_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelHistogramData(CancelHistogramData { req_id }))
+}
+
+pub fn encode_histogram_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistogramDataMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTOGRAM_DATA);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.items.len() as i32);
+
+    for item in &msg.items {
+        buf.push_double(item.price);
+        buf.push_decimal(item.size);
+    }
+
+    Ok(())
+}
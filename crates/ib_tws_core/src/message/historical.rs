@@ -26,8 +26,8 @@ pub fn decode_historical_ticks_bid_ask(
         let mask = buf.read_int()?;
         let price_bid = buf.read_double()?;
         let price_ask = buf.read_double()?;
-        let size_bid = buf.read_long()?;
-        let size_ask = buf.read_long()?;
+        let size_bid = buf.read_decimal()?;
+        let size_ask = buf.read_decimal()?;
 
         let tick = HistoricalTickBidAsk {
             time,
@@ -66,7 +66,7 @@ pub fn decode_historical_ticks(
         let time = buf.read_long()?;
         let _ = buf.read_int()?;
         let price = buf.read_double()?;
-        let size = buf.read_long()?;
+        let size = buf.read_decimal()?;
 
         let tick = HistoricalTick { time, price, size };
 
@@ -225,7 +225,7 @@ pub fn decode_historical_ticks_last(
         let time = buf.read_long()?;
         let mask = buf.read_int()?;
         let price = buf.read_double()?;
-        let size = buf.read_long()?;
+        let size = buf.read_decimal()?;
         let exchange = buf.read_string()?;
         let special_conditions = buf.read_string()?;
 
@@ -260,6 +260,14 @@ pub fn encode_req_historical_data(
 ) -> Result<DispatchId, EncodeError> {
     const VERSION: i32 = 6;
 
+    if ctx.server_version() < MIN_SERVER_VER_HISTORICAL_SCHEDULE
+        && req.what_to_show.eq_ignore_ascii_case("SCHEDULE")
+    {
+        return Err(EncodeError::VersionLessError(
+            MIN_SERVER_VER_HISTORICAL_SCHEDULE,
+        ));
+    }
+
     buf.push_int(REQ_HISTORICAL_DATA);
 
     if ctx.server_version() < MIN_SERVER_VER_SYNT_REALTIME_BARS {
@@ -365,3 +373,337 @@ pub fn encode_cancel_historical_data(
 
     Ok(DispatchId::Oneshot(req.req_id))
 }
+
+pub fn decode_historical_schedule_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let req_id = buf.read_int()?;
+    let start_date_time = buf.read_string()?;
+    let end_date_time = buf.read_string()?;
+    let time_zone = buf.read_string()?;
+
+    let count = buf.read_int()?;
+    let mut sessions = Vec::with_capacity(count.max(0) as usize);
+    for _ in 0..count {
+        let start_date_time = buf.read_string()?;
+        let end_date_time = buf.read_string()?;
+        let ref_date = buf.read_string()?;
+        sessions.push(HistoricalSession {
+            start_date_time,
+            end_date_time,
+            ref_date,
+        });
+    }
+
+    Ok((
+        Response::HistoricalScheduleMsg(HistoricalScheduleMsg {
+            req_id,
+            start_date_time,
+            end_date_time,
+            time_zone,
+            sessions,
+        }),
+        req_id,
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn encode_historical_ticks_bid_ask(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalTickBidAskMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_TICKS_BID_ASK);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.ticks.len() as i32);
+
+    for tick in &msg.ticks {
+        buf.push_long(tick.time);
+        buf.push_int(tick.mask);
+        buf.push_double(tick.price_bid);
+        buf.push_double(tick.price_ask);
+        buf.push_decimal(tick.size_bid);
+        buf.push_decimal(tick.size_ask);
+    }
+
+    buf.push_bool(msg.done);
+
+    Ok(())
+}
+
+pub fn encode_historical_ticks(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalTicksMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_TICKS);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.ticks.len() as i32);
+
+    for tick in &msg.ticks {
+        buf.push_long(tick.time);
+        buf.push_int(0); // unused
+        buf.push_double(tick.price);
+        buf.push_decimal(tick.size);
+    }
+
+    buf.push_bool(msg.done);
+
+    Ok(())
+}
+
+pub fn encode_historical_data_update_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalDataUpdateMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_DATA_UPDATE);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.bar.count);
+    buf.push_string(&msg.bar.time);
+    buf.push_double(msg.bar.open);
+    buf.push_double(msg.bar.close);
+    buf.push_double(msg.bar.high);
+    buf.push_double(msg.bar.low);
+    buf.push_decimal(msg.bar.wap);
+    buf.push_decimal(msg.bar.volume);
+
+    Ok(())
+}
+
+pub fn encode_historical_news_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalNewsEndMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_NEWS_END);
+    buf.push_int(msg.req_id);
+    buf.push_bool(msg.has_more);
+
+    Ok(())
+}
+
+pub fn encode_historical_news_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalNewsMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_NEWS);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.time);
+    buf.push_string(&msg.provider_code);
+    buf.push_string(&msg.article_id);
+    buf.push_string(&msg.headline);
+
+    Ok(())
+}
+
+pub fn encode_historical_data_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 3;
+
+    buf.push_int(HISTORICAL_DATA);
+    if ctx.server_version() < MIN_SERVER_VER_SYNT_REALTIME_BARS {
+        buf.push_int(VERSION);
+    }
+
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.start_date);
+    buf.push_string(&msg.end_date);
+    buf.push_int(msg.bars.len() as i32);
+    for bar in &msg.bars {
+        buf.push_string(&bar.time);
+        buf.push_double(bar.open);
+        buf.push_double(bar.high);
+        buf.push_double(bar.low);
+        buf.push_double(bar.close);
+        buf.push_decimal(bar.volume);
+
+        buf.push_decimal(bar.wap);
+
+        if ctx.server_version() < MIN_SERVER_VER_SYNT_REALTIME_BARS {
+            buf.push_string("false"); // has gaps
+        }
+
+        buf.push_int(bar.count);
+    }
+
+    Ok(())
+}
+
+pub fn encode_historical_ticks_last(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalTickLastMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_TICKS_LAST);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.ticks.len() as i32);
+
+    for tick in &msg.ticks {
+        buf.push_long(tick.time);
+        buf.push_int(tick.mask);
+        buf.push_double(tick.price);
+        buf.push_decimal(tick.size);
+        buf.push_string(&tick.exchange);
+        buf.push_string(&tick.special_conditions);
+    }
+
+    buf.push_bool(msg.done);
+
+    Ok(())
+}
+
+pub fn decode_req_historical_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    if ctx.server_version() < MIN_SERVER_VER_SYNT_REALTIME_BARS {
+        let _version = buf.read_int()?;
+    }
+
+    let req_id = buf.read_int()?;
+
+    let mut contract = decode_contract(buf)?;
+
+    contract.include_expired = buf.read_bool()?;
+
+    let end_date_time = buf.read_string()?;
+    let bar_size_setting = buf.read_string()?;
+
+    let duration_str = buf.read_string()?;
+    let use_rth = buf.read_int()?;
+    let what_to_show = buf.read_string()?;
+
+    let format_date = buf.read_int()?;
+
+    if contract.sec_type.to_uppercase() == "BAG" {
+        let count = buf.read_int()?;
+        for _ in 0..count {
+            contract.combo_legs.push(ComboLeg {
+                con_id: buf.read_int()?,
+                ratio: buf.read_int()?,
+                action: buf.read_string()?,
+                exchange: buf.read_string()?,
+                open_close: 0,
+                short_sale_slot: 0,
+                designated_location: "".to_string(),
+                exempt_code: -1,
+            });
+        }
+    }
+
+    let keepup_to_date = if ctx.server_version() >= MIN_SERVER_VER_SYNT_REALTIME_BARS {
+        buf.read_bool()?
+    } else {
+        false
+    };
+
+    let chart_options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqHistoricalData(ReqHistoricalData {
+        req_id,
+        contract,
+        end_date_time,
+        duration_str,
+        bar_size_setting,
+        what_to_show,
+        use_rth,
+        format_date,
+        keepup_to_date,
+        chart_options,
+    }))
+}
+
+pub fn decode_req_historical_news(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let con_id = buf.read_int()?;
+    let provider_code = buf.read_string()?;
+    let start_time = buf.read_string()?;
+    let end_time = buf.read_string()?;
+    let total_results = buf.read_int()?;
+
+    let options = if ctx.server_version() >= MIN_SERVER_VER_NEWS_QUERY_ORIGINS {
+        decode_tagvalue_from_string(buf)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Request::ReqHistoricalNews(ReqHistoricalNews {
+        req_id,
+        con_id,
+        provider_code,
+        start_time,
+        end_time,
+        total_results,
+        options,
+    }))
+}
+
+pub fn decode_req_historical_ticks(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let mut contract = decode_contract(buf)?;
+    contract.include_expired = buf.read_bool()?;
+    let start_time = buf.read_string()?;
+    let end_time = buf.read_string()?;
+    let num_of_ticks = buf.read_int()?;
+    let what_to_show = buf.read_string()?;
+    let use_rth = buf.read_int()?;
+    let ignore_size = buf.read_bool()?;
+    let options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqHistoricalTicks(ReqHistoricalTicks {
+        req_id,
+        contract,
+        start_time,
+        end_time,
+        num_of_ticks,
+        what_to_show,
+        use_rth,
+        ignore_size,
+        options,
+    }))
+}
+
+pub fn decode_cancel_historical_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelHistoricalData(CancelHistoricalData { req_id }))
+}
+
+pub fn encode_historical_schedule_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &HistoricalScheduleMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(HISTORICAL_SCHEDULE);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.start_date_time);
+    buf.push_string(&msg.end_date_time);
+    buf.push_string(&msg.time_zone);
+
+    buf.push_int(msg.sessions.len() as i32);
+    for session in &msg.sessions {
+        buf.push_string(&session.start_date_time);
+        buf.push_string(&session.end_date_time);
+        buf.push_string(&session.ref_date);
+    }
+
+    Ok(())
+}
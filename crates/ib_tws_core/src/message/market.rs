@@ -1,9 +1,11 @@
+use std::collections::HashSet;
 use std::io;
 use std::{f64, i32};
 
 use approx::abs_diff_eq;
 use bit::BitIndex;
 use bytes::BytesMut;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
 
 use super::constants::*;
@@ -14,7 +16,7 @@ use super::response::*;
 use super::util::*;
 use super::wire::{TwsWireDecoder, TwsWireEncoder};
 use crate::domain::*;
-use crate::domain::market_data::TickByTickType;
+use crate::domain::market_data::{GenericTick, MarketDataType, TickByTickType};
 
 pub fn encode_req_mkt_data(
     ctx: &mut Context,
@@ -134,12 +136,21 @@ pub fn decode_tick_generic_msg(
 
 #[instrument(err)]
 pub fn decode_tick_option_computation_msg(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
-    let version = buf.read_int()?;
+    let version = if ctx.server_version() >= MIN_SERVER_VER_PRICE_BASED_VOLATILITY {
+        i32::MAX
+    } else {
+        buf.read_int()?
+    };
     let req_id = buf.read_int()?;
     let tick_type = buf.read_int()?;
+    let tick_attrib = if ctx.server_version() >= MIN_SERVER_VER_PRICE_BASED_VOLATILITY {
+        buf.read_int()?
+    } else {
+        i32::MAX
+    };
     let mut implied_vol = buf.read_double()?;
     if abs_diff_eq!(implied_vol, -1.0f64) {
         // -1 is the "not yet computed" indicator
@@ -200,6 +211,7 @@ pub fn decode_tick_option_computation_msg(
         Response::TickOptionComputationMsg(TickOptionComputationMsg {
             req_id,
             tick_type: tick_type.try_into().unwrap_or_default(),
+            tick_attrib,
             implied_vol,
             delta,
             opt_price,
@@ -585,7 +597,7 @@ pub fn encode_cancel_mkt_data(
 }
 
 pub fn encode_cancel_mkt_depth(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
     req: &CancelMktDepth,
 ) -> Result<DispatchId, EncodeError> {
@@ -595,6 +607,10 @@ pub fn encode_cancel_mkt_depth(
     buf.push_int(VERSION);
     buf.push_int(req.req_id);
 
+    if ctx.server_version() >= MIN_SERVER_VER_SMART_DEPTH {
+        buf.push_bool(req.is_smart_depth);
+    }
+
     Ok(DispatchId::Oneshot(req.req_id))
 }
 
@@ -728,3 +744,606 @@ pub fn decode_realtime_bars_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_mkt_data(ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let mut contract = decode_contract(buf)?;
+
+    if contract.sec_type.to_uppercase() == "BAG" {
+        contract.combo_legs = read_combo_legs(buf)?;
+    }
+
+    if buf.read_bool()? {
+        contract.delta_neutral_contract = Some(DeltaNeutralContract {
+            con_id: buf.read_int()?,
+            delta: buf.read_double()?,
+            price: buf.read_double()?,
+        });
+    }
+
+    let mut generic_tick_list = HashSet::new();
+    for tick in buf.read_string()?.split(',').filter(|tick| !tick.is_empty()) {
+        match tick.parse().ok().and_then(|tick: i32| GenericTick::try_from(tick).ok()) {
+            Some(tick) => {
+                generic_tick_list.insert(tick);
+            }
+            None => warn!(tick, "skipping unknown generic tick"),
+        }
+    }
+
+    let snapshot = buf.read_bool()?;
+
+    let regulatory_snapshot = if ctx.server_version() >= MIN_SERVER_VER_REQ_SMART_COMPONENTS {
+        buf.read_bool()?
+    } else {
+        false
+    };
+
+    let mkt_data_options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqMktData(ReqMktData {
+        req_id,
+        contract,
+        generic_tick_list,
+        snapshot,
+        regulatory_snapshot,
+        mkt_data_options,
+    }))
+}
+
+fn read_combo_legs(buf: &mut BytesMut) -> Result<Vec<ComboLeg>, io::Error> {
+    let count = buf.read_int()?;
+    let mut combo_legs = Vec::new();
+    for _ in 0..count {
+        combo_legs.push(ComboLeg {
+            con_id: buf.read_int()?,
+            ratio: buf.read_int()?,
+            action: buf.read_string()?,
+            exchange: buf.read_string()?,
+            open_close: 0,
+            short_sale_slot: 0,
+            designated_location: "".to_string(),
+            exempt_code: -1,
+        });
+    }
+    Ok(combo_legs)
+}
+
+pub fn encode_tick_efp_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickEFPMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(TICK_EFP);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.to_i32().unwrap_or_default());
+    buf.push_double(msg.basis_points);
+    buf.push_string(&msg.formatted_basis_points);
+    buf.push_double(msg.implied_futures_price);
+    buf.push_int(msg.hold_days);
+    buf.push_string(&msg.future_last_trade_date);
+    buf.push_double(msg.dividend_impact);
+    buf.push_double(msg.dividends_to_last_trade_date);
+
+    Ok(())
+}
+
+pub fn encode_tick_string_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickStringMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(TICK_STRING);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    buf.push_string(&msg.value);
+
+    Ok(())
+}
+
+pub fn encode_tick_generic_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickGenericMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(TICK_GENERIC);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    buf.push_double(msg.value);
+
+    Ok(())
+}
+
+pub fn encode_tick_option_computation_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickOptionComputationMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 6;
+
+    // f64::MAX is sent as the "not yet computed" indicator of each field
+    let or_unset = |v: f64, unset: f64| if v == f64::MAX { unset } else { v };
+
+    buf.push_int(TICK_OPTION_COMPUTATION);
+    if ctx.server_version() < MIN_SERVER_VER_PRICE_BASED_VOLATILITY {
+        buf.push_int(VERSION);
+    }
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    if ctx.server_version() >= MIN_SERVER_VER_PRICE_BASED_VOLATILITY {
+        buf.push_int(msg.tick_attrib);
+    }
+    buf.push_double(or_unset(msg.implied_vol, -1.0));
+    buf.push_double(or_unset(msg.delta, -2.0));
+    buf.push_double(or_unset(msg.opt_price, -1.0));
+    buf.push_double(or_unset(msg.pv_dividend, -1.0));
+    buf.push_double(or_unset(msg.gamma, -2.0));
+    buf.push_double(or_unset(msg.vega, -2.0));
+    buf.push_double(or_unset(msg.theta, -2.0));
+    buf.push_double(or_unset(msg.und_price, -1.0));
+
+    Ok(())
+}
+
+pub fn encode_tick_size_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickSizeMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(TICK_SIZE);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    buf.push_decimal(msg.size);
+
+    Ok(())
+}
+
+pub fn encode_tick_price_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickPriceMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 3;
+
+    buf.push_int(TICK_PRICE);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    buf.push_double(msg.price);
+    buf.push_decimal(msg.size);
+
+    let mut mask = 0u32;
+    mask.set_bit(0, msg.attribs.can_auto_execute);
+    if ctx.server_version() >= MIN_SERVER_VER_PAST_LIMIT {
+        mask.set_bit(1, msg.attribs.past_limit);
+        if ctx.server_version() >= MIN_SERVER_VER_PRE_OPEN_BID_ASK {
+            mask.set_bit(2, msg.attribs.pre_open);
+        }
+    }
+    buf.push_int(mask as i32);
+
+    Ok(())
+}
+
+pub fn encode_tick_req_params_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickReqParamsMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(TICK_REQ_PARAMS);
+    buf.push_int(msg.req_id);
+    buf.push_double(msg.min_tick);
+    buf.push_string(&msg.bbo_exchange);
+    buf.push_int(msg.snapshot_permissions);
+
+    Ok(())
+}
+
+pub fn encode_tick_by_tick_all_last_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickByTickAllLastMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(TICK_BY_TICK);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tick_type.clone() as i32);
+    buf.push_long(msg.time);
+    buf.push_double(msg.price);
+    buf.push_decimal(msg.size);
+
+    let mut mask = 0u32;
+    mask.set_bit(0, msg.attribs.past_limit);
+    mask.set_bit(1, msg.attribs.unreported);
+    buf.push_int(mask as i32);
+
+    buf.push_string(&msg.exchange);
+    buf.push_string(&msg.special_conditions);
+
+    Ok(())
+}
+
+pub fn encode_tick_by_tick_bid_ask_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickByTickBidAskMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(TICK_BY_TICK);
+    buf.push_int(msg.req_id);
+    buf.push_int(TickByTickType::BidAsk as i32);
+    buf.push_long(msg.time);
+    buf.push_double(msg.bid_price);
+    buf.push_double(msg.ask_price);
+    buf.push_decimal(msg.bid_size);
+    buf.push_decimal(msg.ask_size);
+
+    let mut mask = 0u32;
+    mask.set_bit(0, msg.attribs.bid_past_low);
+    mask.set_bit(1, msg.attribs.ask_past_high);
+    buf.push_int(mask as i32);
+
+    Ok(())
+}
+
+pub fn encode_tick_by_tick_mid_point_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickByTickMidPointMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(TICK_BY_TICK);
+    buf.push_int(msg.req_id);
+    buf.push_int(TickByTickType::MidPoint as i32);
+    buf.push_long(msg.time);
+    buf.push_double(msg.mid_point);
+
+    Ok(())
+}
+
+pub fn encode_tick_by_tick_none_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    _msg: &TickByTickNoneMsg,
+) -> Result<(), EncodeError> {
+    // the message carries no request id, nothing routes it anyway
+    buf.push_int(TICK_BY_TICK);
+    buf.push_int(-1);
+    buf.push_int(0);
+    buf.push_long(0);
+
+    Ok(())
+}
+
+pub fn encode_tick_snapshot_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickSnapshotEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(TICK_SNAPSHOT_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn encode_tick_news_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &TickNewsMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(TICK_NEWS);
+    buf.push_int(msg.req_id);
+    buf.push_long(msg.time_stamp);
+    buf.push_string(&msg.provider_code);
+    buf.push_string(&msg.article_id);
+    buf.push_string(&msg.headline);
+    buf.push_string(&msg.extra_data);
+
+    Ok(())
+}
+
+pub fn encode_market_depth_l2_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &MarketDepthL2Msg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(MARKET_DEPTH_L2);
+    buf.push_int(VERSION);
+    buf.push_int(msg.id);
+
+    buf.push_int(msg.position);
+    buf.push_string(&msg.market_maker);
+    buf.push_int(msg.operation);
+    buf.push_int(msg.side);
+    buf.push_double(msg.price);
+    buf.push_int(msg.size);
+
+    Ok(())
+}
+
+pub fn encode_market_depth_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &MarketDepthMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(MARKET_DEPTH);
+    buf.push_int(VERSION);
+    buf.push_int(msg.id);
+
+    buf.push_int(msg.position);
+    buf.push_int(msg.operation);
+    buf.push_int(msg.side);
+    buf.push_double(msg.price);
+    buf.push_decimal(msg.size);
+
+    Ok(())
+}
+
+pub fn decode_req_tick_by_tick_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let contract = decode_contract(buf)?;
+
+    let tick_type = match buf.read_string()?.as_str() {
+        "Last" => TickByTickType::Last,
+        "AllLast" => TickByTickType::AllLast,
+        "BidAsk" => TickByTickType::BidAsk,
+        "MidPoint" => TickByTickType::MidPoint,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown tick_by_tick tick_type",
+            ))
+        }
+    };
+
+    let (num_of_ticks, ignore_size) =
+        if ctx.server_version() >= MIN_SERVER_VER_TICK_BY_TICK_IGNORE_SIZE {
+            (buf.read_int()?, buf.read_bool()?)
+        } else {
+            (0, false)
+        };
+
+    Ok(Request::ReqTickByTickData(ReqTickByTickData {
+        req_id,
+        contract,
+        tick_type,
+        num_of_ticks,
+        ignore_size,
+    }))
+}
+
+pub fn decode_cancel_tick_by_tick_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelTickByTickData(CancelTickByTickData { req_id }))
+}
+
+pub fn decode_cancel_realtime_bars(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelRealtimeBars(CancelRealtimeBars { req_id }))
+}
+
+pub fn decode_req_realtime_bars(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let contract = decode_contract(buf)?;
+
+    let bar_size = buf.read_int()?;
+    let what_to_show = buf.read_string()?;
+    let use_rth = buf.read_bool()?;
+
+    let options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqRealtimeBars(ReqRealtimeBars {
+        req_id,
+        contract,
+        bar_size,
+        what_to_show,
+        use_rth,
+        options,
+    }))
+}
+
+pub fn decode_req_mkt_depth(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let contract = decode_contract(buf)?;
+
+    let num_rows = buf.read_int()?;
+    let is_smart_depth = buf.read_bool()?;
+
+    let options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqMktDepth(ReqMktDepth {
+        req_id,
+        contract,
+        num_rows,
+        is_smart_depth,
+        options,
+    }))
+}
+
+pub fn decode_cancel_mkt_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelMktData(CancelMktData { req_id }))
+}
+
+pub fn decode_cancel_mkt_depth(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let is_smart_depth = if ctx.server_version() >= MIN_SERVER_VER_SMART_DEPTH {
+        buf.read_bool()?
+    } else {
+        false
+    };
+
+    Ok(Request::CancelMktDepth(CancelMktDepth {
+        req_id,
+        is_smart_depth,
+    }))
+}
+
+pub fn decode_calculate_implied_volatility(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let contract = decode_contract(buf)?;
+
+    let option_price = buf.read_double()?;
+    let under_price = buf.read_double()?;
+
+    Ok(Request::CalculateImpliedVolatility(
+        CalculateImpliedVolatility {
+            req_id,
+            contract,
+            option_price,
+            under_price,
+        },
+    ))
+}
+
+pub fn decode_cancel_calculate_implied_volatility(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelCalculateImpliedVolatility(
+        CancelCalculateImpliedVolatility { req_id },
+    ))
+}
+
+pub fn decode_calculate_option_price(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let contract = decode_contract(buf)?;
+
+    let volatility = buf.read_double()?;
+    let under_price = buf.read_double()?;
+
+    Ok(Request::CalculateOptionPrice(CalculateOptionPrice {
+        req_id,
+        contract,
+        volatility,
+        under_price,
+    }))
+}
+
+pub fn decode_cancel_calculate_option_price(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelCalculateOptionPrice(
+        CancelCalculateOptionPrice { req_id },
+    ))
+}
+
+pub fn decode_req_market_data_type(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let market_data_type = MarketDataType::try_from(buf.read_int()?)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "unknown market data type"))?;
+
+    Ok(Request::ReqMarketDataType(ReqMarketDataType {
+        market_data_type,
+    }))
+}
+
+pub fn encode_market_data_type_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &MarketDataTypeMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(MARKET_DATA_TYPE);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.market_data_type as i32);
+
+    Ok(())
+}
+
+pub fn encode_realtime_bars_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &RealTimeBarsMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 3;
+
+    buf.push_int(REAL_TIME_BARS);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_long(msg.time);
+    buf.push_double(msg.open);
+    buf.push_double(msg.high);
+    buf.push_double(msg.low);
+    buf.push_double(msg.close);
+    buf.push_long(msg.volume);
+    buf.push_double(msg.wap);
+    buf.push_int(msg.count);
+
+    Ok(())
+}
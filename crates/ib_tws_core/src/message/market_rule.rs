@@ -54,3 +54,32 @@ pub fn decode_market_rule(
         OPCODE_REQ_MARKET_RULE,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_market_rule(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let market_rule_id = buf.read_int()?;
+
+    Ok(Request::ReqMarketRule(ReqMarketRule { market_rule_id }))
+}
+
+pub fn encode_market_rule(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &MarketRule,
+) -> Result<(), EncodeError> {
+    buf.push_int(MARKET_RULE);
+    buf.push_int(msg.market_rule_id);
+    buf.push_int(msg.price_increments.len() as i32);
+
+    for price_increment in &msg.price_increments {
+        buf.push_double(price_increment.low_edge);
+        buf.push_double(price_increment.increment);
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use std::{error, fmt, io, io::Cursor};
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, BufMut, BytesMut};
 
-use super::constants::MAX_MSG_LENGTH;
+use super::{
+    constants::{API_HEAD, MAX_MSG_LENGTH},
+    context::Context,
+    Request, Response,
+};
 
 pub const FRAME_HEAD_LEN: usize = 4;
 
@@ -41,6 +45,164 @@ pub fn decode_data(n: usize, src: &mut BytesMut) -> io::Result<Option<BytesMut>>
     Ok(Some(src.split_to(n)))
 }
 
+/// Splits the bytes read from TWS into responses and frames the requests written to it,
+/// independent of the runtime. The transports wrap it in the codec traits of their runtime.
+#[derive(Debug)]
+pub struct Codec {
+    state: FrameState,
+    ctx: Context,
+}
+
+impl Codec {
+    #[must_use]
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+
+    /// Decode the next response, `None` until a whole frame was read.
+    /// # Errors
+    /// Returns an error if the frame is too big or the message can't be decoded.
+    #[instrument(err, skip(src))]
+    pub fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Response>> {
+        match decode_frame(&mut self.state, src)? {
+            Some(mut data) => Ok(Some(self.ctx.decode_message(&mut data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Encode a request as a length-prefixed frame.
+    /// # Errors
+    /// Returns an error if the request isn't supported by the server version.
+    /// # Panics
+    /// Panics if the message is longer than `u32::MAX` bytes.
+    pub fn encode(&mut self, request: &Request, buf: &mut BytesMut) -> io::Result<()> {
+        let req = self.ctx.encode_message(request)?;
+        encode_frame(req, buf);
+        trace!(?request, ?buf, "encoded request");
+        Ok(())
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Codec {
+            state: FrameState::Head,
+            ctx: Context::new(),
+        }
+    }
+}
+
+/// The server side of `Codec`: splits the bytes read from an API client into requests and
+/// frames the responses written to it, for mock servers and proxies.
+///
+/// The first frame is expected after the `API\0` prefix and decoded as the handshake, the
+/// server version is then taken from the `Response::HandshakeAck` encoded in reply.
+#[derive(Debug)]
+pub struct ServerCodec {
+    prefixed: bool,
+    state: FrameState,
+    ctx: Context,
+}
+
+impl ServerCodec {
+    #[must_use]
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+
+    /// Decode the next request, `None` until a whole frame was read.
+    /// # Errors
+    /// Returns an error if the client didn't start with the `API\0` prefix, the frame is
+    /// too big or the message can't be decoded.
+    #[instrument(err, skip(src))]
+    pub fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Request>> {
+        if !self.prefixed {
+            if src.len() < API_HEAD.len() {
+                return Ok(None);
+            }
+            if &src[..API_HEAD.len()] != API_HEAD {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "expected the API prefix",
+                ));
+            }
+            let _ = src.split_to(API_HEAD.len());
+            self.prefixed = true;
+        }
+
+        match decode_frame(&mut self.state, src)? {
+            Some(mut data) => Ok(Some(self.ctx.decode_request(&mut data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Encode a response as a length-prefixed frame.
+    /// # Errors
+    /// Returns an error if the response isn't supported by the server version.
+    /// # Panics
+    /// Panics if the message is longer than `u32::MAX` bytes.
+    pub fn encode(&mut self, response: &Response, buf: &mut BytesMut) -> io::Result<()> {
+        let msg = self.ctx.encode_response(response)?;
+        encode_frame(msg, buf);
+        trace!(?response, ?buf, "encoded response");
+        Ok(())
+    }
+}
+
+impl Default for ServerCodec {
+    fn default() -> Self {
+        ServerCodec {
+            prefixed: false,
+            state: FrameState::Head,
+            ctx: Context::new(),
+        }
+    }
+}
+
+fn decode_frame(state: &mut FrameState, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+    if src.is_empty() {
+        return Ok(None);
+    }
+
+    let n = match *state {
+        FrameState::Head => match decode_head(src)? {
+            Some(n) => {
+                *state = FrameState::Data(n);
+                n
+            }
+            None => return Ok(None),
+        },
+        FrameState::Data(n) => n,
+    };
+
+    match decode_data(n, src)? {
+        Some(data) => {
+            *state = FrameState::Head;
+            src.reserve(FRAME_HEAD_LEN);
+            Ok(Some(data))
+        }
+        None => Ok(None),
+    }
+}
+
+fn encode_frame(mut msg: BytesMut, buf: &mut BytesMut) {
+    buf.reserve(msg.len() + FRAME_HEAD_LEN);
+    buf.put_u32(
+        msg.len()
+            .try_into()
+            .expect("tried to encode message longer than maximum"),
+    );
+    buf.put(&mut msg);
+}
+
 #[derive(Debug, Clone)]
 pub struct FrameTooBigError {
     pub current_size: usize,
@@ -10,6 +10,7 @@ use super::request::*;
 use super::response::*;
 use super::util::*;
 use super::wire::{TwsWireDecoder, TwsWireEncoder};
+use crate::domain::misc::ServerLogLevel;
 use crate::domain::*;
 
 pub fn encode_req_ids(
@@ -200,3 +201,203 @@ pub fn encode_exercise_options(
 
     Ok(DispatchId::Oneshot(req.req_id))
 }
+
+pub fn encode_req_user_info(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &ReqUserInfo,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_USER_INFO {
+        return Err(EncodeError::VersionLessError(MIN_SERVER_VER_USER_INFO));
+    }
+
+    buf.push_int(REQ_USER_INFO);
+    buf.push_int(req.req_id);
+
+    Ok(DispatchId::Oneshot(req.req_id))
+}
+
+pub fn decode_user_info_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let req_id = buf.read_int()?;
+    let white_branding_id = buf.read_string()?;
+    Ok((
+        Response::UserInfoMsg(UserInfoMsg {
+            req_id,
+            white_branding_id,
+        }),
+        req_id,
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_ids(_ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let num_ids = buf.read_int()?;
+
+    Ok(Request::ReqIds(ReqIds { num_ids }))
+}
+
+pub fn decode_set_server_log_level(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let log_level = ServerLogLevel::try_from(buf.read_int()?)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "unknown server log level"))?;
+
+    Ok(Request::SetServerLogLevel(SetServerLogLevel { log_level }))
+}
+
+pub fn decode_req_current_time(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqCurrentTime(ReqCurrentTime {}))
+}
+
+pub fn decode_req_global_cancel(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqGlobalCancel(ReqGlobalCancel {}))
+}
+
+pub fn decode_req_soft_dollar_tiers(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::ReqSoftDollarTiers(ReqSoftDollarTiers { req_id }))
+}
+
+pub fn encode_soft_dollar_tiers_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &SoftDollarTiersMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(SOFT_DOLLAR_TIERS);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.tiers.len() as i32);
+
+    for tier in &msg.tiers {
+        buf.push_string(&tier.name);
+        buf.push_string(&tier.value);
+        buf.push_string(&tier.display_name);
+    }
+
+    Ok(())
+}
+
+pub fn encode_current_time_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &CurrentTimeMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(CURRENT_TIME);
+    buf.push_int(VERSION);
+    buf.push_long(msg.time);
+
+    Ok(())
+}
+
+pub fn encode_next_valid_id_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &NextValidIdMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(NEXT_VALID_ID);
+    buf.push_int(VERSION);
+    buf.push_int(msg.order_id);
+
+    Ok(())
+}
+
+pub fn encode_smart_components_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &SmartComponentsMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(SMART_COMPONENTS);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.map.len() as i32);
+
+    for (bit_number, (exchange, exchange_letter)) in &msg.map {
+        buf.push_int(*bit_number);
+        buf.push_string(exchange);
+        buf.push_string(&char::from(*exchange_letter).to_string());
+    }
+
+    Ok(())
+}
+
+pub fn decode_req_smart_components(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let bbo_exchange = buf.read_string()?;
+
+    Ok(Request::ReqSmartComponents(ReqSmartComponents {
+        req_id,
+        bbo_exchange,
+    }))
+}
+
+pub fn decode_exercise_options(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let contract = decode_contract_without_primary_exch(buf)?;
+
+    let exercise_action = buf.read_int()?;
+    let exercise_quantity = buf.read_int()?;
+    let account = buf.read_string()?;
+    let overriden = buf.read_int()?;
+
+    Ok(Request::ExerciseOptions(ExerciseOptions {
+        req_id,
+        contract,
+        exercise_action,
+        exercise_quantity,
+        account,
+        overriden,
+    }))
+}
+
+pub fn decode_req_user_info(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::ReqUserInfo(ReqUserInfo { req_id }))
+}
+
+pub fn encode_user_info_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &UserInfoMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(USER_INFO);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.white_branding_id);
+
+    Ok(())
+}
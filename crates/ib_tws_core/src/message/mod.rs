@@ -7,11 +7,10 @@ mod account;
 mod auth;
 mod bulletins;
 
-// TODO: Merge this with AsyncClient
 #[cfg(feature = "async")]
-mod commander;
+pub(crate) mod commander;
 #[cfg(feature = "async")]
-mod dispatcher;
+pub(crate) mod dispatcher;
 
 pub mod constants;
 pub mod context;
@@ -42,3 +41,4 @@ pub mod response;
 mod scanner;
 mod util;
 pub mod wire;
+mod wsh;
@@ -88,3 +88,66 @@ pub fn decode_news_article_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_news_provider(
+    ctx: &mut Context,
+    _buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    Ok(Request::ReqNewsProvider(ReqNewsProvider {
+        server_version: ctx.server_version(),
+    }))
+}
+
+pub fn encode_news_providers_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &NewsProviderMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(NEWS_PROVIDERS);
+    buf.push_int(msg.providers.len() as i32);
+
+    for provider in &msg.providers {
+        buf.push_string(&provider.code);
+        buf.push_string(&provider.name);
+    }
+
+    Ok(())
+}
+
+pub fn decode_req_news_article(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let provider_code = buf.read_string()?;
+    let article_id = buf.read_string()?;
+
+    let options = if ctx.server_version() >= MIN_SERVER_VER_NEWS_QUERY_ORIGINS {
+        decode_tagvalue_from_string(buf)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Request::ReqNewsArticle(ReqNewsArticle {
+        req_id,
+        provider_code,
+        article_id,
+        options,
+    }))
+}
+
+pub fn encode_news_article_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &NewsArticleMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(NEWS_ARTICLE);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.article_type);
+    buf.push_string(&msg.article_text);
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use std::io;
 use std::{f64, i32};
 
 use bytes::BytesMut;
+use rust_decimal::prelude::{Decimal, ToPrimitive};
 
 use super::constants::*;
 use super::context::{Context, DispatchId};
@@ -17,7 +18,11 @@ pub fn decode_open_order_msg(
     ctx: &mut Context,
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
-    let version = buf.read_int()?;
+    let version = if ctx.server_version() < MIN_SERVER_VER_ORDER_CONTAINER {
+        buf.read_int()?
+    } else {
+        ctx.server_version()
+    };
 
     let mut order: Order = Default::default();
     order.order_id = buf.read_int()?;
@@ -47,9 +52,9 @@ pub fn decode_open_order_msg(
     order.action = buf.read_string()?;
 
     order.total_quantity = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     order.order_type = buf.read_string()?;
@@ -127,7 +132,7 @@ pub fn decode_open_order_msg(
         order.delta = buf.read_double_max()?;
         order.stock_range_lower = buf.read_double_max()?;
         order.stock_range_upper = buf.read_double_max()?;
-        order.display_size = buf.read_int()?;
+        order.display_size = buf.read_decimal_max()?;
         if version < 18 {
             // will never happen
             /* order.m_rthOnly = */
@@ -136,7 +141,7 @@ pub fn decode_open_order_msg(
         order.block_order = buf.read_bool()?;
         order.sweep_to_fill = buf.read_bool()?;
         order.all_or_none = buf.read_bool()?;
-        order.min_qty = buf.read_int_max()?;
+        order.min_qty = buf.read_decimal_max()?;
         order.oca_type = buf.read_int()?;
         order.etrade_only = buf.read_bool()?;
         order.firm_quote_only = buf.read_bool()?;
@@ -200,35 +205,7 @@ pub fn decode_open_order_msg(
     }
 
     if version >= 29 {
-        let count = buf.read_int()?;
-        for _ in 0..count {
-            let con_id = buf.read_int()?;
-            let ratio = buf.read_int()?;
-            let action = buf.read_string()?;
-            let exchange = buf.read_string()?;
-            let open_close = buf.read_int()?;
-            let short_sale_slot = buf.read_int()?;
-            let designated_location = buf.read_string()?;
-            let exempt_code = buf.read_int()?;
-
-            contract.combo_legs.push(ComboLeg {
-                con_id,
-                ratio,
-                action,
-                exchange,
-                open_close,
-                short_sale_slot,
-                designated_location,
-                exempt_code,
-            });
-        }
-
-        let order_combo_legs_count = buf.read_int()?;
-
-        for _ in 0..order_combo_legs_count {
-            let price = buf.read_double_max()?;
-            order.order_combo_legs.push(OrderComboLeg { price });
-        }
+        read_combo_legs(buf, &mut contract, &mut order)?;
     }
 
     if version >= 26 {
@@ -354,18 +331,7 @@ pub fn decode_open_order_msg(
             order.reference_exchange_id = buf.read_string()?;
         }
 
-        let conditions_count = buf.read_int()?;
-
-        if conditions_count > 0 {
-            for _ in 0..conditions_count {
-                let order_condition_type = buf.read_int()?;
-                let condition = order_condition_read(buf, order_condition_type)?;
-                order.conditions.push(condition);
-            }
-
-            order.conditions_ignore_rth = buf.read_bool()?;
-            order.conditions_cancel_order = buf.read_bool()?;
-        }
+        read_order_conditions(buf, &mut order)?;
 
         order.adjusted_order_type = buf.read_string()?;
         order.trigger_price = buf.read_double_max()?;
@@ -396,6 +362,34 @@ pub fn decode_open_order_msg(
         order.dont_use_auto_price_for_hedge = buf.read_bool()?;
     }
 
+    if ctx.server_version() >= MIN_SERVER_VER_ORDER_CONTAINER {
+        order.is_oms_container = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_D_PEG_ORDERS {
+        order.discretionary_up_to_limit_price = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PRICE_MGMT_ALGO {
+        order.use_price_mgmt_algo = read_optional_bool(buf)?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DURATION {
+        order.duration = buf.read_int_max()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_POST_TO_ATS {
+        order.post_to_ats = buf.read_int_max()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_CANCEL_PARENT {
+        order.auto_cancel_parent = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        read_peg_best_peg_mid_attributes(buf, &mut order)?;
+    }
+
     Ok((
         Response::OpenOrderMsg(OpenOrderMsg {
             order_id: order.order_id,
@@ -420,15 +414,15 @@ pub fn decode_order_status_msg(
     let status = buf.read_string()?;
 
     let filled = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     let remaining = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     let avg_fill_price = buf.read_double()?;
@@ -475,6 +469,79 @@ pub fn decode_order_status_msg(
     ))
 }
 
+fn read_combo_legs(
+    buf: &mut BytesMut,
+    contract: &mut Contract,
+    order: &mut Order,
+) -> Result<(), io::Error> {
+    let count = buf.read_int()?;
+    for _ in 0..count {
+        let con_id = buf.read_int()?;
+        let ratio = buf.read_int()?;
+        let action = buf.read_string()?;
+        let exchange = buf.read_string()?;
+        let open_close = buf.read_int()?;
+        let short_sale_slot = buf.read_int()?;
+        let designated_location = buf.read_string()?;
+        let exempt_code = buf.read_int()?;
+
+        contract.combo_legs.push(ComboLeg {
+            con_id,
+            ratio,
+            action,
+            exchange,
+            open_close,
+            short_sale_slot,
+            designated_location,
+            exempt_code,
+        });
+    }
+
+    let order_combo_legs_count = buf.read_int()?;
+
+    for _ in 0..order_combo_legs_count {
+        let price = buf.read_double_max()?;
+        order.order_combo_legs.push(OrderComboLeg { price });
+    }
+
+    Ok(())
+}
+
+fn read_peg_best_peg_mid_attributes(
+    buf: &mut BytesMut,
+    order: &mut Order,
+) -> Result<(), io::Error> {
+    order.min_trade_qty = buf.read_int_max()?;
+    order.min_compete_size = buf.read_int_max()?;
+    order.compete_against_best_offset = buf.read_double_max()?;
+    order.mid_offset_at_whole = buf.read_double_max()?;
+    order.mid_offset_at_half = buf.read_double_max()?;
+    Ok(())
+}
+
+fn read_order_conditions(buf: &mut BytesMut, order: &mut Order) -> Result<(), io::Error> {
+    let conditions_count = buf.read_int()?;
+
+    if conditions_count > 0 {
+        for _ in 0..conditions_count {
+            let order_condition_type = buf.read_int()?;
+            let condition = order_condition_read(buf, order_condition_type)?;
+            order.conditions.push(condition);
+        }
+
+        order.conditions_ignore_rth = buf.read_bool()?;
+        order.conditions_cancel_order = buf.read_bool()?;
+    }
+
+    Ok(())
+}
+
+// empty means "not set", anything else is a 0/1 flag
+fn read_optional_bool(buf: &mut BytesMut) -> Result<Option<bool>, io::Error> {
+    let v = buf.read_int_max()?;
+    Ok(if v == i32::MAX { None } else { Some(v != 0) })
+}
+
 fn order_condition_read(
     buf: &mut BytesMut,
     condition_type: i32,
@@ -588,6 +655,202 @@ pub fn decode_open_order_end_msg(
     ))
 }
 
+// [NO REQ_ID]
+// Completed orders carry neither a message version nor an order id.
+pub fn decode_completed_order_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let mut contract: Contract = Default::default();
+    contract.con_id = buf.read_int()?;
+    contract.symbol = buf.read_string()?;
+    contract.sec_type = buf.read_string()?;
+    contract.last_trade_date_or_contract_month = buf.read_string()?;
+    contract.strike = buf.read_double()?;
+    contract.right = buf.read_string()?;
+    contract.multiplier = buf.read_string()?;
+    contract.exchange = buf.read_string()?;
+    contract.currency = buf.read_string()?;
+    contract.local_symbol = buf.read_string()?;
+    contract.trading_class = buf.read_string()?;
+
+    let mut order: Order = Default::default();
+    order.action = buf.read_string()?;
+    order.total_quantity = buf.read_decimal()?;
+    order.order_type = buf.read_string()?;
+    order.lmt_price = buf.read_double_max()?;
+    order.aux_price = buf.read_double_max()?;
+    order.tif = buf.read_string()?;
+    order.oca_group = buf.read_string()?;
+    order.account = buf.read_string()?;
+    order.open_close = buf.read_string()?;
+    order.origin = buf.read_int()?;
+    order.order_ref = buf.read_string()?;
+    order.perm_id = buf.read_int()?;
+    order.outside_rth = buf.read_bool()?;
+    order.hidden = buf.read_bool()?;
+    order.discretionary_amt = buf.read_double()?;
+    order.good_after_time = buf.read_string()?;
+
+    order.fa_group = buf.read_string()?;
+    order.fa_method = buf.read_string()?;
+    order.fa_percentage = buf.read_string()?;
+    order.fa_profile = buf.read_string()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_MODELS_SUPPORT {
+        order.model_code = buf.read_string()?;
+    }
+
+    order.good_till_date = buf.read_string()?;
+    order.rule_80a = buf.read_string()?;
+    order.percent_offset = buf.read_double_max()?;
+    order.settling_firm = buf.read_string()?;
+    order.short_sale_slot = buf.read_int()?;
+    order.designated_location = buf.read_string()?;
+    order.exempt_code = buf.read_int()?;
+    order.starting_price = buf.read_double_max()?;
+    order.stock_ref_price = buf.read_double_max()?;
+    order.delta = buf.read_double_max()?;
+    order.stock_range_lower = buf.read_double_max()?;
+    order.stock_range_upper = buf.read_double_max()?;
+    order.display_size = buf.read_decimal_max()?;
+    order.sweep_to_fill = buf.read_bool()?;
+    order.all_or_none = buf.read_bool()?;
+    order.min_qty = buf.read_decimal_max()?;
+    order.oca_type = buf.read_int()?;
+    order.trigger_method = buf.read_int()?;
+
+    order.volatility = buf.read_double_max()?;
+    order.volatility_type = buf.read_int()?;
+    order.delta_neutral_order_type = buf.read_string()?;
+    order.delta_neutral_aux_price = buf.read_double_max()?;
+    if !order.delta_neutral_order_type.is_empty() {
+        order.delta_neutral_con_id = buf.read_int()?;
+        order.delta_neutral_short_sale = buf.read_bool()?;
+        order.delta_neutral_short_sale_slot = buf.read_int()?;
+        order.delta_neutral_designated_location = buf.read_string()?;
+    }
+    order.continuous_update = buf.read_int()?;
+    order.reference_price_type = buf.read_int()?;
+
+    order.trail_stop_price = buf.read_double_max()?;
+    order.trailing_percent = buf.read_double_max()?;
+
+    contract.combo_legs_descrip = buf.read_string()?;
+    read_combo_legs(buf, &mut contract, &mut order)?;
+
+    let count = buf.read_int()?;
+    for _ in 0..count {
+        let tag = buf.read_string()?;
+        let value = buf.read_string()?;
+        order
+            .smart_combo_routing_params
+            .push(TagValue { tag, value });
+    }
+
+    order.scale_init_level_size = buf.read_int_max()?;
+    order.scale_subs_level_size = buf.read_int_max()?;
+    order.scale_price_increment = buf.read_double_max()?;
+    if order.scale_price_increment > 0.0 && order.scale_price_increment != f64::MAX {
+        order.scale_price_adjust_value = buf.read_double_max()?;
+        order.scale_price_adjust_interval = buf.read_int_max()?;
+        order.scale_profit_offset = buf.read_double_max()?;
+        order.scale_auto_reset = buf.read_bool()?;
+        order.scale_init_position = buf.read_int_max()?;
+        order.scale_init_fill_qty = buf.read_int_max()?;
+        order.scale_random_percent = buf.read_bool()?;
+    }
+
+    order.hedge_type = buf.read_string()?;
+    if !order.hedge_type.is_empty() {
+        order.hedge_param = buf.read_string()?;
+    }
+
+    order.clearing_account = buf.read_string()?;
+    order.clearing_intent = buf.read_string()?;
+    order.not_held = buf.read_bool()?;
+
+    if buf.read_bool()? {
+        let con_id = buf.read_int()?;
+        let delta = buf.read_double()?;
+        let price = buf.read_double()?;
+        contract.delta_neutral_contract = Some(DeltaNeutralContract {
+            con_id,
+            delta,
+            price,
+        });
+    }
+
+    order.algo_strategy = buf.read_string()?;
+    if !order.algo_strategy.is_empty() {
+        let count = buf.read_int()?;
+        for _ in 0..count {
+            let tag = buf.read_string()?;
+            let value = buf.read_string()?;
+            order.algo_params.push(TagValue { tag, value });
+        }
+    }
+
+    order.solicited = buf.read_bool()?;
+
+    let mut order_state: OrderState = Default::default();
+    order_state.status = buf.read_string()?;
+
+    order.randomize_size = buf.read_bool()?;
+    order.randomize_price = buf.read_bool()?;
+
+    if order.order_type == OrderType::PEG_BENCH.to_string() {
+        order.reference_contract_id = buf.read_int()?;
+        order.is_pegged_change_amount_decrease = buf.read_bool()?;
+        order.pegged_change_amount = buf.read_double()?;
+        order.reference_change_amount = buf.read_double()?;
+        order.reference_exchange_id = buf.read_string()?;
+    }
+
+    read_order_conditions(buf, &mut order)?;
+
+    order.trail_stop_price = buf.read_double_max()?;
+    order.lmt_price_offset = buf.read_double_max()?;
+    order.cash_qty = buf.read_double_max()?;
+    order.dont_use_auto_price_for_hedge = buf.read_bool()?;
+    order.is_oms_container = buf.read_bool()?;
+    order.auto_cancel_date = buf.read_string()?;
+    order.filled_quantity = buf.read_decimal_max()?;
+    order.ref_futures_con_id = buf.read_int()?;
+    order.auto_cancel_parent = buf.read_bool()?;
+    order.shareholder = buf.read_string()?;
+    order.imbalance_only = buf.read_bool()?;
+    order.route_marketable_to_bbo = buf.read_bool()?;
+    order.parent_perm_id = buf.read_long()?;
+
+    order_state.completed_time = buf.read_string()?;
+    order_state.completed_status = buf.read_string()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        read_peg_best_peg_mid_attributes(buf, &mut order)?;
+    }
+
+    Ok((
+        Response::CompletedOrderMsg(CompletedOrderMsg {
+            contract,
+            order,
+            order_state,
+        }),
+        OPCODE_REQ_COMPLETED_ORDERS,
+    ))
+}
+
+// [NO REQ_ID]
+pub fn decode_completed_orders_end_msg(
+    _ctx: &mut Context,
+    _buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    Ok((
+        Response::CompletedOrdersEndMsg(CompletedOrdersEndMsg {}),
+        OPCODE_REQ_COMPLETED_ORDERS,
+    ))
+}
+
 fn encoder_order_condition(buf: &mut BytesMut, order_condition: &OrderCondition) {
     match order_condition {
         OrderCondition::PriceCondition(ref pc) => {
@@ -663,7 +926,9 @@ pub fn encode_place_order(
     const VERSION: i32 = 45;
 
     buf.push_int(PLACE_ORDER);
-    buf.push_int(VERSION);
+    if ctx.server_version() < MIN_SERVER_VER_ORDER_CONTAINER {
+        buf.push_int(VERSION);
+    }
     buf.push_int(req.id);
 
     encode_contract(buf, &req.contract);
@@ -673,10 +938,12 @@ pub fn encode_place_order(
 
     buf.push_string(&req.order.action);
 
-    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.push_double(req.order.total_quantity)
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT {
+        buf.push_decimal(req.order.total_quantity);
+    } else if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_double(req.order.total_quantity.to_f64().unwrap_or_default());
     } else {
-        buf.push_int(req.order.total_quantity as i32);
+        buf.push_int(req.order.total_quantity.to_i32().unwrap_or_default());
     }
 
     buf.push_string(&req.order.order_type);
@@ -694,7 +961,11 @@ pub fn encode_place_order(
 
     buf.push_bool(req.order.block_order);
     buf.push_bool(req.order.sweep_to_fill);
-    buf.push_int(req.order.display_size);
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT {
+        buf.push_decimal(req.order.display_size);
+    } else {
+        buf.push_int(req.order.display_size.to_i32().unwrap_or_default());
+    }
     buf.push_int(req.order.trigger_method);
     buf.push_bool(req.order.outside_rth);
     buf.push_bool(req.order.hidden);
@@ -745,7 +1016,11 @@ pub fn encode_place_order(
     buf.push_string(&req.order.rule_80a);
     buf.push_string(&req.order.settling_firm);
     buf.push_bool(req.order.all_or_none);
-    buf.push_int_max(req.order.min_qty);
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT {
+        buf.push_decimal_max(req.order.min_qty);
+    } else {
+        buf.push_int_max(req.order.min_qty.to_i32().unwrap_or(i32::MAX));
+    }
     buf.push_double_max(req.order.percent_offset);
     buf.push_bool(req.order.etrade_only);
     buf.push_bool(req.order.firm_quote_only);
@@ -758,7 +1033,7 @@ pub fn encode_place_order(
     buf.push_double_max(req.order.stock_range_upper);
     buf.push_bool(req.order.override_percentage_constraints);
     buf.push_double_max(req.order.volatility);
-    buf.push_int(req.order.volatility_type);
+    buf.push_int_max(req.order.volatility_type);
     buf.push_string(&req.order.delta_neutral_order_type);
     buf.push_double_max(req.order.delta_neutral_aux_price);
 
@@ -775,7 +1050,7 @@ pub fn encode_place_order(
     }
 
     buf.push_int(req.order.continuous_update);
-    buf.push_int(req.order.reference_price_type);
+    buf.push_int_max(req.order.reference_price_type);
     buf.push_double_max(req.order.trail_stop_price);
     buf.push_double_max(req.order.trailing_percent);
     buf.push_int_max(req.order.scale_init_level_size);
@@ -811,6 +1086,8 @@ pub fn encode_place_order(
         buf.push_int(uc.con_id);
         buf.push_double(uc.delta);
         buf.push_double(uc.price);
+    } else {
+        buf.push_bool(false);
     }
 
     buf.push_string(&req.order.algo_strategy);
@@ -842,14 +1119,15 @@ pub fn encode_place_order(
 
         buf.push_int(req.order.conditions.len() as i32);
 
-        // TODO
-        for item in &req.order.conditions {
-            buf.push_int(item.type_val());
-            encoder_order_condition(buf, item);
-        }
+        if !req.order.conditions.is_empty() {
+            for item in &req.order.conditions {
+                buf.push_int(item.type_val());
+                encoder_order_condition(buf, item);
+            }
 
-        buf.push_bool(req.order.conditions_ignore_rth);
-        buf.push_bool(req.order.conditions_cancel_order);
+            buf.push_bool(req.order.conditions_ignore_rth);
+            buf.push_bool(req.order.conditions_cancel_order);
+        }
 
         buf.push_string(req.order.adjusted_order_type.to_string().as_str());
         buf.push_double(req.order.trigger_price);
@@ -869,11 +1147,84 @@ pub fn encode_place_order(
         buf.push_string(&req.order.soft_dollar_tier.value);
     }
 
+    if ctx.server_version() >= MIN_SERVER_VER_CASH_QTY {
+        buf.push_double_max(req.order.cash_qty);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DECISION_MAKER {
+        buf.push_string(&req.order.mifid2_decision_maker);
+        buf.push_string(&req.order.mified2_decision_algo);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_MIFID_EXECUTION {
+        buf.push_string(&req.order.mified2_execution_trader);
+        buf.push_string(&req.order.mified2_execution_algo);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_PRICE_FOR_HEDGE {
+        buf.push_bool(req.order.dont_use_auto_price_for_hedge);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_ORDER_CONTAINER {
+        buf.push_bool(req.order.is_oms_container);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_D_PEG_ORDERS {
+        buf.push_bool(req.order.discretionary_up_to_limit_price);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PRICE_MGMT_ALGO {
+        match req.order.use_price_mgmt_algo {
+            Some(value) => buf.push_bool(value),
+            None => buf.push_string(""),
+        }
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DURATION {
+        buf.push_int_max(req.order.duration);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_POST_TO_ATS {
+        buf.push_int_max(req.order.post_to_ats);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_CANCEL_PARENT {
+        buf.push_bool(req.order.auto_cancel_parent);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_ADVANCED_ORDER_REJECT {
+        buf.push_string(&req.order.advanced_error_override);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_MANUAL_ORDER_TIME {
+        buf.push_string(&req.order.manual_order_time);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        let mut send_mid_offsets = false;
+        if req.contract.exchange == "IBKRATS" {
+            buf.push_int_max(req.order.min_trade_qty);
+        }
+        if req.order.order_type == OrderType::PEG_BEST.to_string() {
+            buf.push_int_max(req.order.min_compete_size);
+            buf.push_double_max(req.order.compete_against_best_offset);
+            if req.order.is_compete_against_best_offset_up_to_mid() {
+                send_mid_offsets = true;
+            }
+        } else if req.order.order_type == OrderType::PEG_MID.to_string() {
+            send_mid_offsets = true;
+        }
+        if send_mid_offsets {
+            buf.push_double_max(req.order.mid_offset_at_whole);
+            buf.push_double_max(req.order.mid_offset_at_half);
+        }
+    }
+
     Ok(DispatchId::Oneshot(req.id))
 }
 
 pub fn encode_cancel_order(
-    _ctx: &mut Context,
+    ctx: &mut Context,
     buf: &mut BytesMut,
     req: &CacelOrder,
 ) -> Result<DispatchId, EncodeError> {
@@ -883,6 +1234,10 @@ pub fn encode_cancel_order(
     buf.push_int(VERSION);
     buf.push_int(req.id);
 
+    if ctx.server_version() >= MIN_SERVER_VER_MANUAL_ORDER_TIME {
+        buf.push_string(&req.manual_order_cancel_time);
+    }
+
     Ok(DispatchId::Oneshot(req.id))
 }
 
@@ -925,3 +1280,867 @@ pub fn encode_req_all_open_orders(
 
     Ok(DispatchId::Global(OPCODE_REQ_ALL_OPEN_ORDERS))
 }
+
+pub fn encode_req_completed_orders(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &ReqCompletedOrders,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_REQ_COMPLETED_ORDERS {
+        return Err(EncodeError::VersionLessError(
+            MIN_SERVER_VER_REQ_COMPLETED_ORDERS,
+        ));
+    }
+
+    buf.push_int(REQ_COMPLETED_ORDERS);
+    buf.push_bool(req.api_only);
+
+    Ok(DispatchId::Global(OPCODE_REQ_COMPLETED_ORDERS))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn encode_open_order_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &OpenOrderMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 34;
+
+    let order = &msg.order;
+    let contract = &msg.contract;
+    let order_state = &msg.order_state;
+
+    buf.push_int(OPEN_ORDER);
+    if ctx.server_version() < MIN_SERVER_VER_ORDER_CONTAINER {
+        buf.push_int(VERSION);
+    }
+    buf.push_int(msg.order_id);
+
+    buf.push_int(contract.con_id);
+    buf.push_string(&contract.symbol);
+    buf.push_string(&contract.sec_type);
+    buf.push_string(&contract.last_trade_date_or_contract_month);
+    buf.push_double(contract.strike);
+    buf.push_string(&contract.right);
+    buf.push_string(&contract.multiplier);
+    buf.push_string(&contract.exchange);
+    buf.push_string(&contract.currency);
+    buf.push_string(&contract.local_symbol);
+    buf.push_string(&contract.trading_class);
+
+    buf.push_string(&order.action);
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_decimal(order.total_quantity);
+    } else {
+        buf.push_int(order.total_quantity.to_i32().unwrap_or_default());
+    }
+    buf.push_string(&order.order_type);
+    buf.push_double_max(order.lmt_price);
+    buf.push_double_max(order.aux_price);
+    buf.push_string(&order.tif);
+    buf.push_string(&order.oca_group);
+    buf.push_string(&order.account);
+    buf.push_string(&order.open_close);
+    buf.push_int(order.origin);
+    buf.push_string(&order.order_ref);
+    buf.push_int(order.client_id);
+    buf.push_int(order.perm_id);
+    buf.push_bool(order.outside_rth);
+    buf.push_bool(order.hidden);
+    buf.push_double(order.discretionary_amt);
+    buf.push_string(&order.good_after_time);
+    buf.push_string(""); // deprecated sharesAllocation field
+    buf.push_string(&order.fa_group);
+    buf.push_string(&order.fa_method);
+    buf.push_string(&order.fa_percentage);
+    buf.push_string(&order.fa_profile);
+
+    if ctx.server_version() >= MIN_SERVER_VER_MODELS_SUPPORT {
+        buf.push_string(&order.model_code);
+    }
+
+    buf.push_string(&order.good_till_date);
+    buf.push_string(&order.rule_80a);
+    buf.push_double_max(order.percent_offset);
+    buf.push_string(&order.settling_firm);
+    buf.push_int(order.short_sale_slot);
+    buf.push_string(&order.designated_location);
+    buf.push_int(order.exempt_code);
+    buf.push_int(order.auction_strategy);
+    buf.push_double_max(order.starting_price);
+    buf.push_double_max(order.stock_ref_price);
+    buf.push_double_max(order.delta);
+    buf.push_double_max(order.stock_range_lower);
+    buf.push_double_max(order.stock_range_upper);
+    buf.push_decimal_max(order.display_size);
+    buf.push_bool(order.block_order);
+    buf.push_bool(order.sweep_to_fill);
+    buf.push_bool(order.all_or_none);
+    buf.push_decimal_max(order.min_qty);
+    buf.push_int(order.oca_type);
+    buf.push_bool(order.etrade_only);
+    buf.push_bool(order.firm_quote_only);
+    buf.push_double_max(order.nbbo_price_cap);
+
+    buf.push_int(order.parent_id);
+    buf.push_int(order.trigger_method);
+
+    buf.push_double_max(order.volatility);
+    buf.push_int(order.volatility_type);
+    buf.push_string(&order.delta_neutral_order_type);
+    buf.push_double_max(order.delta_neutral_aux_price);
+    if !order.delta_neutral_order_type.is_empty() {
+        buf.push_int(order.delta_neutral_con_id);
+        buf.push_string(&order.delta_neutral_settling_firm);
+        buf.push_string(&order.delta_neutral_clearing_account);
+        buf.push_string(&order.delta_neutral_clearing_intent);
+
+        buf.push_string(&order.delta_neutral_open_close);
+        buf.push_bool(order.delta_neutral_short_sale);
+        buf.push_int(order.delta_neutral_short_sale_slot);
+        buf.push_string(&order.delta_neutral_designated_location);
+    }
+    buf.push_int(order.continuous_update);
+    if ctx.server_version() == 26 {
+        buf.push_double(order.stock_range_lower);
+        buf.push_double(order.stock_range_upper);
+    }
+    buf.push_int(order.reference_price_type);
+
+    buf.push_double_max(order.trail_stop_price);
+    buf.push_double_max(order.trailing_percent);
+
+    buf.push_double_max(order.basis_points);
+    buf.push_int_max(order.basis_points_type);
+    buf.push_string(&contract.combo_legs_descrip);
+
+    write_combo_legs(buf, contract, order);
+    write_tag_values(buf, &order.smart_combo_routing_params);
+
+    buf.push_int_max(order.scale_init_level_size);
+    buf.push_int_max(order.scale_subs_level_size);
+    buf.push_double_max(order.scale_price_increment);
+    write_scale_attributes(buf, order);
+
+    buf.push_string(&order.hedge_type);
+    if !order.hedge_type.is_empty() {
+        buf.push_string(&order.hedge_param);
+    }
+
+    buf.push_bool(order.opt_out_smart_routing);
+    buf.push_string(&order.clearing_account);
+    buf.push_string(&order.clearing_intent);
+    buf.push_bool(order.not_held);
+
+    write_delta_neutral_contract(buf, contract);
+
+    buf.push_string(&order.algo_strategy);
+    if !order.algo_strategy.is_empty() {
+        write_tag_values(buf, &order.algo_params);
+    }
+
+    buf.push_bool(order.solicited);
+
+    buf.push_bool(order.what_if);
+    buf.push_string(&order_state.status);
+    if ctx.server_version() >= MIN_SERVER_VER_WHAT_IF_EXT_FIELDS {
+        buf.push_string(&order_state.init_margin_before);
+        buf.push_string(&order_state.maint_margin_before);
+        buf.push_string(&order_state.equity_with_loan_before);
+        buf.push_string(&order_state.init_margin_change);
+        buf.push_string(&order_state.maint_margin_change);
+        buf.push_string(&order_state.equity_with_loan_change);
+    }
+    buf.push_string(&order_state.init_margin_after);
+    buf.push_string(&order_state.maint_margin_after);
+    buf.push_string(&order_state.equity_with_loan_after);
+    buf.push_double_max(order_state.commission);
+    buf.push_double_max(order_state.min_commission);
+    buf.push_double_max(order_state.max_commission);
+    buf.push_string(&order_state.commission_currency);
+    buf.push_string(&order_state.warning_text);
+
+    buf.push_bool(order.randomize_size);
+    buf.push_bool(order.randomize_price);
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGGED_TO_BENCHMARK {
+        write_pegged_to_benchmark_attributes(buf, order);
+        write_order_conditions(buf, order);
+
+        buf.push_string(&order.adjusted_order_type);
+        buf.push_double_max(order.trigger_price);
+        buf.push_double_max(order.trail_stop_price);
+        buf.push_double_max(order.lmt_price_offset);
+        buf.push_double_max(order.adjusted_stop_price);
+        buf.push_double_max(order.adjusted_stop_limit_price);
+        buf.push_double_max(order.adjusted_trailing_amount);
+        buf.push_int(order.adjustable_trailing_unit);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_SOFT_DOLLAR_TIER {
+        buf.push_string(&order.soft_dollar_tier.name);
+        buf.push_string(&order.soft_dollar_tier.value);
+        buf.push_string(&order.soft_dollar_tier.display_name);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_CASH_QTY {
+        buf.push_double_max(order.cash_qty);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_PRICE_FOR_HEDGE {
+        buf.push_bool(order.dont_use_auto_price_for_hedge);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_ORDER_CONTAINER {
+        buf.push_bool(order.is_oms_container);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_D_PEG_ORDERS {
+        buf.push_bool(order.discretionary_up_to_limit_price);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PRICE_MGMT_ALGO {
+        write_optional_bool(buf, order.use_price_mgmt_algo);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DURATION {
+        buf.push_int_max(order.duration);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_POST_TO_ATS {
+        buf.push_int_max(order.post_to_ats);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_CANCEL_PARENT {
+        buf.push_bool(order.auto_cancel_parent);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        write_peg_best_peg_mid_attributes(buf, order);
+    }
+
+    Ok(())
+}
+
+pub fn encode_order_status_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &OrderStatusMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 6;
+
+    buf.push_int(ORDER_STATUS);
+    if ctx.server_version() < MIN_SERVER_VER_MARKET_CAP_PRICE {
+        buf.push_int(VERSION);
+    }
+    buf.push_int(msg.id);
+    buf.push_string(&msg.status);
+
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_decimal(msg.filled);
+        buf.push_decimal(msg.remaining);
+    } else {
+        buf.push_int(msg.filled.to_i32().unwrap_or_default());
+        buf.push_int(msg.remaining.to_i32().unwrap_or_default());
+    }
+
+    buf.push_double(msg.avg_fill_price);
+    buf.push_int(msg.perm_id);
+    buf.push_int(msg.parent_id);
+    buf.push_double(msg.last_fill_price);
+    buf.push_int(msg.client_id);
+    buf.push_string(&msg.why_held);
+
+    if ctx.server_version() >= MIN_SERVER_VER_MARKET_CAP_PRICE {
+        buf.push_double(msg.mkt_cap_price);
+    }
+
+    Ok(())
+}
+
+fn write_combo_legs(buf: &mut BytesMut, contract: &Contract, order: &Order) {
+    buf.push_int(contract.combo_legs.len() as i32);
+    for elem in &contract.combo_legs {
+        buf.push_int(elem.con_id);
+        buf.push_int(elem.ratio);
+        buf.push_string(&elem.action);
+        buf.push_string(&elem.exchange);
+        buf.push_int(elem.open_close);
+        buf.push_int(elem.short_sale_slot);
+        buf.push_string(&elem.designated_location);
+        buf.push_int(elem.exempt_code);
+    }
+
+    buf.push_int(order.order_combo_legs.len() as i32);
+    for elem in &order.order_combo_legs {
+        buf.push_double_max(elem.price);
+    }
+}
+
+fn write_tag_values(buf: &mut BytesMut, tag_values: &[TagValue]) {
+    buf.push_int(tag_values.len() as i32);
+    for elem in tag_values {
+        buf.push_string(&elem.tag);
+        buf.push_string(&elem.value);
+    }
+}
+
+fn read_tag_values(buf: &mut BytesMut) -> Result<Vec<TagValue>, io::Error> {
+    let count = buf.read_int()?;
+    let mut tag_values = Vec::new();
+    for _ in 0..count {
+        let tag = buf.read_string()?;
+        let value = buf.read_string()?;
+        tag_values.push(TagValue { tag, value });
+    }
+    Ok(tag_values)
+}
+
+fn write_scale_attributes(buf: &mut BytesMut, order: &Order) {
+    if order.scale_price_increment > 0.0 && order.scale_price_increment != f64::MAX {
+        buf.push_double_max(order.scale_price_adjust_value);
+        buf.push_int_max(order.scale_price_adjust_interval);
+        buf.push_double_max(order.scale_profit_offset);
+        buf.push_bool(order.scale_auto_reset);
+        buf.push_int_max(order.scale_init_position);
+        buf.push_int_max(order.scale_init_fill_qty);
+        buf.push_bool(order.scale_random_percent);
+    }
+}
+
+fn write_delta_neutral_contract(buf: &mut BytesMut, contract: &Contract) {
+    if let Some(ref dnc) = contract.delta_neutral_contract {
+        buf.push_bool(true);
+        buf.push_int(dnc.con_id);
+        buf.push_double(dnc.delta);
+        buf.push_double(dnc.price);
+    } else {
+        buf.push_bool(false);
+    }
+}
+
+fn read_delta_neutral_contract(buf: &mut BytesMut) -> Result<Option<DeltaNeutralContract>, io::Error> {
+    if !buf.read_bool()? {
+        return Ok(None);
+    }
+    let con_id = buf.read_int()?;
+    let delta = buf.read_double()?;
+    let price = buf.read_double()?;
+    Ok(Some(DeltaNeutralContract {
+        con_id,
+        delta,
+        price,
+    }))
+}
+
+fn write_pegged_to_benchmark_attributes(buf: &mut BytesMut, order: &Order) {
+    if order.order_type == OrderType::PEG_BENCH.to_string() {
+        buf.push_int(order.reference_contract_id);
+        buf.push_bool(order.is_pegged_change_amount_decrease);
+        buf.push_double(order.pegged_change_amount);
+        buf.push_double(order.reference_change_amount);
+        buf.push_string(&order.reference_exchange_id);
+    }
+}
+
+fn read_pegged_to_benchmark_attributes(
+    buf: &mut BytesMut,
+    order: &mut Order,
+) -> Result<(), io::Error> {
+    if order.order_type == OrderType::PEG_BENCH.to_string() {
+        order.reference_contract_id = buf.read_int()?;
+        order.is_pegged_change_amount_decrease = buf.read_bool()?;
+        order.pegged_change_amount = buf.read_double()?;
+        order.reference_change_amount = buf.read_double()?;
+        order.reference_exchange_id = buf.read_string()?;
+    }
+    Ok(())
+}
+
+fn write_order_conditions(buf: &mut BytesMut, order: &Order) {
+    buf.push_int(order.conditions.len() as i32);
+
+    if !order.conditions.is_empty() {
+        for item in &order.conditions {
+            buf.push_int(item.type_val());
+            encoder_order_condition(buf, item);
+        }
+
+        buf.push_bool(order.conditions_ignore_rth);
+        buf.push_bool(order.conditions_cancel_order);
+    }
+}
+
+fn write_optional_bool(buf: &mut BytesMut, value: Option<bool>) {
+    match value {
+        Some(value) => buf.push_bool(value),
+        None => buf.push_string(""),
+    }
+}
+
+fn write_peg_best_peg_mid_attributes(buf: &mut BytesMut, order: &Order) {
+    buf.push_int_max(order.min_trade_qty);
+    buf.push_int_max(order.min_compete_size);
+    buf.push_double_max(order.compete_against_best_offset);
+    buf.push_double_max(order.mid_offset_at_whole);
+    buf.push_double_max(order.mid_offset_at_half);
+}
+
+pub fn encode_open_order_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    _msg: &OpenOrderEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(OPEN_ORDER_END);
+    buf.push_int(VERSION);
+
+    Ok(())
+}
+
+pub fn encode_completed_order_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &CompletedOrderMsg,
+) -> Result<(), EncodeError> {
+    let order = &msg.order;
+    let contract = &msg.contract;
+    let order_state = &msg.order_state;
+
+    buf.push_int(COMPLETED_ORDER);
+
+    buf.push_int(contract.con_id);
+    buf.push_string(&contract.symbol);
+    buf.push_string(&contract.sec_type);
+    buf.push_string(&contract.last_trade_date_or_contract_month);
+    buf.push_double(contract.strike);
+    buf.push_string(&contract.right);
+    buf.push_string(&contract.multiplier);
+    buf.push_string(&contract.exchange);
+    buf.push_string(&contract.currency);
+    buf.push_string(&contract.local_symbol);
+    buf.push_string(&contract.trading_class);
+
+    buf.push_string(&order.action);
+    buf.push_decimal(order.total_quantity);
+    buf.push_string(&order.order_type);
+    buf.push_double_max(order.lmt_price);
+    buf.push_double_max(order.aux_price);
+    buf.push_string(&order.tif);
+    buf.push_string(&order.oca_group);
+    buf.push_string(&order.account);
+    buf.push_string(&order.open_close);
+    buf.push_int(order.origin);
+    buf.push_string(&order.order_ref);
+    buf.push_int(order.perm_id);
+    buf.push_bool(order.outside_rth);
+    buf.push_bool(order.hidden);
+    buf.push_double(order.discretionary_amt);
+    buf.push_string(&order.good_after_time);
+
+    buf.push_string(&order.fa_group);
+    buf.push_string(&order.fa_method);
+    buf.push_string(&order.fa_percentage);
+    buf.push_string(&order.fa_profile);
+
+    if ctx.server_version() >= MIN_SERVER_VER_MODELS_SUPPORT {
+        buf.push_string(&order.model_code);
+    }
+
+    buf.push_string(&order.good_till_date);
+    buf.push_string(&order.rule_80a);
+    buf.push_double_max(order.percent_offset);
+    buf.push_string(&order.settling_firm);
+    buf.push_int(order.short_sale_slot);
+    buf.push_string(&order.designated_location);
+    buf.push_int(order.exempt_code);
+    buf.push_double_max(order.starting_price);
+    buf.push_double_max(order.stock_ref_price);
+    buf.push_double_max(order.delta);
+    buf.push_double_max(order.stock_range_lower);
+    buf.push_double_max(order.stock_range_upper);
+    buf.push_decimal_max(order.display_size);
+    buf.push_bool(order.sweep_to_fill);
+    buf.push_bool(order.all_or_none);
+    buf.push_decimal_max(order.min_qty);
+    buf.push_int(order.oca_type);
+    buf.push_int(order.trigger_method);
+
+    buf.push_double_max(order.volatility);
+    buf.push_int(order.volatility_type);
+    buf.push_string(&order.delta_neutral_order_type);
+    buf.push_double_max(order.delta_neutral_aux_price);
+    if !order.delta_neutral_order_type.is_empty() {
+        buf.push_int(order.delta_neutral_con_id);
+        buf.push_bool(order.delta_neutral_short_sale);
+        buf.push_int(order.delta_neutral_short_sale_slot);
+        buf.push_string(&order.delta_neutral_designated_location);
+    }
+    buf.push_int(order.continuous_update);
+    buf.push_int(order.reference_price_type);
+
+    buf.push_double_max(order.trail_stop_price);
+    buf.push_double_max(order.trailing_percent);
+
+    buf.push_string(&contract.combo_legs_descrip);
+    write_combo_legs(buf, contract, order);
+    write_tag_values(buf, &order.smart_combo_routing_params);
+
+    buf.push_int_max(order.scale_init_level_size);
+    buf.push_int_max(order.scale_subs_level_size);
+    buf.push_double_max(order.scale_price_increment);
+    write_scale_attributes(buf, order);
+
+    buf.push_string(&order.hedge_type);
+    if !order.hedge_type.is_empty() {
+        buf.push_string(&order.hedge_param);
+    }
+
+    buf.push_string(&order.clearing_account);
+    buf.push_string(&order.clearing_intent);
+    buf.push_bool(order.not_held);
+
+    write_delta_neutral_contract(buf, contract);
+
+    buf.push_string(&order.algo_strategy);
+    if !order.algo_strategy.is_empty() {
+        write_tag_values(buf, &order.algo_params);
+    }
+
+    buf.push_bool(order.solicited);
+
+    buf.push_string(&order_state.status);
+
+    buf.push_bool(order.randomize_size);
+    buf.push_bool(order.randomize_price);
+
+    write_pegged_to_benchmark_attributes(buf, order);
+    write_order_conditions(buf, order);
+
+    buf.push_double_max(order.trail_stop_price);
+    buf.push_double_max(order.lmt_price_offset);
+    buf.push_double_max(order.cash_qty);
+    buf.push_bool(order.dont_use_auto_price_for_hedge);
+    buf.push_bool(order.is_oms_container);
+    buf.push_string(&order.auto_cancel_date);
+    buf.push_decimal_max(order.filled_quantity);
+    buf.push_int(order.ref_futures_con_id);
+    buf.push_bool(order.auto_cancel_parent);
+    buf.push_string(&order.shareholder);
+    buf.push_bool(order.imbalance_only);
+    buf.push_bool(order.route_marketable_to_bbo);
+    buf.push_long(order.parent_perm_id);
+
+    buf.push_string(&order_state.completed_time);
+    buf.push_string(&order_state.completed_status);
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        write_peg_best_peg_mid_attributes(buf, order);
+    }
+
+    Ok(())
+}
+
+pub fn encode_completed_orders_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    _msg: &CompletedOrdersEndMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(COMPLETED_ORDERS_END);
+
+    Ok(())
+}
+
+pub fn decode_place_order(ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    if ctx.server_version() < MIN_SERVER_VER_ORDER_CONTAINER {
+        let _version = buf.read_int()?;
+    }
+    let id = buf.read_int()?;
+
+    let mut contract = decode_contract(buf)?;
+    contract.sec_id_type = buf.read_string()?;
+    contract.sec_id = buf.read_string()?;
+
+    let mut order: Order = Default::default();
+    order.order_id = id;
+    order.action = buf.read_string()?;
+    // older servers get the quantity as an int or a double, both read back as a decimal
+    order.total_quantity = buf.read_decimal()?;
+    order.order_type = buf.read_string()?;
+    order.lmt_price = buf.read_double_max()?;
+    order.aux_price = buf.read_double_max()?;
+
+    order.tif = buf.read_string()?;
+    order.oca_group = buf.read_string()?;
+    order.account = buf.read_string()?;
+    order.open_close = buf.read_string()?;
+    order.origin = buf.read_int()?;
+    order.order_ref = buf.read_string()?;
+    order.transmit = buf.read_bool()?;
+    order.parent_id = buf.read_int()?;
+
+    order.block_order = buf.read_bool()?;
+    order.sweep_to_fill = buf.read_bool()?;
+    order.display_size = buf.read_decimal()?;
+    order.trigger_method = buf.read_int()?;
+    order.outside_rth = buf.read_bool()?;
+    order.hidden = buf.read_bool()?;
+
+    if contract.sec_type.to_uppercase() == "BAG" {
+        read_combo_legs(buf, &mut contract, &mut order)?;
+        order.smart_combo_routing_params = read_tag_values(buf)?;
+    }
+
+    // skip deprecated sharesAllocation field
+    let _ = buf.read_string()?;
+    order.discretionary_amt = buf.read_double()?;
+    order.good_after_time = buf.read_string()?;
+    order.good_till_date = buf.read_string()?;
+    order.fa_group = buf.read_string()?;
+    order.fa_method = buf.read_string()?;
+    order.fa_percentage = buf.read_string()?;
+    order.fa_profile = buf.read_string()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_MODELS_SUPPORT {
+        order.model_code = buf.read_string()?;
+    }
+
+    order.short_sale_slot = buf.read_int()?;
+    order.designated_location = buf.read_string()?;
+    order.exempt_code = buf.read_int()?;
+
+    order.oca_type = buf.read_int()?;
+    order.rule_80a = buf.read_string()?;
+    order.settling_firm = buf.read_string()?;
+    order.all_or_none = buf.read_bool()?;
+    order.min_qty = buf.read_decimal_max()?;
+    order.percent_offset = buf.read_double_max()?;
+    order.etrade_only = buf.read_bool()?;
+    order.firm_quote_only = buf.read_bool()?;
+    order.nbbo_price_cap = buf.read_double_max()?;
+    order.auction_strategy = buf.read_int_max()?;
+    order.starting_price = buf.read_double_max()?;
+    order.stock_ref_price = buf.read_double_max()?;
+    order.delta = buf.read_double_max()?;
+    order.stock_range_lower = buf.read_double_max()?;
+    order.stock_range_upper = buf.read_double_max()?;
+    order.override_percentage_constraints = buf.read_bool()?;
+    order.volatility = buf.read_double_max()?;
+    order.volatility_type = buf.read_int_max()?;
+    order.delta_neutral_order_type = buf.read_string()?;
+    order.delta_neutral_aux_price = buf.read_double_max()?;
+
+    if !order.delta_neutral_order_type.is_empty() {
+        order.delta_neutral_con_id = buf.read_int()?;
+        order.delta_neutral_settling_firm = buf.read_string()?;
+        order.delta_neutral_clearing_account = buf.read_string()?;
+        order.delta_neutral_clearing_intent = buf.read_string()?;
+
+        order.delta_neutral_open_close = buf.read_string()?;
+        order.delta_neutral_short_sale = buf.read_bool()?;
+        order.delta_neutral_short_sale_slot = buf.read_int()?;
+        order.delta_neutral_designated_location = buf.read_string()?;
+    }
+
+    order.continuous_update = buf.read_int()?;
+    order.reference_price_type = buf.read_int_max()?;
+    order.trail_stop_price = buf.read_double_max()?;
+    order.trailing_percent = buf.read_double_max()?;
+    order.scale_init_level_size = buf.read_int_max()?;
+    order.scale_subs_level_size = buf.read_int_max()?;
+    order.scale_price_increment = buf.read_double_max()?;
+
+    if order.scale_price_increment > 0.0 && order.scale_price_increment != f64::MAX {
+        order.scale_price_adjust_value = buf.read_double_max()?;
+        order.scale_price_adjust_interval = buf.read_int_max()?;
+        order.scale_profit_offset = buf.read_double_max()?;
+        order.scale_auto_reset = buf.read_bool()?;
+        order.scale_init_position = buf.read_int_max()?;
+        order.scale_init_fill_qty = buf.read_int_max()?;
+        order.scale_random_percent = buf.read_bool()?;
+    }
+
+    order.scale_table = buf.read_string()?;
+    order.active_start_time = buf.read_string()?;
+    order.active_stop_time = buf.read_string()?;
+
+    order.hedge_type = buf.read_string()?;
+    if !order.hedge_type.is_empty() {
+        order.hedge_param = buf.read_string()?;
+    }
+
+    order.opt_out_smart_routing = buf.read_bool()?;
+    order.clearing_account = buf.read_string()?;
+    order.clearing_intent = buf.read_string()?;
+    order.not_held = buf.read_bool()?;
+
+    contract.delta_neutral_contract = read_delta_neutral_contract(buf)?;
+
+    order.algo_strategy = buf.read_string()?;
+    if !order.algo_strategy.is_empty() {
+        order.algo_params = read_tag_values(buf)?;
+    }
+
+    order.algo_id = buf.read_string()?;
+    order.what_if = buf.read_bool()?;
+
+    order.order_misc_options = decode_tagvalue_from_string(buf)?;
+
+    order.solicited = buf.read_bool()?;
+    order.randomize_size = buf.read_bool()?;
+    order.randomize_price = buf.read_bool()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGGED_TO_BENCHMARK {
+        read_pegged_to_benchmark_attributes(buf, &mut order)?;
+        read_order_conditions(buf, &mut order)?;
+
+        order.adjusted_order_type = buf.read_string()?;
+        order.trigger_price = buf.read_double()?;
+        order.lmt_price_offset = buf.read_double()?;
+        order.adjusted_stop_price = buf.read_double()?;
+        order.adjusted_stop_limit_price = buf.read_double()?;
+        order.adjusted_trailing_amount = buf.read_double()?;
+        order.adjustable_trailing_unit = buf.read_int()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_EXT_OPERATOR {
+        order.ext_operator = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_SOFT_DOLLAR_TIER {
+        order.soft_dollar_tier.name = buf.read_string()?;
+        order.soft_dollar_tier.value = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_CASH_QTY {
+        order.cash_qty = buf.read_double_max()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DECISION_MAKER {
+        order.mifid2_decision_maker = buf.read_string()?;
+        order.mified2_decision_algo = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_MIFID_EXECUTION {
+        order.mified2_execution_trader = buf.read_string()?;
+        order.mified2_execution_algo = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_PRICE_FOR_HEDGE {
+        order.dont_use_auto_price_for_hedge = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_ORDER_CONTAINER {
+        order.is_oms_container = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_D_PEG_ORDERS {
+        order.discretionary_up_to_limit_price = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PRICE_MGMT_ALGO {
+        order.use_price_mgmt_algo = read_optional_bool(buf)?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_DURATION {
+        order.duration = buf.read_int_max()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_POST_TO_ATS {
+        order.post_to_ats = buf.read_int_max()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_AUTO_CANCEL_PARENT {
+        order.auto_cancel_parent = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_ADVANCED_ORDER_REJECT {
+        order.advanced_error_override = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_MANUAL_ORDER_TIME {
+        order.manual_order_time = buf.read_string()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_PEGBEST_PEGMID_OFFSETS {
+        let mut read_mid_offsets = false;
+        if contract.exchange == "IBKRATS" {
+            order.min_trade_qty = buf.read_int_max()?;
+        }
+        if order.order_type == OrderType::PEG_BEST.to_string() {
+            order.min_compete_size = buf.read_int_max()?;
+            order.compete_against_best_offset = buf.read_double_max()?;
+            if order.is_compete_against_best_offset_up_to_mid() {
+                read_mid_offsets = true;
+            }
+        } else if order.order_type == OrderType::PEG_MID.to_string() {
+            read_mid_offsets = true;
+        }
+        if read_mid_offsets {
+            order.mid_offset_at_whole = buf.read_double_max()?;
+            order.mid_offset_at_half = buf.read_double_max()?;
+        }
+    }
+
+    Ok(Request::PlaceOrder(PlaceOrder {
+        id,
+        contract,
+        order,
+    }))
+}
+
+pub fn decode_cancel_order(ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let id = buf.read_int()?;
+
+    let manual_order_cancel_time = if ctx.server_version() >= MIN_SERVER_VER_MANUAL_ORDER_TIME {
+        buf.read_string()?
+    } else {
+        "".to_string()
+    };
+
+    Ok(Request::CancelOrder(CacelOrder {
+        id,
+        manual_order_cancel_time,
+    }))
+}
+
+pub fn decode_req_open_orders(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqOpenOrders(ReqOpenOrders {}))
+}
+
+pub fn decode_req_auto_open_orders(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let auto_bind = buf.read_bool()?;
+
+    Ok(Request::ReqAutoOpenOrders(ReqAutoOpenOrders { auto_bind }))
+}
+
+pub fn decode_req_all_open_orders(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqAllOpenOrders(ReqAllOpenOrders {}))
+}
+
+pub fn decode_req_completed_orders(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let api_only = buf.read_bool()?;
+
+    Ok(Request::ReqCompletedOrders(ReqCompletedOrders { api_only }))
+}
@@ -2,6 +2,8 @@ use std::io;
 use std::{f64, i32};
 
 use bytes::BytesMut;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 
 use super::constants::*;
 use super::context::{Context, DispatchId};
@@ -107,7 +109,11 @@ pub fn decode_pnl_single_msg(
     buf: &mut BytesMut,
 ) -> Result<(Response, i32), io::Error> {
     let req_id = buf.read_int()?;
-    let pos = buf.read_int()?;
+    let pos = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT {
+        buf.read_decimal()?
+    } else {
+        Decimal::from(buf.read_int()?)
+    };
     let daily_pnl = buf.read_double()?;
     let mut unrealized_pnl = f64::MAX;
     let mut realized_pnl = f64::MAX;
@@ -134,3 +140,97 @@ pub fn decode_pnl_single_msg(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_pnl(_ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let account = buf.read_string()?;
+    let model_code = buf.read_string()?;
+
+    Ok(Request::ReqPnl(ReqPnl {
+        req_id,
+        account,
+        model_code,
+    }))
+}
+
+pub fn decode_cancel_pnl(_ctx: &mut Context, buf: &mut BytesMut) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelPnl(CancelPnl { req_id }))
+}
+
+pub fn encode_pnl_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PnlMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(PNL);
+    buf.push_int(msg.req_id);
+    buf.push_double(msg.daily_pnl);
+
+    if ctx.server_version() >= MIN_SERVER_VER_UNREALIZED_PNL {
+        buf.push_double(msg.unrealized_pnl);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_REALIZED_PNL {
+        buf.push_double(msg.realized_pnl);
+    }
+
+    Ok(())
+}
+
+pub fn decode_req_pnl_single(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+    let account = buf.read_string()?;
+    let model_code = buf.read_string()?;
+    let con_id = buf.read_int()?;
+
+    Ok(Request::ReqPnlSingle(ReqPnlSingle {
+        req_id,
+        account,
+        model_code,
+        con_id,
+    }))
+}
+
+pub fn decode_cancel_pnl_single(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelPnlSingle(CancelPnlSingle { req_id }))
+}
+
+pub fn encode_pnl_single_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PnlSingleMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(PNL_SINGLE);
+    buf.push_int(msg.req_id);
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT {
+        buf.push_decimal(msg.pos);
+    } else {
+        buf.push_int(msg.pos.to_i32().unwrap_or_default());
+    }
+    buf.push_double(msg.daily_pnl);
+
+    if ctx.server_version() >= MIN_SERVER_VER_UNREALIZED_PNL {
+        buf.push_double(msg.unrealized_pnl);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_REALIZED_PNL {
+        buf.push_double(msg.realized_pnl);
+    }
+
+    buf.push_double(msg.value);
+
+    Ok(())
+}
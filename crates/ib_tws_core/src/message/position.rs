@@ -1,6 +1,8 @@
 use std::io;
 
 use bytes::BytesMut;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 
 use super::constants::*;
 use super::context::{Context, DispatchId};
@@ -68,7 +70,7 @@ pub fn decode_position_multi_msg(
     contract.currency = buf.read_string()?;
     contract.local_symbol = buf.read_string()?;
     contract.trading_class = buf.read_string()?;
-    let pos = buf.read_double()?;
+    let pos = buf.read_decimal()?;
     let avg_cost = buf.read_double()?;
     let model_code = buf.read_string()?;
 
@@ -150,9 +152,9 @@ pub fn decode_position_msg(
     }
 
     let pos = if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-        buf.read_double()?
+        buf.read_decimal()?
     } else {
-        f64::from(buf.read_int()?)
+        Decimal::from(buf.read_int()?)
     };
 
     let avg_cost = if version >= 3 {
@@ -183,3 +185,135 @@ pub fn decode_position_end_msg(
         OPCODE_REQ_POSITIONS,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_positions_multi(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+    let account = buf.read_string()?;
+    let model_code = buf.read_string()?;
+
+    Ok(Request::ReqPositionsMulti(ReqPositionsMulti {
+        req_id,
+        account,
+        model_code,
+    }))
+}
+
+pub fn decode_cancel_positions_multi(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelPositionsMulti(CancelPositionsMulti { req_id }))
+}
+
+pub fn encode_position_multi_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PositionMultiMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(POSITION_MULTI);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.account);
+
+    write_position_contract(buf, &msg.contract);
+    buf.push_decimal(msg.pos);
+    buf.push_double(msg.avg_cost);
+    buf.push_string(&msg.model_code);
+
+    Ok(())
+}
+
+pub fn encode_position_multi_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PositionMultiEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(POSITION_MULTI_END);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+
+    Ok(())
+}
+
+pub fn decode_req_positions(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqPositions(ReqPositions {}))
+}
+
+pub fn decode_cancel_positions(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::CancelPositions(CancelPositions {}))
+}
+
+pub fn encode_position_msg(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &PositionMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 3;
+
+    buf.push_int(POSITION);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.account);
+
+    write_position_contract(buf, &msg.contract);
+
+    if ctx.server_version() >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
+        buf.push_decimal(msg.pos);
+    } else {
+        buf.push_int(msg.pos.to_i32().unwrap_or_default());
+    }
+
+    buf.push_double(msg.avg_cost);
+
+    Ok(())
+}
+
+pub fn encode_position_end_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    _msg: &PositionEndMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(POSITION_END);
+    buf.push_int(VERSION);
+
+    Ok(())
+}
+
+fn write_position_contract(buf: &mut BytesMut, contract: &Contract) {
+    buf.push_int(contract.con_id);
+    buf.push_string(&contract.symbol);
+    buf.push_string(&contract.sec_type);
+    buf.push_string(&contract.last_trade_date_or_contract_month);
+    buf.push_double(contract.strike);
+    buf.push_string(&contract.right);
+    buf.push_string(&contract.multiplier);
+    buf.push_string(&contract.exchange);
+    buf.push_string(&contract.currency);
+    buf.push_string(&contract.local_symbol);
+    buf.push_string(&contract.trading_class);
+}
@@ -84,30 +84,28 @@ pub enum Request {
     ReqHistoricalTicks(ReqHistoricalTicks),
     ReqTickByTickData(ReqTickByTickData),
     CancelTickByTickData(CancelTickByTickData),
+    ReqCompletedOrders(ReqCompletedOrders),
+    ReqWshMetaData(ReqWshMetaData),
+    CancelWshMetaData(CancelWshMetaData),
+    ReqWshEventData(ReqWshEventData),
+    CancelWshEventData(CancelWshEventData),
+    ReqUserInfo(ReqUserInfo),
 }
 
 impl Request {
+    /// Assign the id of a new request. Cancellations and updates keep the id of the request
+    /// they refer to.
     pub(crate) fn set_request_id(&mut self, request_id: i32) {
         match self {
-            Self::CancelScannerSubscription(msg) => msg.req_id = request_id,
             Self::ReqScannerSubscription(msg) => msg.req_id = request_id,
             Self::ReqMktData(msg) => msg.req_id = request_id,
-            Self::CancelHistoricalData(msg) => msg.req_id = request_id,
             Self::CalculateImpliedVolatility(msg) => msg.req_id = request_id,
             Self::CalculateOptionPrice(msg) => msg.req_id = request_id,
-            Self::CancelAccountSummary(msg) => msg.req_id = request_id,
-            Self::CancelCalculateImpliedVolatility(msg) => msg.req_id = request_id,
-            Self::CancelCalculateOptionPrice(msg) => msg.req_id = request_id,
-            Self::CancelFundamentalData(msg) => msg.req_id = request_id,
-            Self::CancelRealtimeBars(msg) => msg.req_id = request_id,
             Self::ReqHistoricalData(msg) => msg.req_id = request_id,
             Self::ReqHeadTimestamp(msg) => msg.req_id = request_id,
-            Self::CancelHeadTimestamp(msg) => msg.req_id = request_id,
             Self::ReqRealtimeBars(msg) => msg.req_id = request_id,
             Self::ReqContractDetails(msg) => msg.req_id = request_id,
             Self::ReqMktDepth(msg) => msg.req_id = request_id,
-            Self::CancelMktData(msg) => msg.req_id = request_id,
-            Self::CancelMktDepth(msg) => msg.req_id = request_id,
             Self::ExerciseOptions(msg) => msg.req_id = request_id,
             Self::ReqExecutions(msg) => msg.req_id = request_id,
             Self::ReqAccountSummary(msg) => msg.req_id = request_id,
@@ -118,27 +116,110 @@ impl Request {
             // Self::ReqMatchingSymbols(msg) => msg.req_id = request_id,
             Self::ReqSmartComponents(msg) => msg.req_id = request_id,
             Self::ReqPnl(msg) => msg.req_id = request_id,
-            Self::CancelPnl(msg) => msg.req_id = request_id,
             Self::ReqPnlSingle(msg) => msg.req_id = request_id,
-            Self::CancelPnlSingle(msg) => msg.req_id = request_id,
             Self::ReqHistoricalTicks(msg) => msg.req_id = request_id,
-            // Self::ReqWshMetaData(msg) => msg.req_id = request_id,
-            // Self::ReqUserInfo(msg) => msg.req_id = request_id,
-            Self::CancelTickByTickData(msg) => msg.req_id = request_id,
+            Self::ReqWshMetaData(msg) => msg.req_id = request_id,
+            Self::ReqWshEventData(msg) => msg.req_id = request_id,
+            Self::ReqUserInfo(msg) => msg.req_id = request_id,
             Self::ReqTickByTickData(msg) => msg.req_id = request_id,
             Self::QueryDisplayGroups(msg) => msg.req_id = request_id,
             Self::SubscribeToGroupEvents(msg) => msg.req_id = request_id,
-            Self::UpdateDisplayGroup(msg) => msg.req_id = request_id,
-            Self::UnsubscribeFromGroupEvents(msg) => msg.req_id = request_id,
             Self::ReqPositionsMulti(msg) => msg.req_id = request_id,
-            Self::CancelPositionsMulti(msg) => msg.req_id = request_id,
             Self::ReqAccountUpdatesMulti(msg) => msg.req_id = request_id,
-            Self::CancelAccountUpdatesMulti(msg) => msg.req_id = request_id,
             Self::ReqNewsArticle(msg) => msg.req_id = request_id,
             Self::ReqHistoricalNews(msg) => msg.req_id = request_id,
+            Self::ReqHistogramData(msg) => msg.req_id = request_id,
             _ => (),
         }
     }
+
+    /// The request id of a request, a cancellation or an update, for relays rewriting them.
+    /// Order ids, of `PlaceOrder` and `CancelOrder`, are not request ids.
+    pub fn request_id_mut(&mut self) -> Option<&mut i32> {
+        match self {
+            Self::CancelScannerSubscription(msg) => Some(&mut msg.req_id),
+            Self::ReqScannerSubscription(msg) => Some(&mut msg.req_id),
+            Self::ReqMktData(msg) => Some(&mut msg.req_id),
+            Self::CancelHistoricalData(msg) => Some(&mut msg.req_id),
+            Self::CancelRealtimeBars(msg) => Some(&mut msg.req_id),
+            Self::ReqHistoricalData(msg) => Some(&mut msg.req_id),
+            Self::ReqHeadTimestamp(msg) => Some(&mut msg.req_id),
+            Self::CancelHeadTimestamp(msg) => Some(&mut msg.req_id),
+            Self::ReqRealtimeBars(msg) => Some(&mut msg.req_id),
+            Self::ReqContractDetails(msg) => Some(&mut msg.req_id),
+            Self::ReqMktDepth(msg) => Some(&mut msg.req_id),
+            Self::CancelMktData(msg) => Some(&mut msg.req_id),
+            Self::CancelMktDepth(msg) => Some(&mut msg.req_id),
+            Self::ExerciseOptions(msg) => Some(&mut msg.req_id),
+            Self::ReqExecutions(msg) => Some(&mut msg.req_id),
+            Self::ReplaceFA(msg) => Some(&mut msg.req_id),
+            Self::ReqFundamentalData(msg) => Some(&mut msg.req_id),
+            Self::CancelFundamentalData(msg) => Some(&mut msg.req_id),
+            Self::CalculateImpliedVolatility(msg) => Some(&mut msg.req_id),
+            Self::CancelCalculateImpliedVolatility(msg) => Some(&mut msg.req_id),
+            Self::CalculateOptionPrice(msg) => Some(&mut msg.req_id),
+            Self::CancelCalculateOptionPrice(msg) => Some(&mut msg.req_id),
+            Self::ReqSecDefOptParams(msg) => Some(&mut msg.req_id),
+            Self::ReqSoftDollarTiers(msg) => Some(&mut msg.req_id),
+            Self::ReqPositionsMulti(msg) => Some(&mut msg.req_id),
+            Self::CancelPositionsMulti(msg) => Some(&mut msg.req_id),
+            Self::CancelAccountUpdatesMulti(msg) => Some(&mut msg.req_id),
+            Self::ReqAccountUpdatesMulti(msg) => Some(&mut msg.req_id),
+            Self::ReqAccountSummary(msg) => Some(&mut msg.req_id),
+            Self::CancelAccountSummary(msg) => Some(&mut msg.req_id),
+            Self::QueryDisplayGroups(msg) => Some(&mut msg.req_id),
+            Self::SubscribeToGroupEvents(msg) => Some(&mut msg.req_id),
+            Self::UpdateDisplayGroup(msg) => Some(&mut msg.req_id),
+            Self::UnsubscribeFromGroupEvents(msg) => Some(&mut msg.req_id),
+            Self::MatchingSymbol(msg) => Some(&mut msg.req_id),
+            Self::ReqSmartComponents(msg) => Some(&mut msg.req_id),
+            Self::ReqNewsArticle(msg) => Some(&mut msg.req_id),
+            Self::ReqHistoricalNews(msg) => Some(&mut msg.req_id),
+            Self::ReqHistogramData(msg) => Some(&mut msg.req_id),
+            Self::CancelHistogramData(msg) => Some(&mut msg.req_id),
+            Self::ReqPnl(msg) => Some(&mut msg.req_id),
+            Self::CancelPnl(msg) => Some(&mut msg.req_id),
+            Self::ReqPnlSingle(msg) => Some(&mut msg.req_id),
+            Self::CancelPnlSingle(msg) => Some(&mut msg.req_id),
+            Self::ReqHistoricalTicks(msg) => Some(&mut msg.req_id),
+            Self::ReqTickByTickData(msg) => Some(&mut msg.req_id),
+            Self::CancelTickByTickData(msg) => Some(&mut msg.req_id),
+            Self::ReqWshMetaData(msg) => Some(&mut msg.req_id),
+            Self::CancelWshMetaData(msg) => Some(&mut msg.req_id),
+            Self::ReqWshEventData(msg) => Some(&mut msg.req_id),
+            Self::CancelWshEventData(msg) => Some(&mut msg.req_id),
+            Self::ReqUserInfo(msg) => Some(&mut msg.req_id),
+            Self::Handshake(_)
+            | Self::StartApi(_)
+            | Self::ReqScannerParameters(_)
+            | Self::PlaceOrder(_)
+            | Self::ReqAccountUpdates(_)
+            | Self::CancelOrder(_)
+            | Self::ReqOpenOrders(_)
+            | Self::ReqIds(_)
+            | Self::ReqNewsBulletins(_)
+            | Self::CancelNewsBulletins(_)
+            | Self::SetServerLogLevel(_)
+            | Self::ReqAutoOpenOrders(_)
+            | Self::ReqAllOpenOrders(_)
+            | Self::ReqManagedAccts(_)
+            | Self::RequestFA(_)
+            | Self::ReqCurrentTime(_)
+            | Self::ReqGlobalCancel(_)
+            | Self::ReqMarketDataType(_)
+            | Self::ReqPositions(_)
+            | Self::CancelPositions(_)
+            | Self::VerifyRequest(_)
+            | Self::VerifyMessage(_)
+            | Self::VerfyAndAuthRequest(_)
+            | Self::VerifyAndAuthMessage(_)
+            | Self::ReqFamilyCodes(_)
+            | Self::ReqMktDepthExchanges(_)
+            | Self::ReqNewsProvider(_)
+            | Self::ReqMarketRule(_)
+            | Self::ReqCompletedOrders(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -341,6 +422,7 @@ pub struct CancelMktData {
 #[allow(dead_code)]
 pub struct CancelMktDepth {
     pub req_id: i32,
+    pub is_smart_depth: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -380,6 +462,7 @@ pub struct ReqExecutions {
 #[allow(dead_code)]
 pub struct CacelOrder {
     pub id: i32,
+    pub manual_order_cancel_time: String,
 }
 
 #[derive(Debug, Clone)]
@@ -777,3 +860,57 @@ impl ReqTickByTickData {
 pub struct CancelTickByTickData {
     pub req_id: i32,
 }
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ReqCompletedOrders {
+    pub api_only: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct ReqWshMetaData {
+    pub(crate) req_id: i32,
+}
+
+impl ReqWshMetaData {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct CancelWshMetaData {
+    pub req_id: i32,
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ReqWshEventData {
+    pub(crate) req_id: i32,
+    pub wsh_event_data: WshEventData,
+}
+
+impl ReqWshEventData {
+    #[must_use]
+    pub fn new(wsh_event_data: WshEventData) -> Self {
+        Self {
+            req_id: 0,
+            wsh_event_data,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct CancelWshEventData {
+    pub req_id: i32,
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ReqUserInfo {
+    pub req_id: i32,
+}
@@ -2,9 +2,11 @@ use std::io;
 
 use bytes::BytesMut;
 
+use super::constants::*;
 use super::context::Context;
+use super::error::EncodeError;
 use super::response::*;
-use super::wire::TwsWireDecoder;
+use super::wire::{TwsWireDecoder, TwsWireEncoder};
 
 pub fn decode_reroute_mkt_depth_req(
     _ctx: &mut Context,
@@ -41,3 +43,32 @@ pub fn decode_reroute_mkt_data_req(
         req_id,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn encode_reroute_mkt_depth_req(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &RerouteMktDepthReq,
+) -> Result<(), EncodeError> {
+    buf.push_int(REROUTE_MKT_DEPTH_REQ);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.con_id);
+    buf.push_string(&msg.exchange);
+
+    Ok(())
+}
+
+pub fn encode_reroute_mkt_data_req(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &RerouteMktDataReq,
+) -> Result<(), EncodeError> {
+    buf.push_int(REROUTE_MKT_DATA_REQ);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.con_id);
+    buf.push_string(&msg.exchange);
+
+    Ok(())
+}
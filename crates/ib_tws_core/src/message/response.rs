@@ -10,7 +10,10 @@ use super::constants::{
     OPCODE_REQ_SCANNER_PARAMETERS, OPCODE_VERIFY_AND_AUTH_MESSAGE, OPCODE_VERIFY_AND_AUTH_REQUEST,
     OPCODE_VERIFY_MESSAGE, OPCODE_VERIFY_REQUEST,
 };
-use crate::domain::{*, market_data::{MarketDataType, TickByTickType}};
+use crate::domain::{
+    market_data::{MarketDataType, TickByTickType},
+    *,
+};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -93,13 +96,32 @@ pub enum Response {
     TickByTickMidPointMsg(TickByTickMidPointMsg),
     TickByTickNoneMsg(TickByTickNoneMsg),
     SmartComponentsMsg(SmartComponentsMsg),
+    CompletedOrderMsg(CompletedOrderMsg),
+    CompletedOrdersEndMsg(CompletedOrdersEndMsg),
+    WshMetaDataMsg(WshMetaDataMsg),
+    WshEventDataMsg(WshEventDataMsg),
+    ReplaceFaEndMsg(ReplaceFaEndMsg),
+    HistoricalScheduleMsg(HistoricalScheduleMsg),
+    UserInfoMsg(UserInfoMsg),
+    UnknownMsg(UnknownMsg),
 }
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct HandshakeAck {
+    /// `-1` when the Gateway redirects to another address.
     pub server_version: i32,
-    pub addr_or_time: String,
+    /// The time of the server when the connection was accepted.
+    pub server_time: Option<String>,
+    /// The `host[:port]` to connect to instead.
+    pub redirect: Option<String>,
+}
+
+impl HandshakeAck {
+    #[must_use]
+    pub fn is_redirect(&self) -> bool {
+        self.redirect.is_some()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -160,7 +182,7 @@ pub struct HistoricalDataUpdateMsg {
 #[allow(dead_code)]
 pub struct PnlSingleMsg {
     pub req_id: i32,
-    pub pos: i32,
+    pub pos: Decimal,
     pub daily_pnl: f64,
     pub unrealized_pnl: f64,
     pub realized_pnl: f64,
@@ -407,6 +429,16 @@ pub struct HistoricalDataMsg {
     pub bars: Vec<Bar>,
 }
 
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct HistoricalScheduleMsg {
+    pub req_id: i32,
+    pub start_date_time: String,
+    pub end_date_time: String,
+    pub time_zone: String,
+    pub sessions: Vec<HistoricalSession>,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ReceiveFaMsg {
@@ -414,6 +446,13 @@ pub struct ReceiveFaMsg {
     pub xml: String,
 }
 
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ReplaceFaEndMsg {
+    pub req_id: i32,
+    pub text: String,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ManagedAcctsMsg {
@@ -507,12 +546,52 @@ pub struct OpenOrderMsg {
     pub order_state: OrderState,
 }
 
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct CompletedOrderMsg {
+    pub contract: Contract,
+    pub order: Order,
+    pub order_state: OrderState,
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct CompletedOrdersEndMsg {}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct WshMetaDataMsg {
+    pub req_id: i32,
+    pub data_json: String,
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct WshEventDataMsg {
+    pub req_id: i32,
+    pub data_json: String,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ErrMsgMsg {
     pub id: i32,
     pub error_code: i32,
     pub error_message: String,
+    pub advanced_order_reject_json: String,
+    pub advanced_order_reject: Option<AdvancedOrderReject>,
+}
+
+impl ErrMsgMsg {
+    #[must_use]
+    pub fn code(&self) -> TwsErrorCode {
+        TwsErrorCode::from(self.error_code)
+    }
+
+    #[must_use]
+    pub fn is_warning(&self) -> bool {
+        self.code().is_warning()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -525,7 +604,7 @@ pub struct AcctUpdateTimeMsg {
 #[allow(dead_code)]
 pub struct PortfolioValueMsg {
     pub contract: Contract,
-    pub position: f64,
+    pub position: Decimal,
     pub market_price: f64,
     pub market_value: f64,
     pub average_cost: f64,
@@ -548,8 +627,8 @@ pub struct AcctValueMsg {
 pub struct OrderStatusMsg {
     pub id: i32,
     pub status: String,
-    pub filled: f64,
-    pub remaining: f64,
+    pub filled: Decimal,
+    pub remaining: Decimal,
     pub avg_fill_price: f64,
     pub perm_id: i32,
     pub parent_id: i32,
@@ -594,6 +673,8 @@ pub struct TickGenericMsg {
 pub struct TickOptionComputationMsg {
     pub req_id: i32,
     pub tick_type: TickType,
+    // 0 - return based, 1 - price based
+    pub tick_attrib: i32,
     pub implied_vol: f64,
     pub delta: f64,
     pub opt_price: f64,
@@ -629,7 +710,7 @@ pub struct PositionEndMsg {}
 pub struct PositionMsg {
     pub account: String,
     pub contract: Contract,
-    pub pos: f64,
+    pub pos: Decimal,
     pub avg_cost: f64,
 }
 
@@ -658,7 +739,7 @@ pub struct PositionMultiMsg {
     pub account: String,
     pub model_code: String,
     pub contract: Contract,
-    pub pos: f64,
+    pub pos: Decimal,
     pub avg_cost: f64,
 }
 
@@ -739,6 +820,21 @@ pub struct TickByTickMidPointMsg {
 #[allow(dead_code)]
 pub struct TickByTickNoneMsg {}
 
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct UserInfoMsg {
+    pub req_id: i32,
+    pub white_branding_id: String,
+}
+
+/// A frame skipped in tolerant mode, either because the message id is not supported or
+/// because its fields could not be decoded. `fields` holds the raw fields, message id included.
+#[derive(Debug, Clone)]
+pub struct UnknownMsg {
+    pub msg_id: i32,
+    pub fields: Vec<String>,
+}
+
 impl Response {
     pub fn request_id(&self) -> Option<i32> {
         match self {
@@ -820,6 +916,104 @@ impl Response {
             Response::TickByTickMidPointMsg(ref msg) => Some(msg.req_id),
             Response::TickByTickNoneMsg(ref msg) => None,
             Response::SmartComponentsMsg(ref msg) => Some(msg.req_id),
+            Response::CompletedOrderMsg(ref msg) => None,
+            Response::CompletedOrdersEndMsg(ref msg) => None,
+            Response::WshMetaDataMsg(ref msg) => Some(msg.req_id),
+            Response::WshEventDataMsg(ref msg) => Some(msg.req_id),
+            Response::ReplaceFaEndMsg(ref msg) => Some(msg.req_id),
+            Response::HistoricalScheduleMsg(ref msg) => Some(msg.req_id),
+            Response::UserInfoMsg(ref msg) => Some(msg.req_id),
+            Response::UnknownMsg(ref msg) => None,
         }
     }
+
+    /// The request id of a response, for relays rewriting them. `None` for responses keyed
+    /// by order id or not keyed at all; unlike `request_id`, also for replies keyed by the
+    /// opcode of their request.
+    pub fn request_id_mut(&mut self) -> Option<&mut i32> {
+        match self {
+            Response::HistoricalTickLastMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalTickBidAskMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalTicksMsg(msg) => Some(&mut msg.req_id),
+            Response::RerouteMktDepthReq(msg) => Some(&mut msg.req_id),
+            Response::RerouteMktDataReq(msg) => Some(&mut msg.req_id),
+            Response::HistoricalDataUpdateMsg(msg) => Some(&mut msg.req_id),
+            Response::PnlSingleMsg(msg) => Some(&mut msg.req_id),
+            Response::PnlMsg(msg) => Some(&mut msg.req_id),
+            Response::HistogramDataMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalNewsEndMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalNewsMsg(msg) => Some(&mut msg.req_id),
+            Response::NewsArticleMsg(msg) => Some(&mut msg.req_id),
+            Response::TickNewsMsg(msg) => Some(&mut msg.req_id),
+            Response::HeadTimestampMsg(msg) => Some(&mut msg.req_id),
+            Response::SymbolSamplesMsg(msg) => Some(&mut msg.req_id),
+            Response::SoftDollarTiersMsg(msg) => Some(&mut msg.req_id),
+            Response::SecurityDefinitionOptionalParameterEndMsg(msg) => Some(&mut msg.req_id),
+            Response::SecurityDefinitionOptionalParameterMsg(msg) => Some(&mut msg.req_id),
+            Response::DisplayGroupUpdatedMsg(msg) => Some(&mut msg.req_id),
+            Response::DisplayGroupListMsg(msg) => Some(&mut msg.req_id),
+            Response::MarketDataTypeMsg(msg) => Some(&mut msg.req_id),
+            Response::TickSnapshotEndMsg(msg) => Some(&mut msg.req_id),
+            // the id of the order the delta neutral contract was validated for
+            Response::DeltaNeutralValidationMsg(msg) => Some(&mut msg.req_id),
+            Response::ExecutionDataEndMsg(msg) => Some(&mut msg.req_id),
+            Response::ContractDataEndMsg(msg) => Some(&mut msg.req_id),
+            Response::FundamentalDataMsg(msg) => Some(&mut msg.req_id),
+            Response::RealTimeBarsMsg(msg) => Some(&mut msg.req_id),
+            Response::ScannerDataMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalDataMsg(msg) => Some(&mut msg.req_id),
+            Response::MarketDepthL2Msg(msg) => Some(&mut msg.id),
+            Response::MarketDepthMsg(msg) => Some(&mut msg.id),
+            Response::ExecutionDataMsg(msg) if msg.req_id != -1 => Some(&mut msg.req_id),
+            Response::BondContractDataMsg(msg) => Some(&mut msg.req_id),
+            Response::ContractDataMsg(msg) => Some(&mut msg.req_id),
+            Response::ErrMsgMsg(msg) if msg.id != -1 => Some(&mut msg.id),
+            Response::TickEFPMsg(msg) => Some(&mut msg.req_id),
+            Response::TickStringMsg(msg) => Some(&mut msg.req_id),
+            Response::TickGenericMsg(msg) => Some(&mut msg.req_id),
+            Response::TickOptionComputationMsg(msg) => Some(&mut msg.req_id),
+            Response::AccountSummaryEndMsg(msg) => Some(&mut msg.req_id),
+            Response::AccountSummaryMsg(msg) => Some(&mut msg.req_id),
+            Response::TickSizeMsg(msg) => Some(&mut msg.req_id),
+            Response::TickPriceMsg(msg) => Some(&mut msg.req_id),
+            Response::PositionMultiMsg(msg) => Some(&mut msg.req_id),
+            Response::PositionMultiEndMsg(msg) => Some(&mut msg.req_id),
+            Response::AccountUpdateMultiMsg(msg) => Some(&mut msg.req_id),
+            Response::AccountUpdateMultiEndMsg(msg) => Some(&mut msg.req_id),
+            Response::TickReqParamsMsg(msg) => Some(&mut msg.req_id),
+            Response::TickByTickAllLastMsg(msg) => Some(&mut msg.req_id),
+            Response::TickByTickBidAskMsg(msg) => Some(&mut msg.req_id),
+            Response::TickByTickMidPointMsg(msg) => Some(&mut msg.req_id),
+            Response::SmartComponentsMsg(msg) => Some(&mut msg.req_id),
+            Response::WshMetaDataMsg(msg) => Some(&mut msg.req_id),
+            Response::WshEventDataMsg(msg) => Some(&mut msg.req_id),
+            Response::ReplaceFaEndMsg(msg) => Some(&mut msg.req_id),
+            Response::HistoricalScheduleMsg(msg) => Some(&mut msg.req_id),
+            Response::UserInfoMsg(msg) => Some(&mut msg.req_id),
+            _ => None,
+        }
+    }
+
+    /// Whether `request_id` returns the opcode of the request instead of a request id.
+    /// These replies can't be told apart from the ones to a request with the same id.
+    pub fn is_global(&self) -> bool {
+        matches!(
+            self,
+            Response::HandshakeAck(_)
+                | Response::MarketRule(_)
+                | Response::NewsProviderMsg(_)
+                | Response::MktDepthExchangesMsg(_)
+                | Response::FamilyCodesMsg(_)
+                | Response::VerifyAndAuthCompletedMsg(_)
+                | Response::VerifyAndAuthMessageMsg(_)
+                | Response::VerifyCompletedMsg(_)
+                | Response::VerifyMessageApiMsg(_)
+                | Response::CurrentTimeMsg(_)
+                | Response::ScannerParametersMsg(_)
+                | Response::ReceiveFaMsg(_)
+                | Response::ManagedAcctsMsg(_)
+                | Response::NextValidIdMsg(_)
+                | Response::OpenOrderMsg(_)
+        )
+    }
 }
@@ -142,3 +142,115 @@ pub fn decode_scanner_parameters_msg(
         OPCODE_REQ_SCANNER_PARAMETERS,
     ))
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_scanner_subscription(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    let subscribe = ScannerSubscription {
+        number_of_rows: buf.read_int_max()?,
+        instrument: buf.read_string()?,
+        location_code: buf.read_string()?,
+        scan_code: buf.read_string()?,
+        above_price: buf.read_double_max()?,
+        below_price: buf.read_double_max()?,
+        above_volume: buf.read_int_max()?,
+        market_cap_above: buf.read_double_max()?,
+        market_cap_below: buf.read_double_max()?,
+        moody_rating_above: buf.read_string()?,
+        moody_rating_below: buf.read_string()?,
+        sp_rating_above: buf.read_string()?,
+        sp_rating_below: buf.read_string()?,
+        maturity_date_above: buf.read_string()?,
+        maturity_date_below: buf.read_string()?,
+        coupon_rate_above: buf.read_double_max()?,
+        coupon_rate_below: buf.read_double_max()?,
+        exclude_convertible: buf.read_string()?,
+        average_option_volume_above: buf.read_int_max()?,
+        scanner_setting_pairs: buf.read_string()?,
+        stock_type_filter: buf.read_string()?,
+    };
+
+    let options = decode_tagvalue_from_string(buf)?;
+
+    Ok(Request::ReqScannerSubscription(ReqScannerSubscription {
+        req_id,
+        subscribe,
+        options,
+    }))
+}
+
+pub fn decode_cancel_scanner_subscription(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelScannerSubscription(
+        CancelScannerSubscription { req_id },
+    ))
+}
+
+pub fn encode_scanner_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ScannerDataMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 3;
+
+    buf.push_int(SCANNER_DATA);
+    buf.push_int(VERSION);
+    buf.push_int(msg.req_id);
+    buf.push_int(msg.datas.len() as i32);
+    for data in &msg.datas {
+        let contract_details = &data.contract_details;
+        buf.push_int(data.rank);
+        buf.push_int(contract_details.contract.con_id);
+        buf.push_string(&contract_details.contract.symbol);
+        buf.push_string(&contract_details.contract.sec_type);
+        buf.push_string(&contract_details.contract.last_trade_date_or_contract_month);
+        buf.push_double(contract_details.contract.strike);
+        buf.push_string(&contract_details.contract.right);
+        buf.push_string(&contract_details.contract.exchange);
+        buf.push_string(&contract_details.contract.currency);
+        buf.push_string(&contract_details.contract.local_symbol);
+        buf.push_string(&contract_details.market_name);
+        buf.push_string(&contract_details.contract.trading_class);
+        buf.push_string(&data.distance);
+        buf.push_string(&data.benchmark);
+        buf.push_string(&data.projection);
+        buf.push_string(&data.legs);
+    }
+
+    Ok(())
+}
+
+pub fn decode_req_scanner_parameters(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let _version = buf.read_int()?;
+
+    Ok(Request::ReqScannerParameters(ReqScannerParameters {}))
+}
+
+pub fn encode_scanner_parameters_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &ScannerParametersMsg,
+) -> Result<(), EncodeError> {
+    const VERSION: i32 = 1;
+
+    buf.push_int(SCANNER_PARAMETERS);
+    buf.push_int(VERSION);
+    buf.push_string(&msg.xml);
+
+    Ok(())
+}
@@ -29,7 +29,9 @@ pub fn read_last_trade_date(
 ) -> Result<(), io::Error> {
     let last_trade_date_or_contract_month = buf.read_string()?;
     if !last_trade_date_or_contract_month.is_empty() {
-        let splitted: Vec<&str> = last_trade_date_or_contract_month.split("\\s+").collect();
+        let splitted: Vec<&str> = last_trade_date_or_contract_month
+            .split_whitespace()
+            .collect();
         if !splitted.is_empty() {
             if is_bond {
                 contract.maturity = splitted[0].to_string();
@@ -47,6 +49,25 @@ pub fn read_last_trade_date(
     Ok(())
 }
 
+/// The inverse of `read_last_trade_date`: the date, followed by the last trade time and, for
+/// bonds, the time zone.
+pub fn write_last_trade_date(buf: &mut BytesMut, contract: &ContractDetails, is_bond: bool) {
+    let mut last_trade_date = if is_bond {
+        contract.maturity.clone()
+    } else {
+        contract.contract.last_trade_date_or_contract_month.clone()
+    };
+    if !contract.last_trade_time.is_empty() {
+        last_trade_date.push(' ');
+        last_trade_date.push_str(&contract.last_trade_time);
+        if is_bond && !contract.timezone_id.is_empty() {
+            last_trade_date.push(' ');
+            last_trade_date.push_str(&contract.timezone_id);
+        }
+    }
+    buf.push_string(&last_trade_date);
+}
+
 pub fn encode_contract_without_primary_exch(buf: &mut BytesMut, contract: &Contract) {
     buf.push_int(contract.con_id);
 
@@ -75,3 +96,54 @@ pub fn encode_tagvalue_as_string(buf: &mut BytesMut, options: &[TagValue]) {
     }
     buf.push_string(&sb)
 }
+
+pub fn decode_contract(buf: &mut BytesMut) -> Result<Contract, io::Error> {
+    let mut contract: Contract = Default::default();
+    contract.con_id = buf.read_int()?;
+
+    contract.symbol = buf.read_string()?;
+    contract.sec_type = buf.read_string()?;
+    contract.last_trade_date_or_contract_month = buf.read_string()?;
+    contract.strike = buf.read_double()?;
+    contract.right = buf.read_string()?;
+    contract.multiplier = buf.read_string()?;
+    contract.exchange = buf.read_string()?;
+    contract.primary_exch = buf.read_string()?;
+    contract.currency = buf.read_string()?;
+    contract.local_symbol = buf.read_string()?;
+
+    contract.trading_class = buf.read_string()?;
+    Ok(contract)
+}
+
+pub fn decode_contract_without_primary_exch(buf: &mut BytesMut) -> Result<Contract, io::Error> {
+    let mut contract: Contract = Default::default();
+    contract.con_id = buf.read_int()?;
+
+    contract.symbol = buf.read_string()?;
+    contract.sec_type = buf.read_string()?;
+    contract.last_trade_date_or_contract_month = buf.read_string()?;
+    contract.strike = buf.read_double()?;
+    contract.right = buf.read_string()?;
+    contract.multiplier = buf.read_string()?;
+    contract.exchange = buf.read_string()?;
+    contract.currency = buf.read_string()?;
+    contract.local_symbol = buf.read_string()?;
+
+    contract.trading_class = buf.read_string()?;
+    Ok(contract)
+}
+
+pub fn decode_tagvalue_from_string(buf: &mut BytesMut) -> Result<Vec<TagValue>, io::Error> {
+    let s = buf.read_string()?;
+    Ok(s.split(';')
+        .filter(|elem| !elem.is_empty())
+        .map(|elem| {
+            let (tag, value) = elem.split_once('=').unwrap_or((elem, ""));
+            TagValue {
+                tag: tag.to_string(),
+                value: value.to_string(),
+            }
+        })
+        .collect())
+}
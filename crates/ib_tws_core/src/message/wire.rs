@@ -64,6 +64,20 @@ pub trait TwsWireEncoder {
             self.push_double(v);
         }
     }
+
+    #[inline(always)]
+    fn push_decimal(&mut self, v: Decimal) {
+        self.push_string(&v.normalize().to_string());
+    }
+
+    #[inline(always)]
+    fn push_decimal_max(&mut self, v: Decimal) {
+        if v == Decimal::MAX {
+            self.push_u8(0);
+        } else {
+            self.push_decimal(v);
+        }
+    }
 }
 
 impl TwsWireEncoder for BytesMut {
@@ -88,13 +102,36 @@ pub trait TwsWireDecoder {
         if str.is_empty() {
             Ok(Decimal::new(0, 0))
         } else {
+            // older servers send quantities as doubles, which may use an exponent
             Decimal::from_str(&str)
+                .or_else(|_| Decimal::from_scientific(&str))
                 .map_err(|_| {
-                    io::Error::new(io::ErrorKind::InvalidData, "cannot read decimal from stream")
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "cannot read decimal from stream",
+                    )
                 })
         }
     }
 
+    fn read_decimal_max(&mut self) -> Result<Decimal, io::Error> {
+        let str = self.read_string()?;
+        match str.as_str() {
+            // unset values come as empty or as the max of the server side type
+            "" | "2147483647" | "9223372036854775807" | "1.7976931348623157E308" => {
+                Ok(Decimal::MAX)
+            }
+            _ => Decimal::from_str(&str)
+                .or_else(|_| Decimal::from_scientific(&str))
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "cannot read decimal from stream",
+                    )
+                }),
+        }
+    }
+
     fn read_int(&mut self) -> Result<i32, io::Error> {
         let s = self.split()?;
         let s = unsafe { str::from_utf8_unchecked(&s) };
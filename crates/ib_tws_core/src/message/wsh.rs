@@ -0,0 +1,201 @@
+use std::io;
+
+use bytes::BytesMut;
+
+use super::constants::*;
+use super::context::{Context, DispatchId};
+use super::error::EncodeError;
+use super::request::*;
+use super::response::*;
+use super::wire::{TwsWireDecoder, TwsWireEncoder};
+use crate::domain::WshEventData;
+
+pub fn encode_req_wsh_meta_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &ReqWshMetaData,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_WSHE_CALENDAR {
+        return Err(EncodeError::VersionLessError(MIN_SERVER_VER_WSHE_CALENDAR));
+    }
+    buf.push_int(REQ_WSH_META_DATA);
+    buf.push_int(req.req_id);
+
+    Ok(DispatchId::Oneshot(req.req_id))
+}
+
+pub fn encode_cancel_wsh_meta_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &CancelWshMetaData,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_WSHE_CALENDAR {
+        return Err(EncodeError::VersionLessError(MIN_SERVER_VER_WSHE_CALENDAR));
+    }
+    buf.push_int(CANCEL_WSH_META_DATA);
+    buf.push_int(req.req_id);
+
+    Ok(DispatchId::Oneshot(req.req_id))
+}
+
+pub fn encode_req_wsh_event_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &ReqWshEventData,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_WSHE_CALENDAR {
+        return Err(EncodeError::VersionLessError(MIN_SERVER_VER_WSHE_CALENDAR));
+    }
+
+    let data = &req.wsh_event_data;
+    if ctx.server_version() < MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS && data.has_filters() {
+        return Err(EncodeError::VersionLessError(
+            MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS,
+        ));
+    }
+    if ctx.server_version() < MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS_DATE && data.has_date_range()
+    {
+        return Err(EncodeError::VersionLessError(
+            MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS_DATE,
+        ));
+    }
+
+    buf.push_int(REQ_WSH_EVENT_DATA);
+    buf.push_int(req.req_id);
+    buf.push_int_max(data.con_id);
+
+    if ctx.server_version() >= MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS {
+        buf.push_string(&data.filter);
+        buf.push_bool(data.fill_watchlist);
+        buf.push_bool(data.fill_portfolio);
+        buf.push_bool(data.fill_competitors);
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS_DATE {
+        buf.push_string(&data.start_date);
+        buf.push_string(&data.end_date);
+        buf.push_int_max(data.total_limit);
+    }
+
+    Ok(DispatchId::Oneshot(req.req_id))
+}
+
+pub fn encode_cancel_wsh_event_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+    req: &CancelWshEventData,
+) -> Result<DispatchId, EncodeError> {
+    if ctx.server_version() < MIN_SERVER_VER_WSHE_CALENDAR {
+        return Err(EncodeError::VersionLessError(MIN_SERVER_VER_WSHE_CALENDAR));
+    }
+    buf.push_int(CANCEL_WSH_EVENT_DATA);
+    buf.push_int(req.req_id);
+
+    Ok(DispatchId::Oneshot(req.req_id))
+}
+
+pub fn decode_wsh_meta_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let req_id = buf.read_int()?;
+    let data_json = buf.read_string()?;
+    Ok((
+        Response::WshMetaDataMsg(WshMetaDataMsg { req_id, data_json }),
+        req_id,
+    ))
+}
+
+pub fn decode_wsh_event_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<(Response, i32), io::Error> {
+    let req_id = buf.read_int()?;
+    let data_json = buf.read_string()?;
+    Ok((
+        Response::WshEventDataMsg(WshEventDataMsg { req_id, data_json }),
+        req_id,
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////
+// Server side: the inverse of the functions above.
+
+pub fn decode_req_wsh_meta_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::ReqWshMetaData(ReqWshMetaData { req_id }))
+}
+
+pub fn decode_cancel_wsh_meta_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelWshMetaData(CancelWshMetaData { req_id }))
+}
+
+pub fn decode_req_wsh_event_data(
+    ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    let mut data = WshEventData::default();
+    data.con_id = buf.read_int_max()?;
+
+    if ctx.server_version() >= MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS {
+        data.filter = buf.read_string()?;
+        data.fill_watchlist = buf.read_bool()?;
+        data.fill_portfolio = buf.read_bool()?;
+        data.fill_competitors = buf.read_bool()?;
+    }
+
+    if ctx.server_version() >= MIN_SERVER_VER_WSH_EVENT_DATA_FILTERS_DATE {
+        data.start_date = buf.read_string()?;
+        data.end_date = buf.read_string()?;
+        data.total_limit = buf.read_int_max()?;
+    }
+
+    Ok(Request::ReqWshEventData(ReqWshEventData {
+        req_id,
+        wsh_event_data: data,
+    }))
+}
+
+pub fn decode_cancel_wsh_event_data(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+) -> Result<Request, io::Error> {
+    let req_id = buf.read_int()?;
+
+    Ok(Request::CancelWshEventData(CancelWshEventData { req_id }))
+}
+
+pub fn encode_wsh_meta_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &WshMetaDataMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(WSH_META_DATA);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.data_json);
+
+    Ok(())
+}
+
+pub fn encode_wsh_event_data_msg(
+    _ctx: &mut Context,
+    buf: &mut BytesMut,
+    msg: &WshEventDataMsg,
+) -> Result<(), EncodeError> {
+    buf.push_int(WSH_EVENT_DATA);
+    buf.push_int(msg.req_id);
+    buf.push_string(&msg.data_json);
+
+    Ok(())
+}
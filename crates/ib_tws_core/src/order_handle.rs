@@ -0,0 +1,106 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{channel::mpsc, ready, Stream, StreamExt};
+
+use crate::{
+    domain::{Contract, Order},
+    message::{
+        request::{CacelOrder, PlaceOrder},
+        Request, Response,
+    },
+    routing::api_error,
+    AsyncClient, Error,
+};
+
+/// An order placed with [`AsyncClient::place_order`].
+///
+/// The handle is a stream of everything TWS reports for the order: `OrderStatusMsg`,
+/// `OpenOrderMsg`, `ExecutionDataMsg` and `CommissionReportMsg` responses, and errors for
+/// the order id. It doesn't end when the order is filled or cancelled, since commission
+/// reports may still follow; drop it once the order is no longer of interest.
+#[derive(Debug)]
+pub struct OrderHandle<'a> {
+    client: &'a AsyncClient,
+    order_id: i32,
+    contract: Contract,
+    responses: mpsc::UnboundedReceiver<Result<Response, Error>>,
+}
+
+impl<'a> OrderHandle<'a> {
+    pub(crate) fn new(
+        client: &'a AsyncClient,
+        order_id: i32,
+        contract: Contract,
+        responses: mpsc::UnboundedReceiver<Result<Response, Error>>,
+    ) -> Self {
+        OrderHandle {
+            client,
+            order_id,
+            contract,
+            responses,
+        }
+    }
+
+    #[must_use]
+    pub fn order_id(&self) -> i32 {
+        self.order_id
+    }
+
+    #[must_use]
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Send the order again with the same id, TWS applies the changes to the live order.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[allow(clippy::unused_async)]
+    pub async fn modify(&self, order: Order) -> Result<(), Error> {
+        self.client.notify(Request::PlaceOrder(PlaceOrder {
+            id: self.order_id,
+            contract: self.contract.clone(),
+            order,
+        }))
+    }
+
+    /// Request the cancellation of the order, the outcome is reported on the stream.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[allow(clippy::unused_async)]
+    pub async fn cancel(&self) -> Result<(), Error> {
+        self.client.notify(Request::CancelOrder(CacelOrder {
+            id: self.order_id,
+            manual_order_cancel_time: String::new(),
+        }))
+    }
+}
+
+impl Stream for OrderHandle<'_> {
+    type Item = Result<Response, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let response = match ready!(self.responses.poll_next_unpin(cx)) {
+                Some(Ok(Response::ErrMsgMsg(err))) => api_error(err),
+                Some(Ok(
+                    response @ (Response::OrderStatusMsg(_)
+                    | Response::OpenOrderMsg(_)
+                    | Response::ExecutionDataMsg(_)
+                    | Response::CommissionReportMsg(_)),
+                )) => Some(Ok(response)),
+                Some(Ok(response)) => {
+                    warn!(?response, "unexpected response for order id");
+                    None
+                }
+                Some(Err(error)) => Some(Err(error)),
+                None => return Poll::Ready(None),
+            };
+            if response.is_some() {
+                return Poll::Ready(response);
+            }
+        }
+    }
+}
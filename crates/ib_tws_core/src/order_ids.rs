@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// First id handed out for requests (tickers, contract details, ...).
+///
+/// TWS reports errors for orders and requests in the same id field, so request ids are kept
+/// well above the order ids, which start from the `NEXT_VALID_ID` sequence of the account.
+pub const FIRST_REQUEST_ID: i32 = 1 << 30;
+
+/// Hands out order ids from the `NEXT_VALID_ID` sequence.
+///
+/// TWS sends the next valid id after `StartApi` and in reply to `ReqIds`, every id it reports
+/// moves the sequence forward but never back, so ids already handed out are not reused.
+#[derive(Debug, Default)]
+pub struct OrderIdAllocator {
+    next_id: AtomicI32,
+}
+
+impl OrderIdAllocator {
+    #[must_use]
+    pub fn new(next_valid_id: i32) -> Self {
+        OrderIdAllocator {
+            next_id: AtomicI32::new(next_valid_id),
+        }
+    }
+
+    /// Take the next order id.
+    pub fn next(&self) -> i32 {
+        let order_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if order_id >= FIRST_REQUEST_ID {
+            warn!(order_id, "order id overlaps with the request ids");
+        }
+        order_id
+    }
+
+    /// The id the next call to [`next`](OrderIdAllocator::next) returns.
+    #[must_use]
+    pub fn peek(&self) -> i32 {
+        self.next_id.load(Ordering::Relaxed)
+    }
+
+    /// Move the sequence forward to the next valid id reported by TWS.
+    pub fn update(&self, next_valid_id: i32) {
+        let previous = self.next_id.fetch_max(next_valid_id, Ordering::Relaxed);
+        debug!(previous, next_valid_id, "updating next valid order id");
+    }
+}
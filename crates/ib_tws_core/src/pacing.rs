@@ -0,0 +1,285 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::message::Request;
+
+/// Messages TWS accepts per second before it reports error 100, or disconnects.
+pub const MAX_MESSAGES_PER_SECOND: usize = 50;
+
+/// Historical data requests with the same parameters have to be this far apart.
+pub const IDENTICAL_HISTORICAL_INTERVAL: Duration = Duration::from_secs(15);
+/// At most `MAX_HISTORICAL_PER_CONTRACT` historical data requests for the same contract and
+/// data type within this window.
+pub const HISTORICAL_PER_CONTRACT_WINDOW: Duration = Duration::from_secs(2);
+pub const MAX_HISTORICAL_PER_CONTRACT: usize = 6;
+/// At most `MAX_HISTORICAL_REQUESTS` historical data requests within this window.
+pub const HISTORICAL_WINDOW: Duration = Duration::from_mins(10);
+pub const MAX_HISTORICAL_REQUESTS: usize = 60;
+
+const MESSAGE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Keeps the outgoing requests within the pacing limits of TWS.
+///
+/// Every message counts towards the limit of 50 per second. `ReqHistoricalData` and
+/// `ReqHistoricalTicks` are also scheduled according to the historical data rules: no
+/// identical request within 15 seconds, at most 6 requests for the same contract within 2
+/// seconds and at most 60 requests within 10 minutes. Historical requests are released in the
+/// order they were scheduled.
+#[derive(Debug, Default)]
+pub struct Pacer {
+    /// When the messages of the last second were sent.
+    messages: VecDeque<Instant>,
+    /// When the historical requests of the last 10 minutes were, or will be, released.
+    historical: VecDeque<HistoricalEntry>,
+}
+
+#[derive(Debug)]
+struct HistoricalEntry {
+    at: Instant,
+    contract: String,
+    parameters: String,
+}
+
+impl Pacer {
+    #[must_use]
+    pub fn new() -> Self {
+        Pacer::default()
+    }
+
+    /// Whether the request is subject to the historical data pacing rules.
+    #[must_use]
+    pub fn is_historical(request: &Request) -> bool {
+        matches!(
+            request,
+            Request::ReqHistoricalData(_) | Request::ReqHistoricalTicks(_)
+        )
+    }
+
+    /// How long sending `request` at `now` would be held back, including the historical
+    /// requests already scheduled ahead of it.
+    #[must_use]
+    pub fn delay(&self, request: &Request, now: Instant) -> Duration {
+        let release = match historical_key(request) {
+            Some((contract, parameters)) => self.release_time(&contract, &parameters, now),
+            None => now,
+        };
+        release.max(self.message_slot(now)) - now
+    }
+
+    /// Schedule a historical request, returns when it may be released. Other requests may be
+    /// released right away.
+    pub fn schedule(&mut self, request: &Request, now: Instant) -> Instant {
+        let Some((contract, parameters)) = historical_key(request) else {
+            return now;
+        };
+        self.prune(now);
+        let at = self.release_time(&contract, &parameters, now);
+        self.historical.push_back(HistoricalEntry {
+            at,
+            contract,
+            parameters,
+        });
+        debug!(delay = ?(at - now), "scheduled historical request");
+        at
+    }
+
+    /// How long to wait at `now` before the next message may be written.
+    #[must_use]
+    pub fn message_delay(&self, now: Instant) -> Duration {
+        self.message_slot(now) - now
+    }
+
+    /// Record a message written at `now`.
+    pub fn record_message(&mut self, now: Instant) {
+        while self
+            .messages
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= MESSAGE_WINDOW)
+        {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(now);
+    }
+
+    fn message_slot(&self, now: Instant) -> Instant {
+        let in_window = self
+            .messages
+            .iter()
+            .filter(|sent| now.duration_since(**sent) < MESSAGE_WINDOW)
+            .count();
+        if in_window < MAX_MESSAGES_PER_SECOND {
+            return now;
+        }
+        self.messages[self.messages.len() - MAX_MESSAGES_PER_SECOND] + MESSAGE_WINDOW
+    }
+
+    fn release_time(&self, contract: &str, parameters: &str, now: Instant) -> Instant {
+        let mut at = self.historical.back().map_or(now, |last| last.at.max(now));
+        // Each rule can only push the release time later, stop once none of them does.
+        loop {
+            let previous = at;
+
+            if let Some(identical) = self
+                .historical
+                .iter()
+                .rev()
+                .find(|entry| entry.parameters == parameters)
+            {
+                at = at.max(identical.at + IDENTICAL_HISTORICAL_INTERVAL);
+            }
+
+            let same_contract: Vec<_> = self
+                .historical
+                .iter()
+                .rev()
+                .filter(|entry| {
+                    entry.contract == contract
+                        && at.duration_since(entry.at) < HISTORICAL_PER_CONTRACT_WINDOW
+                })
+                .collect();
+            if same_contract.len() >= MAX_HISTORICAL_PER_CONTRACT {
+                let oldest = same_contract[MAX_HISTORICAL_PER_CONTRACT - 1];
+                at = at.max(oldest.at + HISTORICAL_PER_CONTRACT_WINDOW);
+            }
+
+            let in_window: Vec<_> = self
+                .historical
+                .iter()
+                .rev()
+                .filter(|entry| at.duration_since(entry.at) < HISTORICAL_WINDOW)
+                .collect();
+            if in_window.len() >= MAX_HISTORICAL_REQUESTS {
+                let oldest = in_window[MAX_HISTORICAL_REQUESTS - 1];
+                at = at.max(oldest.at + HISTORICAL_WINDOW);
+            }
+
+            if at == previous {
+                return at;
+            }
+        }
+    }
+
+    /// Forget the historical requests which no longer count towards any limit.
+    pub fn prune(&mut self, now: Instant) {
+        while self
+            .historical
+            .front()
+            .is_some_and(|entry| now.saturating_duration_since(entry.at) >= HISTORICAL_WINDOW)
+        {
+            self.historical.pop_front();
+        }
+    }
+}
+
+/// The contract and data type, and the full parameters of a historical request, with the
+/// request id left out.
+fn historical_key(request: &Request) -> Option<(String, String)> {
+    match request {
+        Request::ReqHistoricalData(msg) => {
+            let mut msg = msg.clone();
+            msg.req_id = 0;
+            Some((
+                format!("{:?}/{}", msg.contract, msg.what_to_show),
+                format!("{msg:?}"),
+            ))
+        }
+        Request::ReqHistoricalTicks(msg) => {
+            let mut msg = msg.clone();
+            msg.req_id = 0;
+            Some((
+                format!("{:?}/{}", msg.contract, msg.what_to_show),
+                format!("{msg:?}"),
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::Contract, message::request::ReqHistoricalData};
+
+    fn historical(symbol: &str, duration: &str) -> Request {
+        Request::ReqHistoricalData(ReqHistoricalData::new(
+            Contract::new_stock(symbol, "SMART", "USD").unwrap(),
+            String::new(),
+            duration.to_string(),
+            "1 day".to_string(),
+            "TRADES".to_string(),
+            1,
+            1,
+            false,
+            vec![],
+        ))
+    }
+
+    #[test]
+    fn messages_per_second() {
+        let mut pacer = Pacer::new();
+        let start = Instant::now();
+        for _ in 0..MAX_MESSAGES_PER_SECOND {
+            assert_eq!(pacer.message_delay(start), Duration::ZERO);
+            pacer.record_message(start);
+        }
+
+        assert_eq!(pacer.message_delay(start), MESSAGE_WINDOW);
+        let later = start + Duration::from_millis(400);
+        assert_eq!(pacer.message_delay(later), Duration::from_millis(600));
+        assert_eq!(pacer.message_delay(start + MESSAGE_WINDOW), Duration::ZERO);
+    }
+
+    #[test]
+    fn identical_historical_requests() {
+        let mut pacer = Pacer::new();
+        let start = Instant::now();
+        assert_eq!(pacer.schedule(&historical("AAPL", "1 D"), start), start);
+        assert_eq!(pacer.schedule(&historical("AAPL", "2 D"), start), start);
+
+        let later = start + Duration::from_secs(5);
+        let identical = historical("AAPL", "1 D");
+        assert_eq!(pacer.delay(&identical, later), Duration::from_secs(10));
+        assert_eq!(
+            pacer.schedule(&identical, later),
+            start + IDENTICAL_HISTORICAL_INTERVAL
+        );
+    }
+
+    #[test]
+    fn historical_requests_per_contract() {
+        let mut pacer = Pacer::new();
+        let start = Instant::now();
+        for days in 1..=MAX_HISTORICAL_PER_CONTRACT {
+            let request = historical("AAPL", &format!("{days} D"));
+            assert_eq!(pacer.schedule(&request, start), start);
+        }
+
+        assert_eq!(
+            pacer.delay(&historical("MSFT", "1 D"), start),
+            Duration::ZERO
+        );
+        assert_eq!(
+            pacer.schedule(&historical("AAPL", "7 D"), start),
+            start + HISTORICAL_PER_CONTRACT_WINDOW
+        );
+    }
+
+    #[test]
+    fn historical_requests_per_ten_minutes() {
+        let mut pacer = Pacer::new();
+        let start = Instant::now();
+        let mut now = start;
+        for i in 0..MAX_HISTORICAL_REQUESTS {
+            let request = historical(&format!("S{i}"), "1 D");
+            assert_eq!(pacer.schedule(&request, now), now);
+            now += Duration::from_secs(1);
+        }
+
+        assert_eq!(
+            pacer.schedule(&historical("AAPL", "1 D"), now),
+            start + HISTORICAL_WINDOW
+        );
+    }
+}
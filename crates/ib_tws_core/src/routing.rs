@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use crate::{
+    message::{response::ErrMsgMsg, Response},
+    Error,
+};
+
+/// The request id a response is routed to. Warnings are left to the warnings of the client,
+/// and responses keyed by the request opcode are only broadcast.
+pub(crate) fn route_id(response: &Response) -> Option<i32> {
+    match response {
+        Response::ErrMsgMsg(err) if err.is_warning() => None,
+        response if response.is_global() => None,
+        response => response.request_id(),
+    }
+}
+
+/// The order a status, open order, live execution or commission report refers to.
+/// Commission reports only carry the execution id, so `execution_order_ids` keeps the order
+/// id of the executions until their report comes in.
+pub(crate) fn order_route_id(
+    response: &Response,
+    execution_order_ids: &mut HashMap<String, i32>,
+) -> Option<i32> {
+    match response {
+        Response::OrderStatusMsg(msg) => Some(msg.id),
+        Response::OpenOrderMsg(msg) => Some(msg.order_id),
+        Response::ExecutionDataMsg(msg) if msg.req_id == -1 => {
+            execution_order_ids.insert(msg.exec.exec_id.clone(), msg.exec.order_id);
+            Some(msg.exec.order_id)
+        }
+        Response::CommissionReportMsg(msg) => execution_order_ids.remove(&msg.report.exec_id),
+        _ => None,
+    }
+}
+
+/// Turn an error message into a stream item. Warnings don't fail the request and are left
+/// to the warnings of the client.
+pub(crate) fn api_error<T>(err: ErrMsgMsg) -> Option<Result<T, Error>> {
+    if err.is_warning() {
+        debug!(?err, "skipping warning");
+        None
+    } else {
+        Some(Err(Error::ApiError(Box::new(err))))
+    }
+}
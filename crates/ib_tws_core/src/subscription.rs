@@ -0,0 +1,110 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures::{Stream, StreamExt};
+
+use crate::{message::Request, AsyncClient, Error};
+
+/// The replies to a streaming request.
+///
+/// TWS keeps sending data for the request until it is cancelled, so dropping the subscription
+/// sends the matching cancel request. Use [`cancel`](Subscription::cancel) to find out whether
+/// it could be sent. Once TWS ended the request, with its last reply or an error, nothing is
+/// sent.
+pub struct Subscription<'a, T> {
+    client: &'a AsyncClient,
+    request_id: i32,
+    cancel_request: Option<Request>,
+    stream: Pin<Box<dyn Stream<Item = Result<T, Error>> + Send + 'a>>,
+    /// Whether TWS ends the request after this reply.
+    is_last: fn(&T) -> bool,
+    /// Whether the end of the stream leaves the request open in TWS.
+    stops_early: bool,
+}
+
+impl<'a, T> Subscription<'a, T> {
+    pub(crate) fn new(
+        client: &'a AsyncClient,
+        request_id: i32,
+        cancel_request: Request,
+        stream: impl Stream<Item = Result<T, Error>> + Send + 'a,
+    ) -> Self {
+        Subscription {
+            client,
+            request_id,
+            cancel_request: Some(cancel_request),
+            stream: Box::pin(stream),
+            is_last: |_| false,
+            stops_early: false,
+        }
+    }
+
+    /// TWS ends the request by itself after a reply matching `is_last`.
+    pub(crate) fn ends_after(mut self, is_last: fn(&T) -> bool) -> Self {
+        self.is_last = is_last;
+        self
+    }
+
+    /// The stream ends before TWS ends the request, e.g. with the first full snapshot of
+    /// updates which keep coming, so the request is still cancelled once dropped.
+    pub(crate) fn stops_early(mut self) -> Self {
+        self.stops_early = true;
+        self
+    }
+
+    #[must_use]
+    pub fn request_id(&self) -> i32 {
+        self.request_id
+    }
+
+    /// Cancel the request.
+    /// # Errors
+    /// Returns an error if the request channel is closed.
+    #[allow(clippy::unused_async)]
+    pub async fn cancel(mut self) -> Result<(), Error> {
+        match self.cancel_request.take() {
+            Some(request) => self.client.cancel_subscription(self.request_id, request),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Drop for Subscription<'_, T> {
+    fn drop(&mut self) {
+        if let Some(request) = self.cancel_request.take() {
+            if let Err(error) = self.client.cancel_subscription(self.request_id, request) {
+                debug!(request_id = self.request_id, %error, "failed to cancel subscription");
+            }
+        }
+    }
+}
+
+impl<T> Stream for Subscription<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.stream.poll_next_unpin(cx));
+        let ended = match &item {
+            Some(Ok(reply)) => (self.is_last)(reply),
+            // Warnings don't get here, errors end the request.
+            Some(Err(_)) => true,
+            None => !self.stops_early,
+        };
+        if ended && self.cancel_request.take().is_some() {
+            self.client.forget_subscription(self.request_id);
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl<T> fmt::Debug for Subscription<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("request_id", &self.request_id)
+            .field("cancel_request", &self.cancel_request)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,172 @@
+//! The blocking client against the mock server, which runs on a runtime of its own.
+
+use std::{collections::HashSet, time::Duration};
+
+use ib_tws_core::{
+    domain::{market_data::TickType, Contract, ContractDetails, Order},
+    message::{
+        constants::{
+            CANCEL_HISTORICAL_DATA, CANCEL_MKT_DATA, MAX_VERSION, PLACE_ORDER, REQ_CONTRACT_DATA,
+            REQ_HISTORICAL_DATA, REQ_MKT_DATA, START_API,
+        },
+        request::{ReqContractDetails, ReqHistoricalData, ReqMktData},
+        Response,
+    },
+    BlockingClient, Error,
+};
+use ib_tws_mock::{responses, MockOptions, MockServer};
+use rust_decimal::Decimal;
+use tokio::runtime::Runtime;
+
+const CLIENT_ID: i32 = 7;
+
+struct Mock {
+    runtime: Runtime,
+    server: MockServer,
+}
+
+impl Mock {
+    fn start() -> Self {
+        let runtime = Runtime::new().unwrap();
+        let options = MockOptions::new().accounts(["DU111"]).next_valid_id(100);
+        let server = runtime
+            .block_on(MockServer::start_with_options(options))
+            .unwrap();
+        Mock { runtime, server }
+    }
+
+    fn connect(&self) -> BlockingClient {
+        BlockingClient::connect(self.server.addr(), CLIENT_ID).unwrap()
+    }
+
+    /// Wait until the mock received a request with `opcode`, returns its request id.
+    fn wait_for(&self, opcode: i32) -> Option<i32> {
+        self.runtime
+            .block_on(self.server.wait_for(opcode))
+            .request_id()
+    }
+}
+
+fn contract() -> Contract {
+    Contract::new_stock("AAPL", "SMART", "USD").unwrap()
+}
+
+#[test]
+fn handshake() {
+    let mock = Mock::start();
+    let client = mock.connect();
+
+    assert_eq!(client.server_version(), MAX_VERSION);
+    assert_eq!(client.managed_accounts(), ["DU111"]);
+    assert_eq!(client.next_order_id(), 100);
+    let start_api = mock.runtime.block_on(mock.server.wait_for(START_API));
+    assert_eq!(start_api.field(2), Some(CLIENT_ID.to_string().as_str()));
+}
+
+#[test]
+fn contract_details() {
+    let mock = Mock::start();
+    let details = ContractDetails {
+        contract: contract(),
+        long_name: "APPLE INC".to_string(),
+        ..ContractDetails::default()
+    };
+    mock.server.reply(
+        REQ_CONTRACT_DATA,
+        vec![
+            responses::contract_data(0, details),
+            responses::contract_data_end(0),
+        ],
+    );
+    let client = mock.connect();
+
+    let details = client
+        .request_contract_details(ReqContractDetails::new(contract()))
+        .unwrap();
+    assert_eq!(details.contract.symbol, "AAPL");
+    assert_eq!(details.long_name, "APPLE INC");
+}
+
+#[test]
+fn historical_data_times_out_and_cancels() {
+    let mock = Mock::start();
+    let client = mock.connect();
+
+    let request = ReqHistoricalData::new(
+        contract(),
+        String::new(),
+        "1 D".to_string(),
+        "1 day".to_string(),
+        "TRADES".to_string(),
+        1,
+        1,
+        false,
+        vec![],
+    );
+    let result =
+        client.request_historical_data_with_timeout(request, Some(Duration::from_millis(100)));
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+    assert_eq!(
+        mock.wait_for(CANCEL_HISTORICAL_DATA),
+        mock.wait_for(REQ_HISTORICAL_DATA)
+    );
+}
+
+#[test]
+fn dropped_subscription_cancels() {
+    let mock = Mock::start();
+    mock.server.reply(
+        REQ_MKT_DATA,
+        vec![responses::tick_price(
+            0,
+            TickType::BID,
+            185.5,
+            Decimal::new(300, 0),
+        )],
+    );
+    let client = mock.connect();
+
+    let request = ReqMktData::new(contract(), HashSet::new(), false, false, vec![]);
+    let mut subscription = client.request_market_data(request).unwrap();
+    let request_id = subscription.request_id();
+    let tick = subscription.next();
+    assert!(
+        matches!(tick, Some(Ok(Response::TickPriceMsg(ref msg))) if msg.req_id == request_id),
+        "{tick:?}"
+    );
+
+    drop(subscription);
+    assert_eq!(mock.wait_for(CANCEL_MKT_DATA), Some(request_id));
+}
+
+#[test]
+fn order_status() {
+    let mock = Mock::start();
+    mock.server.reply(
+        PLACE_ORDER,
+        vec![
+            responses::order_status(0, "Submitted", Decimal::ZERO, Decimal::new(100, 0), 0.0),
+            responses::order_status(0, "Filled", Decimal::new(100, 0), Decimal::ZERO, 185.5),
+        ],
+    );
+    let client = mock.connect();
+
+    let order = Order {
+        action: "BUY".to_string(),
+        total_quantity: Decimal::new(100, 0),
+        order_type: "LMT".to_string(),
+        lmt_price: 185.5,
+        ..Order::default()
+    };
+    let mut handle = client.place_order(contract(), order).unwrap();
+    assert_eq!(handle.order_id(), 100);
+    let statuses: Vec<_> = handle
+        .by_ref()
+        .take(2)
+        .map(|response| match response {
+            Ok(Response::OrderStatusMsg(msg)) if msg.id == 100 => msg.status,
+            response => panic!("unexpected response {response:?}"),
+        })
+        .collect();
+    assert_eq!(statuses, ["Submitted", "Filled"]);
+}
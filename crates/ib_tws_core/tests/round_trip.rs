@@ -0,0 +1,1458 @@
+//! Every request and response survives the trip through the client and the server side of
+//! `Context`: what the client encodes the server decodes, and the other way around.
+
+use std::fmt::Debug;
+
+use bytes::BytesMut;
+use ib_tws_core::{
+    domain::{
+        condition::{
+            ExecutionCondition, MarginCondition, OrderCondition, PercentChangeCondition,
+            PriceCondition, TimeCondition, VolumeCondition,
+        },
+        market_data::{GenericTick, MarketDataType, TickByTickType},
+        misc::ServerLogLevel,
+        Bar, ComboLeg, CommissionReport, Contract, ContractDescription, ContractDetails,
+        DeltaNeutralContract, DepthMktDataDescription, Execution, ExecutionFilter, FamilyCode,
+        HistogramEntry, HistoricalSession, HistoricalTick, HistoricalTickBidAsk,
+        HistoricalTickLast, Liquidities, NewsProvider, Order, OrderComboLeg, OrderState,
+        PriceIncrement, ScannerSubscription, SoftDollarTier, TagValue, TickAttr, TickType,
+        WshEventData,
+    },
+    message::{constants::*, context::Context, request::*, response::*, Request, Response},
+};
+use ordered_float::NotNan;
+use rust_decimal::Decimal;
+
+const ADVANCED_ORDER_REJECT: &str =
+    r#"{"rejectReason":"Price exceeds the limit","options":[{"code":"8229","text":"Override"}]}"#;
+
+/// The samples of every variant of `$message`, failing to compile when one is missing.
+macro_rules! samples {
+    ($message:ident { $($variant:ident($sample:expr)),* $(,)? }) => {{
+        fn _exhaustive(message: &$message) {
+            match message {
+                $($message::$variant(_) => {})*
+            }
+        }
+        vec![$($message::$variant($sample)),*]
+    }};
+}
+
+fn context(server_version: i32) -> Context {
+    let mut ctx = Context::new();
+    ctx.set_server_version(server_version);
+    ctx
+}
+
+/// The fields of `sent` which didn't arrive as they were sent, if any.
+fn compare<T: Debug>(sent: &T, received: &T, rest: &BytesMut) -> Option<String> {
+    let sent = format!("{sent:#?}");
+    let received = format!("{received:#?}");
+    let mut differences = Vec::new();
+    if sent.lines().count() == received.lines().count() {
+        differences.extend(
+            sent.lines()
+                .zip(received.lines())
+                .filter(|(sent, received)| sent != received)
+                .map(|(sent, received)| {
+                    let sent = sent.trim().trim_end_matches(',');
+                    let received = received.trim().trim_end_matches(',');
+                    format!("  sent {sent}, received {received}")
+                }),
+        );
+    } else {
+        differences.push(format!("  received {received}"));
+    }
+    if !rest.is_empty() {
+        differences.push(format!("  left {rest:?} undecoded"));
+    }
+    if differences.is_empty() {
+        return None;
+    }
+    let name = sent.split('(').next().unwrap_or_default();
+    Some(format!("{name}:\n{}", differences.join("\n")))
+}
+
+fn contract() -> Contract {
+    Contract {
+        con_id: 265_598,
+        symbol: "AAPL".to_string(),
+        sec_type: "OPT".to_string(),
+        last_trade_date_or_contract_month: "20251219".to_string(),
+        strike: 200.5,
+        right: "C".to_string(),
+        multiplier: "100".to_string(),
+        exchange: "SMART".to_string(),
+        primary_exch: "NASDAQ".to_string(),
+        currency: "USD".to_string(),
+        local_symbol: "AAPL  251219C00200500".to_string(),
+        trading_class: "AAPL".to_string(),
+        ..Contract::default()
+    }
+}
+
+/// A stock, with the fields requests send for a contract besides its id.
+/// `contract()` as the messages without a primary exchange report it.
+fn contract_without_primary_exch() -> Contract {
+    Contract {
+        primary_exch: String::new(),
+        ..contract()
+    }
+}
+
+fn stock() -> Contract {
+    Contract {
+        con_id: 265_598,
+        symbol: "AAPL".to_string(),
+        sec_type: "STK".to_string(),
+        exchange: "SMART".to_string(),
+        primary_exch: "NASDAQ".to_string(),
+        currency: "USD".to_string(),
+        local_symbol: "AAPL".to_string(),
+        ..Contract::default()
+    }
+}
+
+fn expired() -> Contract {
+    Contract {
+        include_expired: true,
+        ..contract()
+    }
+}
+
+fn combo_leg(con_id: i32, action: &str) -> ComboLeg {
+    ComboLeg {
+        con_id,
+        ratio: 2,
+        action: action.to_string(),
+        exchange: "SMART".to_string(),
+        open_close: 0,
+        short_sale_slot: 0,
+        designated_location: String::new(),
+        exempt_code: -1,
+    }
+}
+
+/// A spread, market data and historical data requests send the first fields of its legs.
+fn combo() -> Contract {
+    Contract {
+        symbol: "AAPL".to_string(),
+        sec_type: "BAG".to_string(),
+        exchange: "SMART".to_string(),
+        currency: "USD".to_string(),
+        combo_legs: vec![combo_leg(265_598, "BUY"), combo_leg(8314, "SELL")],
+        ..Contract::default()
+    }
+}
+
+fn delta_neutral_combo() -> Contract {
+    Contract {
+        delta_neutral_contract: Some(DeltaNeutralContract::new(8314, 0.5, 185.25)),
+        ..combo()
+    }
+}
+
+fn order() -> Order {
+    Order {
+        order_id: 24,
+        action: "SELL".to_string(),
+        total_quantity: Decimal::new(1005, 1),
+        display_size: Decimal::new(10, 0),
+        order_type: "LMT".to_string(),
+        lmt_price: 185.5,
+        aux_price: 184.25,
+        tif: "GTC".to_string(),
+        account: "DU111".to_string(),
+        oca_group: "oca".to_string(),
+        open_close: "C".to_string(),
+        origin: 1,
+        order_ref: "ref".to_string(),
+        transmit: false,
+        parent_id: 23,
+        block_order: true,
+        sweep_to_fill: true,
+        trigger_method: 2,
+        outside_rth: true,
+        hidden: true,
+        discretionary_amt: 0.5,
+        good_after_time: "20240102 09:30:00".to_string(),
+        good_till_date: "20240103 16:00:00".to_string(),
+        fa_group: "group".to_string(),
+        fa_method: "EqualQuantity".to_string(),
+        fa_percentage: "50".to_string(),
+        model_code: "model".to_string(),
+        short_sale_slot: 2,
+        designated_location: "location".to_string(),
+        exempt_code: 3,
+        oca_type: 2,
+        rule_80a: "I".to_string(),
+        settling_firm: "firm".to_string(),
+        all_or_none: true,
+        min_qty: Decimal::new(50, 0),
+        percent_offset: 0.05,
+        nbbo_price_cap: 190.0,
+        auction_strategy: 2,
+        starting_price: 185.0,
+        stock_ref_price: 184.0,
+        delta: 0.4,
+        stock_range_lower: 180.0,
+        stock_range_upper: 190.0,
+        override_percentage_constraints: true,
+        volatility: 0.3,
+        volatility_type: 2,
+        delta_neutral_order_type: "LMT".to_string(),
+        delta_neutral_aux_price: 1.5,
+        delta_neutral_con_id: 8314,
+        delta_neutral_settling_firm: "dn firm".to_string(),
+        delta_neutral_clearing_account: "dn account".to_string(),
+        delta_neutral_clearing_intent: "IB".to_string(),
+        delta_neutral_open_close: "O".to_string(),
+        delta_neutral_short_sale: true,
+        delta_neutral_short_sale_slot: 1,
+        delta_neutral_designated_location: "dn location".to_string(),
+        continuous_update: 1,
+        reference_price_type: 1,
+        trail_stop_price: 183.0,
+        trailing_percent: 1.5,
+        scale_init_level_size: 10,
+        scale_subs_level_size: 5,
+        scale_price_increment: 0.1,
+        scale_price_adjust_value: 0.05,
+        scale_price_adjust_interval: 60,
+        scale_profit_offset: 0.2,
+        scale_auto_reset: true,
+        scale_init_position: 1,
+        scale_init_fill_qty: 2,
+        scale_random_percent: true,
+        scale_table: "table".to_string(),
+        active_start_time: "20240102 09:30:00".to_string(),
+        active_stop_time: "20240102 16:00:00".to_string(),
+        hedge_type: "D".to_string(),
+        hedge_param: "0.5".to_string(),
+        opt_out_smart_routing: true,
+        clearing_account: "account".to_string(),
+        clearing_intent: "Away".to_string(),
+        not_held: true,
+        algo_strategy: "Adaptive".to_string(),
+        algo_params: vec![TagValue::new("adaptivePriority", "Normal")],
+        algo_id: "algo".to_string(),
+        what_if: true,
+        order_misc_options: vec![TagValue::new("misc", "option")],
+        order_combo_legs: vec![
+            OrderComboLeg { price: 1.25 },
+            OrderComboLeg { price: f64::MAX },
+        ],
+        smart_combo_routing_params: vec![TagValue::new("NonGuaranteed", "1")],
+        solicited: true,
+        randomize_size: true,
+        randomize_price: true,
+        conditions: vec![
+            OrderCondition::PriceCondition(PriceCondition {
+                is_conjunction_connection: true,
+                is_more: true,
+                conid: 265_598,
+                exchange: "SMART".to_string(),
+                price: 190.5,
+                trigger_mode: 2,
+            }),
+            OrderCondition::TimeCondition(TimeCondition {
+                is_conjunction_connection: false,
+                is_more: false,
+                time: "20240102 12:00:00".to_string(),
+            }),
+            OrderCondition::MarginCondition(MarginCondition {
+                is_conjunction_connection: true,
+                is_more: false,
+                percent: 30,
+            }),
+            OrderCondition::ExecutionCondition(ExecutionCondition {
+                is_conjunction_connection: true,
+                sec_type: "STK".to_string(),
+                exchange: "SMART".to_string(),
+                symbol: "MSFT".to_string(),
+            }),
+            OrderCondition::VolumeCondition(VolumeCondition {
+                is_conjunction_connection: false,
+                is_more: true,
+                conid: 265_598,
+                exchange: "SMART".to_string(),
+                volume: 100_000,
+            }),
+            OrderCondition::PercentChangeCondition(PercentChangeCondition {
+                is_conjunction_connection: true,
+                is_more: true,
+                conid: 265_598,
+                exchange: "SMART".to_string(),
+                change_percent: 2.5,
+            }),
+        ],
+        conditions_ignore_rth: true,
+        conditions_cancel_order: true,
+        adjusted_order_type: "STP".to_string(),
+        trigger_price: 186.0,
+        lmt_price_offset: 0.1,
+        adjusted_stop_price: 183.5,
+        adjusted_stop_limit_price: 183.0,
+        adjusted_trailing_amount: 0.5,
+        adjustable_trailing_unit: 1,
+        ext_operator: "operator".to_string(),
+        soft_dollar_tier: SoftDollarTier::new("tier", "1", ""),
+        cash_qty: 10_000.0,
+        mifid2_decision_maker: "maker".to_string(),
+        mified2_decision_algo: "decision algo".to_string(),
+        mified2_execution_trader: "trader".to_string(),
+        mified2_execution_algo: "execution algo".to_string(),
+        dont_use_auto_price_for_hedge: true,
+        is_oms_container: true,
+        discretionary_up_to_limit_price: true,
+        use_price_mgmt_algo: Some(true),
+        duration: 60,
+        post_to_ats: 1,
+        auto_cancel_parent: true,
+        advanced_error_override: "override".to_string(),
+        manual_order_time: "20240102 09:30:00".to_string(),
+        ..Order::default()
+    }
+}
+
+/// The orders whose type or exchange adds fields to `PlaceOrder`.
+fn pegged_orders() -> Vec<Request> {
+    let peg_bench = Order {
+        order_id: 60,
+        order_type: "PEG BENCH".to_string(),
+        reference_contract_id: 8314,
+        is_pegged_change_amount_decrease: true,
+        pegged_change_amount: 0.5,
+        reference_change_amount: 0.25,
+        reference_exchange_id: "NYSE".to_string(),
+        use_price_mgmt_algo: Some(false),
+        ..Order::default()
+    };
+    let peg_best = Order {
+        order_id: 61,
+        order_type: "PEG BEST".to_string(),
+        min_trade_qty: 100,
+        min_compete_size: 200,
+        compete_against_best_offset: Order::COMPETE_AGAINST_BEST_OFFSET_UP_TO_MID,
+        mid_offset_at_whole: 0.02,
+        mid_offset_at_half: 0.01,
+        ..Order::default()
+    };
+    let peg_mid = Order {
+        order_id: 62,
+        order_type: "PEG MID".to_string(),
+        mid_offset_at_whole: 0.03,
+        mid_offset_at_half: 0.015,
+        ..Order::default()
+    };
+    let ibkrats = Contract {
+        exchange: "IBKRATS".to_string(),
+        ..stock()
+    };
+    vec![
+        Request::PlaceOrder(PlaceOrder {
+            id: 60,
+            contract: stock(),
+            order: peg_bench,
+        }),
+        Request::PlaceOrder(PlaceOrder {
+            id: 61,
+            contract: ibkrats,
+            order: peg_best,
+        }),
+        Request::PlaceOrder(PlaceOrder {
+            id: 62,
+            contract: stock(),
+            order: peg_mid,
+        }),
+    ]
+}
+
+fn requests() -> Vec<Request> {
+    samples!(Request {
+        Handshake(Handshake {
+            min_version: MIN_VERSION,
+            max_version: MAX_VERSION,
+            option: Some("+PACEAPI".to_string()),
+        }),
+        StartApi(StartApi {
+            client_id: 7,
+            optional_capabilities: "capabilities".to_string(),
+        }),
+        CancelScannerSubscription(CancelScannerSubscription { req_id: 11 }),
+        ReqScannerParameters(ReqScannerParameters {}),
+        ReqScannerSubscription(ReqScannerSubscription {
+            req_id: 12,
+            subscribe: ScannerSubscription {
+                number_of_rows: 10,
+                instrument: "STK".to_string(),
+                location_code: "STK.US.MAJOR".to_string(),
+                scan_code: "TOP_PERC_GAIN".to_string(),
+                above_price: 5.0,
+                above_volume: 10_000,
+                ..ScannerSubscription::default()
+            },
+            options: vec![TagValue::new("marketCapAbove1e6", "1000")],
+        }),
+        ReqMktData(ReqMktData {
+            req_id: 13,
+            contract: delta_neutral_combo(),
+            generic_tick_list: [GenericTick::MarkPrice].into_iter().collect(),
+            snapshot: false,
+            regulatory_snapshot: true,
+            mkt_data_options: vec![],
+        }),
+        CancelHistoricalData(CancelHistoricalData { req_id: 14 }),
+        CancelRealtimeBars(CancelRealtimeBars { req_id: 15 }),
+        ReqHistoricalData(ReqHistoricalData {
+            req_id: 16,
+            contract: Contract {
+                include_expired: true,
+                ..combo()
+            },
+            end_date_time: "20240102 16:00:00 US/Eastern".to_string(),
+            duration_str: "1 D".to_string(),
+            bar_size_setting: "1 hour".to_string(),
+            what_to_show: "TRADES".to_string(),
+            use_rth: 1,
+            format_date: 2,
+            keepup_to_date: false,
+            chart_options: vec![],
+        }),
+        ReqHeadTimestamp(ReqHeadTimestamp {
+            req_id: 17,
+            contract: expired(),
+            what_to_show: "MIDPOINT".to_string(),
+            use_rth: 0,
+            format_date: 1,
+        }),
+        CancelHeadTimestamp(CancelHeadTimestamp { req_id: 18 }),
+        ReqRealtimeBars(ReqRealtimeBars {
+            req_id: 19,
+            contract: contract(),
+            bar_size: 5,
+            what_to_show: "BID".to_string(),
+            use_rth: true,
+            options: vec![],
+        }),
+        ReqContractDetails(ReqContractDetails::new(Contract {
+            sec_id_type: "ISIN".to_string(),
+            sec_id: "US0378331005".to_string(),
+            issuer_id: "e1234567".to_string(),
+            ..expired()
+        })),
+        ReqMktDepth(ReqMktDepth {
+            req_id: 20,
+            contract: contract(),
+            num_rows: 10,
+            is_smart_depth: true,
+            options: vec![],
+        }),
+        CancelMktData(CancelMktData { req_id: 21 }),
+        CancelMktDepth(CancelMktDepth {
+            req_id: 22,
+            is_smart_depth: true,
+        }),
+        ExerciseOptions(ExerciseOptions {
+            req_id: 23,
+            contract: contract_without_primary_exch(),
+            exercise_action: 1,
+            exercise_quantity: 3,
+            account: "DU111".to_string(),
+            overriden: 1,
+        }),
+        PlaceOrder(PlaceOrder {
+            id: 24,
+            contract: Contract {
+                sec_id_type: "ISIN".to_string(),
+                sec_id: "US0378331005".to_string(),
+                combo_legs: vec![
+                    ComboLeg {
+                        open_close: 1,
+                        short_sale_slot: 2,
+                        designated_location: "location".to_string(),
+                        exempt_code: 3,
+                        ..combo_leg(265_598, "BUY")
+                    },
+                    combo_leg(8314, "SELL"),
+                ],
+                ..delta_neutral_combo()
+            },
+            order: order(),
+        }),
+        ReqAccountUpdates(ReqAccountUpdates {
+            subscribe: true,
+            acct_code: "DU111".to_string(),
+        }),
+        ReqExecutions(ReqExecutions {
+            req_id: 25,
+            filter: ExecutionFilter {
+                client_id: 7,
+                acct_code: "DU111".to_string(),
+                time: "20240102 09:30:00".to_string(),
+                symbol: "AAPL".to_string(),
+                sec_type: "STK".to_string(),
+                exchange: "SMART".to_string(),
+                side: "BUY".to_string(),
+            },
+        }),
+        CancelOrder(CacelOrder {
+            id: 26,
+            manual_order_cancel_time: "20240102 10:00:00".to_string(),
+        }),
+        ReqOpenOrders(ReqOpenOrders {}),
+        ReqIds(ReqIds { num_ids: 1 }),
+        ReqNewsBulletins(ReqNewsBulletins { all_msgs: true }),
+        CancelNewsBulletins(CancelNewsBulletins {}),
+        SetServerLogLevel(SetServerLogLevel {
+            log_level: ServerLogLevel::Detail,
+        }),
+        ReqAutoOpenOrders(ReqAutoOpenOrders { auto_bind: true }),
+        ReqAllOpenOrders(ReqAllOpenOrders {}),
+        ReqManagedAccts(ReqManagedAccts {}),
+        RequestFA(RequestFA { fa_data_type: 1 }),
+        ReplaceFA(ReplaceFA {
+            req_id: 27,
+            fa_data_type: 1,
+            xml: "<ListOfGroups/>".to_string(),
+        }),
+        ReqCurrentTime(ReqCurrentTime {}),
+        ReqFundamentalData(ReqFundamentalData {
+            req_id: 28,
+            contract: stock(),
+            report_type: "ReportsFinSummary".to_string(),
+        }),
+        CancelFundamentalData(CancelFundamentalData { req_id: 29 }),
+        CalculateImpliedVolatility(CalculateImpliedVolatility {
+            req_id: 30,
+            contract: contract(),
+            option_price: 5.25,
+            under_price: 201.5,
+        }),
+        CancelCalculateImpliedVolatility(CancelCalculateImpliedVolatility { req_id: 31 }),
+        CalculateOptionPrice(CalculateOptionPrice {
+            req_id: 32,
+            contract: contract(),
+            volatility: 0.25,
+            under_price: 201.5,
+        }),
+        CancelCalculateOptionPrice(CancelCalculateOptionPrice { req_id: 33 }),
+        ReqGlobalCancel(ReqGlobalCancel {}),
+        ReqMarketDataType(ReqMarketDataType {
+            market_data_type: MarketDataType::DELAYED,
+        }),
+        ReqPositions(ReqPositions {}),
+        ReqSecDefOptParams(ReqSecDefOptParams {
+            req_id: 34,
+            underlying_symbol: "AAPL".to_string(),
+            fut_fop_exchange: "CBOE".to_string(),
+            underlying_sec_type: "STK".to_string(),
+            underlying_con_id: 265_598,
+        }),
+        ReqSoftDollarTiers(ReqSoftDollarTiers { req_id: 35 }),
+        CancelPositions(CancelPositions {}),
+        ReqPositionsMulti(ReqPositionsMulti {
+            req_id: 36,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+        }),
+        CancelPositionsMulti(CancelPositionsMulti { req_id: 37 }),
+        CancelAccountUpdatesMulti(CancelAccountUpdatesMulti { req_id: 38 }),
+        ReqAccountUpdatesMulti(ReqAccountUpdatesMulti {
+            req_id: 39,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+            ledger_and_nlv: true,
+        }),
+        ReqAccountSummary(ReqAccountSummary::new(
+            "All".to_string(),
+            "NetLiquidation,BuyingPower".to_string(),
+        )),
+        CancelAccountSummary(CancelAccountSummary { req_id: 40 }),
+        VerifyRequest(VerifyRequest {
+            api_name: "ib_tws".to_string(),
+            api_version: "1.0".to_string(),
+            extra_auth: true,
+        }),
+        VerifyMessage(VerifyMessage {
+            api_data: "data".to_string(),
+        }),
+        VerfyAndAuthRequest(VerfyAndAuthRequest {
+            api_name: "ib_tws".to_string(),
+            api_version: "1.0".to_string(),
+            opaque_is_vkey: "key".to_string(),
+            extra_auth: true,
+        }),
+        VerifyAndAuthMessage(VerifyAndAuthMessage {
+            api_data: "data".to_string(),
+            xyz_response: "response".to_string(),
+        }),
+        QueryDisplayGroups(QueryDisplayGroups { req_id: 41 }),
+        SubscribeToGroupEvents(SubscribeToGroupEvents {
+            req_id: 42,
+            group_id: 2,
+        }),
+        UpdateDisplayGroup(UpdateDisplayGroup {
+            req_id: 43,
+            contract_info: "265598@SMART".to_string(),
+        }),
+        UnsubscribeFromGroupEvents(UbsubscribeFromGroupEvents { req_id: 44 }),
+        MatchingSymbol(MatchingSymbol {
+            req_id: 45,
+            pattern: "AAP".to_string(),
+        }),
+        ReqFamilyCodes(ReqFamilyCodes {
+            server_version: MAX_VERSION,
+        }),
+        ReqMktDepthExchanges(ReqMktDepthExchanges {}),
+        ReqSmartComponents(ReqSmartComponents {
+            req_id: 46,
+            bbo_exchange: "a6".to_string(),
+        }),
+        ReqNewsProvider(ReqNewsProvider {
+            server_version: MAX_VERSION,
+        }),
+        ReqNewsArticle(ReqNewsArticle {
+            req_id: 47,
+            provider_code: "BZ".to_string(),
+            article_id: "BZ$04507322".to_string(),
+            options: vec![],
+        }),
+        ReqHistoricalNews(ReqHistoricalNews {
+            req_id: 48,
+            con_id: 265_598,
+            provider_code: "BZ+FLY".to_string(),
+            start_time: "2024-01-01 00:00:00.0".to_string(),
+            end_time: "2024-01-02 00:00:00.0".to_string(),
+            total_results: 10,
+            options: vec![],
+        }),
+        ReqHistogramData(ReqHistogramData {
+            req_id: 49,
+            contract: expired(),
+            use_rth: true,
+            time_period: "3 days".to_string(),
+        }),
+        CancelHistogramData(CancelHistogramData { req_id: 50 }),
+        ReqMarketRule(ReqMarketRule { market_rule_id: 26 }),
+        ReqPnl(ReqPnl {
+            req_id: 51,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+        }),
+        CancelPnl(CancelPnl { req_id: 52 }),
+        ReqPnlSingle(ReqPnlSingle {
+            req_id: 53,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+            con_id: 265_598,
+        }),
+        CancelPnlSingle(CancelPnlSingle { req_id: 54 }),
+        ReqHistoricalTicks(ReqHistoricalTicks {
+            req_id: 55,
+            contract: contract(),
+            start_time: "20240102 09:30:00".to_string(),
+            end_time: String::new(),
+            num_of_ticks: 100,
+            what_to_show: "TRADES".to_string(),
+            use_rth: 1,
+            ignore_size: true,
+            options: vec![],
+        }),
+        ReqTickByTickData(ReqTickByTickData::new(
+            contract(),
+            TickByTickType::AllLast,
+            10,
+            true,
+        )),
+        CancelTickByTickData(CancelTickByTickData { req_id: 56 }),
+        ReqCompletedOrders(ReqCompletedOrders { api_only: true }),
+        ReqWshMetaData(ReqWshMetaData::new()),
+        CancelWshMetaData(CancelWshMetaData { req_id: 57 }),
+        ReqWshEventData(ReqWshEventData::new(WshEventData {
+            con_id: 265_598,
+            filter: String::new(),
+            fill_watchlist: true,
+            fill_portfolio: false,
+            fill_competitors: true,
+            start_date: "20240101".to_string(),
+            end_date: "20240201".to_string(),
+            total_limit: 10,
+        })),
+        CancelWshEventData(CancelWshEventData { req_id: 58 }),
+        ReqUserInfo(ReqUserInfo { req_id: 59 }),
+    })
+}
+
+#[test]
+fn requests_round_trip() {
+    let mut failures = Vec::new();
+    for request in requests().into_iter().chain(pegged_orders()) {
+        let server_version = match request {
+            Request::Handshake(_) => -1,
+            _ => MAX_VERSION,
+        };
+        let mut client = context(MAX_VERSION);
+        client.extra_auth = true;
+        let mut buf = client
+            .encode_message(&request)
+            .unwrap_or_else(|error| panic!("{request:?}: {error}"));
+        match context(server_version).decode_request(&mut buf) {
+            Ok(decoded) => failures.extend(compare(&request, &decoded, &buf)),
+            Err(error) => failures.push(format!("{request:?}: {error}")),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+fn bar() -> Bar {
+    Bar {
+        time: "20240102 10:00:00".to_string(),
+        open: 187.15,
+        high: 188.44,
+        low: 183.89,
+        close: 185.64,
+        volume: Decimal::new(82_488_700, 0),
+        count: 1_009_074,
+        wap: Decimal::new(18_584, 2),
+    }
+}
+
+fn contract_details() -> ContractDetails {
+    ContractDetails {
+        contract: Contract {
+            sec_id_type: String::new(),
+            ..contract()
+        },
+        market_name: "AAPL".to_string(),
+        min_tick: 0.01,
+        price_magnifier: 1,
+        order_types: "ACTIVETIM,AD,ADJUST".to_string(),
+        valid_exchanges: "SMART,CBOE".to_string(),
+        under_con_id: 265_598,
+        long_name: "APPLE INC".to_string(),
+        contract_month: "202512".to_string(),
+        industry: "Technology".to_string(),
+        category: "Computers".to_string(),
+        sub_category: "Computers".to_string(),
+        timezone_id: "US/Eastern".to_string(),
+        trading_hours: "20240102:0930-20240102:1600".to_string(),
+        liquid_hours: "20240102:0930-20240102:1600".to_string(),
+        ev_rule: "rule".to_string(),
+        ev_multiplier: 2.0,
+        sec_id_list: vec![TagValue::new("ISIN", "US0378331005")],
+        agg_group: 2,
+        under_symbol: "AAPL".to_string(),
+        under_sec_type: "STK".to_string(),
+        market_rule_ids: "26,26".to_string(),
+        real_expiration_date: "20251219".to_string(),
+        last_trade_time: "16:00:00".to_string(),
+        stock_type: "COMMON".to_string(),
+        min_size: Decimal::new(1, 0),
+        size_increment: Decimal::new(1, 0),
+        suggested_size_increment: Decimal::new(100, 0),
+        ..ContractDetails::default()
+    }
+}
+
+fn bond_details() -> ContractDetails {
+    ContractDetails {
+        contract: Contract {
+            con_id: 147_554_578,
+            symbol: "IBM".to_string(),
+            sec_type: "BOND".to_string(),
+            exchange: "SMART".to_string(),
+            currency: "USD".to_string(),
+            trading_class: "IBM".to_string(),
+            ..Contract::default()
+        },
+        market_name: "IBM".to_string(),
+        min_tick: 0.001,
+        order_types: "LMT".to_string(),
+        valid_exchanges: "SMART".to_string(),
+        long_name: "IBM 4 02/15/42".to_string(),
+        timezone_id: "US/Eastern".to_string(),
+        last_trade_time: "16:00:00".to_string(),
+        ev_rule: "rule".to_string(),
+        ev_multiplier: 2.0,
+        sec_id_list: vec![TagValue::new("CUSIP", "459200HG9")],
+        agg_group: 2,
+        market_rule_ids: "100".to_string(),
+        min_size: Decimal::new(1, 0),
+        size_increment: Decimal::new(1, 0),
+        suggested_size_increment: Decimal::new(1, 0),
+        cusip: "459200HG9".to_string(),
+        ratings: "A".to_string(),
+        desc_append: "append".to_string(),
+        bond_type: "US".to_string(),
+        coupon_type: "FIXED".to_string(),
+        callable: true,
+        putable: false,
+        coupon: 4.0,
+        convertible: true,
+        maturity: "20420215".to_string(),
+        issue_date: "20120215".to_string(),
+        next_option_date: "20300215".to_string(),
+        next_option_type: "CALL".to_string(),
+        next_option_partial: true,
+        notes: "notes".to_string(),
+        ..ContractDetails::default()
+    }
+}
+
+fn execution() -> Execution {
+    Execution {
+        order_id: 24,
+        client_id: 7,
+        exec_id: "0000e0d5.6593a1d5.01.01".to_string(),
+        time: "20240102 10:00:00".to_string(),
+        acct_number: "DU111".to_string(),
+        exchange: "NASDAQ".to_string(),
+        side: "SLD".to_string(),
+        shares: Decimal::new(100, 0),
+        price: 185.5,
+        perm_id: 1_234_567,
+        liquidation: 1,
+        cum_qty: Decimal::new(100, 0),
+        avg_price: 185.5,
+        order_ref: "ref".to_string(),
+        ev_rule: "rule".to_string(),
+        ev_multiplier: 2.0,
+        model_code: "model".to_string(),
+        last_liquidity: Liquidities::Removed,
+    }
+}
+
+fn order_state() -> OrderState {
+    OrderState {
+        status: "Filled".to_string(),
+        init_margin_before: "1".to_string(),
+        maint_margin_before: "2".to_string(),
+        equity_with_loan_before: "3".to_string(),
+        init_margin_change: "4".to_string(),
+        maint_margin_change: "5".to_string(),
+        equity_with_loan_change: "6".to_string(),
+        init_margin_after: "7".to_string(),
+        maint_margin_after: "8".to_string(),
+        equity_with_loan_after: "9".to_string(),
+        commission: 1.0,
+        min_commission: 0.5,
+        max_commission: 1.5,
+        commission_currency: "USD".to_string(),
+        warning_text: "warning".to_string(),
+        ..OrderState::default()
+    }
+}
+
+/// `order()` as an open order message reports it: without the fields only sent with the order.
+fn open_order() -> Order {
+    Order {
+        active_start_time: String::new(),
+        active_stop_time: String::new(),
+        scale_table: String::new(),
+        algo_id: String::new(),
+        transmit: true,
+        override_percentage_constraints: false,
+        order_misc_options: Vec::new(),
+        ext_operator: String::new(),
+        mifid2_decision_maker: String::new(),
+        mified2_decision_algo: String::new(),
+        mified2_execution_trader: String::new(),
+        mified2_execution_algo: String::new(),
+        advanced_error_override: String::new(),
+        manual_order_time: String::new(),
+        ..order()
+    }
+}
+
+/// `open_order()` as a completed order message reports it, which is a subset again.
+fn completed_order() -> Order {
+    Order {
+        order_id: 0,
+        parent_id: 0,
+        block_order: false,
+        delta_neutral_open_close: String::new(),
+        what_if: false,
+        delta_neutral_settling_firm: String::new(),
+        delta_neutral_clearing_account: String::new(),
+        delta_neutral_clearing_intent: String::new(),
+        nbbo_price_cap: f64::MAX,
+        opt_out_smart_routing: false,
+        auction_strategy: 0,
+        adjusted_order_type: String::new(),
+        trigger_price: f64::MAX,
+        adjusted_stop_price: f64::MAX,
+        adjusted_stop_limit_price: f64::MAX,
+        adjusted_trailing_amount: f64::MAX,
+        adjustable_trailing_unit: 0,
+        soft_dollar_tier: SoftDollarTier::default(),
+        discretionary_up_to_limit_price: false,
+        use_price_mgmt_algo: None,
+        duration: i32::MAX,
+        post_to_ats: i32::MAX,
+        ..open_order()
+    }
+}
+
+fn tick_attr() -> TickAttr {
+    TickAttr {
+        can_auto_execute: true,
+        past_limit: false,
+        pre_open: true,
+        ..TickAttr::default()
+    }
+}
+
+fn responses() -> Vec<Response> {
+    samples!(Response {
+        HandshakeAck(HandshakeAck {
+            server_version: MAX_VERSION,
+            server_time: Some("20240102 09:30:00 EST".to_string()),
+            redirect: None,
+        }),
+        HistoricalTickLastMsg(HistoricalTickLastMsg {
+            req_id: 1,
+            ticks: vec![HistoricalTickLast {
+                time: 1_704_205_800,
+                mask: 3,
+                price: 185.5,
+                size: Decimal::new(1005, 1),
+                exchange: "NASDAQ".to_string(),
+                special_conditions: "T".to_string(),
+            }],
+            done: true,
+        }),
+        HistoricalTickBidAskMsg(HistoricalTickBidAskMsg {
+            req_id: 2,
+            ticks: vec![HistoricalTickBidAsk {
+                time: 1_704_205_800,
+                mask: 1,
+                price_bid: 185.5,
+                price_ask: 185.75,
+                size_bid: Decimal::new(300, 0),
+                size_ask: Decimal::new(25, 2),
+            }],
+            done: false,
+        }),
+        HistoricalTicksMsg(HistoricalTicksMsg {
+            req_id: 3,
+            ticks: vec![HistoricalTick {
+                time: 1_704_205_800,
+                price: 185.625,
+                size: Decimal::new(0, 0),
+            }],
+            done: true,
+        }),
+        MarketRule(MarketRule {
+            market_rule_id: 26,
+            price_increments: vec![
+                PriceIncrement {
+                    low_edge: 0.0,
+                    increment: 0.01,
+                },
+                PriceIncrement {
+                    low_edge: 1.0,
+                    increment: 0.05,
+                },
+            ],
+        }),
+        RerouteMktDepthReq(RerouteMktDepthReq {
+            req_id: 4,
+            con_id: 265_598,
+            exchange: "NASDAQ".to_string(),
+        }),
+        RerouteMktDataReq(RerouteMktDataReq {
+            req_id: 5,
+            con_id: 265_598,
+            exchange: "NASDAQ".to_string(),
+        }),
+        HistoricalDataUpdateMsg(HistoricalDataUpdateMsg {
+            req_id: 6,
+            bar: Bar {
+                count: 12,
+                ..bar()
+            },
+        }),
+        PnlSingleMsg(PnlSingleMsg {
+            req_id: 7,
+            pos: Decimal::new(100, 0),
+            daily_pnl: 12.5,
+            unrealized_pnl: 100.25,
+            realized_pnl: 3.5,
+            value: 18_550.0,
+        }),
+        PnlMsg(PnlMsg {
+            req_id: 8,
+            daily_pnl: 12.5,
+            unrealized_pnl: 100.25,
+            realized_pnl: 3.5,
+        }),
+        HistogramDataMsg(HistogramDataMsg {
+            req_id: 9,
+            items: vec![HistogramEntry {
+                price: 185.5,
+                size: Decimal::new(15, 1),
+            }],
+        }),
+        HistoricalNewsEndMsg(HistoricalNewsEndMsg {
+            req_id: 10,
+            has_more: true,
+        }),
+        HistoricalNewsMsg(HistoricalNewsMsg {
+            req_id: 11,
+            time: "2024-01-02 10:00:00.0".to_string(),
+            provider_code: "BZ".to_string(),
+            article_id: "BZ$04507322".to_string(),
+            headline: "Apple shares fall".to_string(),
+        }),
+        NewsArticleMsg(NewsArticleMsg {
+            req_id: 12,
+            article_type: 0,
+            article_text: "Apple shares fell".to_string(),
+        }),
+        NewsProviderMsg(NewsProviderMsg {
+            providers: vec![NewsProvider::new("BZ", "Benzinga Pro")],
+        }),
+        TickNewsMsg(TickNewsMsg {
+            req_id: 13,
+            time_stamp: 1_704_205_800_000,
+            provider_code: "BZ".to_string(),
+            article_id: "BZ$04507322".to_string(),
+            headline: "Apple shares fall".to_string(),
+            extra_data: "A:800015:L:en:K:n/a:C:0.5".to_string(),
+        }),
+        HeadTimestampMsg(HeadTimestampMsg {
+            req_id: 14,
+            head_time_stamp: "19801212 14:30:00".to_string(),
+        }),
+        MktDepthExchangesMsg(MktDepthExchangesMsg {
+            depth_mkt_data_descriptions: vec![DepthMktDataDescription::new(
+                "NASDAQ", "STK", "NASDAQ", "Deep2", 2,
+            )],
+        }),
+        SymbolSamplesMsg(SymbolSamplesMsg {
+            req_id: 15,
+            contract_descriptions: vec![ContractDescription {
+                contract: Contract {
+                    con_id: 265_598,
+                    symbol: "AAPL".to_string(),
+                    sec_type: "STK".to_string(),
+                    primary_exch: "NASDAQ".to_string(),
+                    currency: "USD".to_string(),
+                    description: "APPLE INC".to_string(),
+                    issuer_id: "e1234567".to_string(),
+                    ..Contract::default()
+                },
+                derivative_sec_types: vec!["OPT".to_string(), "WAR".to_string()],
+            }],
+        }),
+        FamilyCodesMsg(FamilyCodesMsg {
+            family_codes: vec![FamilyCode {
+                account_id: "DU111".to_string(),
+                family_code: "F111".to_string(),
+            }],
+        }),
+        SoftDollarTiersMsg(SoftDollarTiersMsg {
+            req_id: 16,
+            tiers: vec![SoftDollarTier::new("tier", "1", "Tier 1")],
+        }),
+        SecurityDefinitionOptionalParameterEndMsg(SecurityDefinitionOptionalParameterEndMsg {
+            req_id: 17,
+        }),
+        SecurityDefinitionOptionalParameterMsg(SecurityDefinitionOptionalParameterMsg {
+            req_id: 18,
+            exchange: "CBOE".to_string(),
+            underlying_con_id: 265_598,
+            trading_class: "AAPL".to_string(),
+            multiplier: "100".to_string(),
+            expirations: ["20251219".to_string()].into_iter().collect(),
+            strikes: [NotNan::new(200.5).unwrap()].into_iter().collect(),
+        }),
+        VerifyAndAuthCompletedMsg(VerifyAndAuthCompletedMsg {
+            is_successful: false,
+            error_text: "denied".to_string(),
+        }),
+        VerifyAndAuthMessageMsg(VerifyAndAuthMessageMsg {
+            api_data: "data".to_string(),
+            xyz_challenge: "challenge".to_string(),
+        }),
+        DisplayGroupUpdatedMsg(DisplayGroupUpdatedMsg {
+            req_id: 19,
+            contract_info: "265598@SMART".to_string(),
+        }),
+        DisplayGroupListMsg(DisplayGroupListMsg {
+            req_id: 20,
+            groups: "1|2|3".to_string(),
+        }),
+        VerifyCompletedMsg(VerifyCompletedMsg {
+            is_successful: true,
+            error_text: String::new(),
+        }),
+        VerifyMessageApiMsg(VerifyMessageApiMsg {
+            api_data: "data".to_string(),
+        }),
+        CommissionReportMsg(CommissionReportMsg {
+            report: CommissionReport {
+                exec_id: "0000e0d5.6593a1d5.01.01".to_string(),
+                commission: 1.0,
+                currency: "USD".to_string(),
+                realized_pnl: 12.5,
+                yield_value: 0.25,
+                yield_redemption_date: 20_250_101,
+            },
+        }),
+        MarketDataTypeMsg(MarketDataTypeMsg {
+            req_id: 21,
+            market_data_type: MarketDataType::DELAYED,
+        }),
+        TickSnapshotEndMsg(TickSnapshotEndMsg { req_id: 22 }),
+        DeltaNeutralValidationMsg(DeltaNeutralValidationMsg {
+            req_id: 23,
+            delta_neutral_contract: DeltaNeutralContract::new(8314, 0.5, 185.25),
+        }),
+        ExecutionDataEndMsg(ExecutionDataEndMsg { req_id: 24 }),
+        AcctDownloadEndMsg(AcctDownloadEndMsg {
+            account_name: "DU111".to_string(),
+        }),
+        OpenOrderEndMsg(OpenOrderEndMsg {}),
+        ContractDataEndMsg(ContractDataEndMsg { req_id: 25 }),
+        FundamentalDataMsg(FundamentalDataMsg {
+            req_id: 26,
+            data: "<ReportSnapshot/>".to_string(),
+        }),
+        RealTimeBarsMsg(RealTimeBarsMsg {
+            req_id: 27,
+            time: 1_704_205_800,
+            open: 187.15,
+            high: 188.44,
+            low: 183.89,
+            close: 185.64,
+            volume: 12_000,
+            wap: 185.84,
+            count: 120,
+        }),
+        CurrentTimeMsg(CurrentTimeMsg {
+            time: 1_704_205_800,
+        }),
+        ScannerDataMsg(ScannerDataMsg {
+            req_id: 28,
+            datas: vec![ScannerData {
+                rank: 0,
+                contract_details: ContractDetails {
+                    contract: Contract {
+                        con_id: 265_598,
+                        symbol: "AAPL".to_string(),
+                        sec_type: "STK".to_string(),
+                        exchange: "SMART".to_string(),
+                        currency: "USD".to_string(),
+                        local_symbol: "AAPL".to_string(),
+                        trading_class: "NMS".to_string(),
+                        ..Contract::default()
+                    },
+                    market_name: "NMS".to_string(),
+                    ..ContractDetails::default()
+                },
+                distance: "1".to_string(),
+                benchmark: "2".to_string(),
+                projection: "3".to_string(),
+                legs: String::new(),
+            }],
+        }),
+        ScannerParametersMsg(ScannerParametersMsg {
+            xml: "<ScanParameterResponse/>".to_string(),
+        }),
+        HistoricalDataMsg(HistoricalDataMsg {
+            req_id: 29,
+            start_date: "20240101 10:00:00".to_string(),
+            end_date: "20240102 16:00:00".to_string(),
+            bars: vec![bar(), bar()],
+        }),
+        ReceiveFaMsg(ReceiveFaMsg {
+            fa_data_type: 1,
+            xml: "<ListOfGroups/>".to_string(),
+        }),
+        ManagedAcctsMsg(ManagedAcctsMsg {
+            accounts: "DU111,DU222".to_string(),
+        }),
+        NewsBulletinsMsg(NewsBulletinsMsg {
+            req_id: 30,
+            msg_type: 1,
+            message: "Trading halted".to_string(),
+            originating_exch: "NYSE".to_string(),
+        }),
+        MarketDepthL2Msg(MarketDepthL2Msg {
+            id: 31,
+            position: 1,
+            market_maker: "NSDQ".to_string(),
+            operation: 0,
+            side: 1,
+            price: 185.5,
+            size: 300,
+        }),
+        MarketDepthMsg(MarketDepthMsg {
+            id: 32,
+            position: 2,
+            operation: 1,
+            side: 0,
+            price: 185.25,
+            size: Decimal::new(200, 0),
+        }),
+        ExecutionDataMsg(ExecutionDataMsg {
+            req_id: 33,
+            contract: contract_without_primary_exch(),
+            exec: execution(),
+        }),
+        BondContractDataMsg(BondContractDataMsg {
+            req_id: 34,
+            contract_details: bond_details(),
+        }),
+        ContractDataMsg(ContractDataMsg {
+            req_id: 35,
+            contract_details: contract_details(),
+        }),
+        NextValidIdMsg(NextValidIdMsg { order_id: 100 }),
+        OpenOrderMsg(OpenOrderMsg {
+            order_id: 24,
+            contract: contract_without_primary_exch(),
+            order: open_order(),
+            order_state: order_state(),
+        }),
+        ErrMsgMsg(ErrMsgMsg {
+            id: 24,
+            error_code: 201,
+            error_message: "Order rejected".to_string(),
+            advanced_order_reject_json: ADVANCED_ORDER_REJECT.to_string(),
+            advanced_order_reject: serde_json::from_str(ADVANCED_ORDER_REJECT).ok(),
+        }),
+        AcctUpdateTimeMsg(AcctUpdateTimeMsg {
+            time_stamp: "10:00".to_string(),
+        }),
+        PortfolioValueMsg(PortfolioValueMsg {
+            contract: Contract {
+                exchange: String::new(),
+                ..contract()
+            },
+            position: Decimal::new(100, 0),
+            market_price: 185.5,
+            market_value: 18_550.0,
+            average_cost: 180.25,
+            unrealized_pnl: 525.0,
+            realized_pnl: 12.5,
+            account_name: "DU111".to_string(),
+        }),
+        AcctValueMsg(AcctValueMsg {
+            key: "NetLiquidation".to_string(),
+            val: "100000.00".to_string(),
+            cur: "USD".to_string(),
+            account_name: "DU111".to_string(),
+        }),
+        OrderStatusMsg(OrderStatusMsg {
+            id: 24,
+            status: "Filled".to_string(),
+            filled: Decimal::new(100, 0),
+            remaining: Decimal::new(0, 0),
+            avg_fill_price: 185.5,
+            perm_id: 1_234_567,
+            parent_id: 23,
+            last_fill_price: 185.5,
+            client_id: 7,
+            why_held: "locate".to_string(),
+            mkt_cap_price: 190.0,
+        }),
+        TickEFPMsg(TickEFPMsg {
+            req_id: 36,
+            tick_type: Decimal::new(38, 0),
+            basis_points: 0.5,
+            formatted_basis_points: "0.50".to_string(),
+            implied_futures_price: 186.0,
+            hold_days: 30,
+            future_last_trade_date: "20240315".to_string(),
+            dividend_impact: 0.1,
+            dividends_to_last_trade_date: 0.2,
+        }),
+        TickStringMsg(TickStringMsg {
+            req_id: 37,
+            tick_type: TickType::LAST_TIMESTAMP,
+            value: "1704205800".to_string(),
+        }),
+        TickGenericMsg(TickGenericMsg {
+            req_id: 38,
+            tick_type: TickType::SHORTABLE,
+            value: 3.0,
+        }),
+        TickOptionComputationMsg(TickOptionComputationMsg {
+            req_id: 39,
+            tick_type: TickType::MODEL_OPTION,
+            tick_attrib: 1,
+            implied_vol: 0.25,
+            delta: 0.5,
+            opt_price: 5.25,
+            pv_dividend: 0.1,
+            gamma: 0.02,
+            vega: 0.15,
+            theta: -0.05,
+            und_price: 185.5,
+        }),
+        AccountSummaryEndMsg(AccountSummaryEndMsg { req_id: 40 }),
+        AccountSummaryMsg(AccountSummaryMsg {
+            req_id: 41,
+            account: "DU111".to_string(),
+            tag: "NetLiquidation".to_string(),
+            value: "100000.00".to_string(),
+            currency: "USD".to_string(),
+        }),
+        PositionEndMsg(PositionEndMsg {}),
+        PositionMsg(PositionMsg {
+            account: "DU111".to_string(),
+            contract: contract_without_primary_exch(),
+            pos: Decimal::new(1005, 1),
+            avg_cost: 180.25,
+        }),
+        TickSizeMsg(TickSizeMsg {
+            req_id: 42,
+            tick_type: TickType::BID_SIZE,
+            size: Decimal::new(300, 0),
+        }),
+        TickPriceMsg(TickPriceMsg {
+            req_id: 43,
+            tick_type: TickType::BID,
+            price: 185.5,
+            size: Decimal::new(300, 0),
+            attribs: tick_attr(),
+        }),
+        PositionMultiMsg(PositionMultiMsg {
+            req_id: 44,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+            contract: contract_without_primary_exch(),
+            pos: Decimal::new(100, 0),
+            avg_cost: 180.25,
+        }),
+        PositionMultiEndMsg(PositionMultiEndMsg { req_id: 45 }),
+        AccountUpdateMultiMsg(AccountUpdateMultiMsg {
+            req_id: 46,
+            account: "DU111".to_string(),
+            model_code: "model".to_string(),
+            key: "NetLiquidation".to_string(),
+            value: "100000.00".to_string(),
+            currency: "USD".to_string(),
+        }),
+        AccountUpdateMultiEndMsg(AccountUpdateMultiEndMsg { req_id: 47 }),
+        TickReqParamsMsg(TickReqParamsMsg {
+            req_id: 48,
+            min_tick: 0.01,
+            bbo_exchange: "a6".to_string(),
+            snapshot_permissions: 3,
+        }),
+        TickByTickAllLastMsg(TickByTickAllLastMsg {
+            req_id: 49,
+            tick_type: TickByTickType::AllLast,
+            time: 1_704_205_800,
+            price: 185.5,
+            size: Decimal::new(100, 0),
+            attribs: TickAttr {
+                past_limit: true,
+                unreported: true,
+                ..TickAttr::default()
+            },
+            exchange: "NASDAQ".to_string(),
+            special_conditions: "T".to_string(),
+        }),
+        TickByTickBidAskMsg(TickByTickBidAskMsg {
+            req_id: 50,
+            time: 1_704_205_800,
+            bid_price: 185.5,
+            ask_price: 185.75,
+            bid_size: Decimal::new(300, 0),
+            ask_size: Decimal::new(200, 0),
+            attribs: TickAttr {
+                bid_past_low: true,
+                ask_past_high: true,
+                ..TickAttr::default()
+            },
+        }),
+        TickByTickMidPointMsg(TickByTickMidPointMsg {
+            req_id: 51,
+            time: 1_704_205_800,
+            mid_point: 185.625,
+        }),
+        TickByTickNoneMsg(TickByTickNoneMsg {}),
+        SmartComponentsMsg(SmartComponentsMsg {
+            req_id: 52,
+            map: [(1, ("NYSE".to_string(), b'N'))].into_iter().collect(),
+        }),
+        CompletedOrderMsg(CompletedOrderMsg {
+            contract: contract_without_primary_exch(),
+            order: completed_order(),
+            order_state: OrderState {
+                status: "Filled".to_string(),
+                completed_time: "20240102 10:00:00".to_string(),
+                completed_status: "Filled".to_string(),
+                ..OrderState::default()
+            },
+        }),
+        CompletedOrdersEndMsg(CompletedOrdersEndMsg {}),
+        WshMetaDataMsg(WshMetaDataMsg {
+            req_id: 53,
+            data_json: "{\"meta\":1}".to_string(),
+        }),
+        WshEventDataMsg(WshEventDataMsg {
+            req_id: 54,
+            data_json: "[{\"conid\":265598}]".to_string(),
+        }),
+        ReplaceFaEndMsg(ReplaceFaEndMsg {
+            req_id: 55,
+            text: "done".to_string(),
+        }),
+        HistoricalScheduleMsg(HistoricalScheduleMsg {
+            req_id: 56,
+            start_date_time: "20240102-09:30:00".to_string(),
+            end_date_time: "20240102-16:00:00".to_string(),
+            time_zone: "US/Eastern".to_string(),
+            sessions: vec![HistoricalSession {
+                start_date_time: "20240102-09:30:00".to_string(),
+                end_date_time: "20240102-16:00:00".to_string(),
+                ref_date: "20240102".to_string(),
+            }],
+        }),
+        UserInfoMsg(UserInfoMsg {
+            req_id: 57,
+            white_branding_id: "brand".to_string(),
+        }),
+        UnknownMsg(UnknownMsg {
+            msg_id: 9999,
+            fields: vec!["9999".to_string(), "a".to_string(), String::new()],
+        }),
+    })
+}
+
+/// The other form of a handshake ack, sent by a Gateway redirecting the client.
+fn redirect() -> Response {
+    Response::HandshakeAck(HandshakeAck {
+        server_version: -1,
+        server_time: None,
+        redirect: Some("127.0.0.1:4002".to_string()),
+    })
+}
+
+#[test]
+fn responses_round_trip() {
+    let mut failures = Vec::new();
+    for response in responses().into_iter().chain([redirect()]) {
+        let client_version = match response {
+            Response::HandshakeAck(_) => -1,
+            _ => MAX_VERSION,
+        };
+        let mut buf = context(MAX_VERSION)
+            .encode_response(&response)
+            .unwrap_or_else(|error| panic!("{response:?}: {error}"));
+        let mut client = context(client_version);
+        client.set_tolerant(matches!(response, Response::UnknownMsg(_)));
+        match client.decode_message(&mut buf) {
+            Ok(decoded) => failures.extend(compare(&response, &decoded, &buf)),
+            Err(error) => failures.push(format!("{response:?}: {error}")),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
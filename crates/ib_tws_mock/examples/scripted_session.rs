@@ -0,0 +1,114 @@
+//! Runs the `AsyncClient` against a scripted mock server, no Gateway needed.
+
+#[macro_use]
+extern crate tracing;
+
+use futures::StreamExt;
+use ib_tws_core::{
+    domain::{Bar, Contract, ContractDetails, Order},
+    message::{
+        constants::{PLACE_ORDER, REQ_CONTRACT_DATA, REQ_HISTORICAL_DATA},
+        request::{ReqContractDetails, ReqHistoricalData},
+        Response,
+    },
+    ConnectOptions,
+};
+use ib_tws_mock::{responses, MockServer};
+use miette::IntoDiagnostic;
+use rust_decimal_macros::dec;
+
+#[tokio::main]
+async fn main() -> miette::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let contract = Contract::new_stock("AAPL", "SMART", "USD").into_diagnostic()?;
+
+    let server = MockServer::start().await.into_diagnostic()?;
+    server.reply(
+        REQ_CONTRACT_DATA,
+        vec![
+            responses::contract_data(
+                0,
+                ContractDetails {
+                    contract: Contract {
+                        con_id: 265_598,
+                        ..contract.clone()
+                    },
+                    long_name: "APPLE INC".to_owned(),
+                    min_tick: 0.01,
+                    ..ContractDetails::default()
+                },
+            ),
+            responses::contract_data_end(0),
+        ],
+    );
+    server.reply(
+        REQ_HISTORICAL_DATA,
+        vec![responses::historical_data(
+            0,
+            vec![Bar {
+                time: "20240102".to_owned(),
+                open: 187.15,
+                high: 188.44,
+                low: 183.89,
+                close: 185.64,
+                volume: dec!(82488700),
+                count: 1_009_074,
+                wap: dec!(185.9),
+            }],
+        )],
+    );
+    server.reply(
+        PLACE_ORDER,
+        vec![
+            responses::order_status(0, "PreSubmitted", dec!(0), dec!(100), 0.0),
+            responses::order_status(0, "Submitted", dec!(0), dec!(100), 0.0),
+            responses::order_status(0, "Filled", dec!(100), dec!(0), 185.5),
+        ],
+    );
+
+    let client = ib_tws_tokio::connect(server.addr(), ConnectOptions::new(0)).await?;
+    info!(accounts = ?client.managed_accounts().await, "connected to mock");
+
+    let details = client
+        .request_contract_details(ReqContractDetails::new(contract.clone()))
+        .await?;
+    info!(
+        con_id = details.contract.con_id,
+        long_name = details.long_name,
+        "contract details"
+    );
+
+    let bars = client
+        .request_historical_data(ReqHistoricalData::new(
+            contract.clone(),
+            String::new(),
+            "1 D".to_owned(),
+            "1 day".to_owned(),
+            "TRADES".to_owned(),
+            1,
+            1,
+            false,
+            vec![],
+        ))
+        .await?;
+    info!(?bars.bars, "historical data");
+
+    let order = Order {
+        action: "BUY".to_owned(),
+        total_quantity: dec!(100),
+        order_type: "MKT".to_owned(),
+        ..Order::default()
+    };
+    let mut order = client.place_order(contract, order).await?;
+    while let Some(update) = order.next().await {
+        info!(?update, "order update");
+        if let Response::OrderStatusMsg(status) = update? {
+            if status.status == "Filled" {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
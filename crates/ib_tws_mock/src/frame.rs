@@ -0,0 +1,72 @@
+use std::io;
+
+use bytes::BytesMut;
+use ib_tws_core::message::{context::Context, wire::TwsWireDecoder, Request};
+
+/// A request received by the [`MockServer`](crate::MockServer), decoded and split into its
+/// fields.
+#[derive(Debug, Clone)]
+pub struct RequestFrame {
+    opcode: i32,
+    fields: Vec<String>,
+    request: Request,
+    request_id: Option<i32>,
+}
+
+impl RequestFrame {
+    /// Decode a frame with the context of its connection, which knows the negotiated server
+    /// version.
+    pub(crate) fn parse(ctx: &mut Context, mut buf: BytesMut) -> io::Result<Self> {
+        let mut request = ctx.decode_request(&mut buf.clone())?;
+        let request_id = match request {
+            Request::PlaceOrder(ref msg) => Some(msg.id),
+            Request::CancelOrder(ref msg) => Some(msg.id),
+            ref mut request => request.request_id_mut().map(|id| *id),
+        };
+
+        let mut fields = Vec::new();
+        while !buf.is_empty() {
+            let field = TwsWireDecoder::split(&mut buf)?;
+            fields.push(String::from_utf8_lossy(&field).into_owned());
+        }
+        let opcode = fields
+            .first()
+            .and_then(|opcode| opcode.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing message id"))?;
+        Ok(RequestFrame {
+            opcode,
+            fields,
+            request,
+            request_id,
+        })
+    }
+
+    /// The message id, one of the request constants in `ib_tws_core::message::constants`.
+    #[must_use]
+    pub fn opcode(&self) -> i32 {
+        self.opcode
+    }
+
+    /// The decoded request.
+    #[must_use]
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// All fields of the message, starting with the message id.
+    #[must_use]
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    #[must_use]
+    pub fn field(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(String::as_str)
+    }
+
+    /// The request id, or the order id of order requests, for the requests which have one.
+    #[must_use]
+    pub fn request_id(&self) -> Option<i32> {
+        self.request_id
+    }
+}
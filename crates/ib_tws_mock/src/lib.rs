@@ -0,0 +1,16 @@
+//! A scriptable mock of the TWS API server, to test clients without a live Gateway.
+//!
+//! The mock runs on Tokio, any client speaking the API protocol can connect to it.
+
+#![warn(clippy::pedantic)]
+
+#[macro_use]
+extern crate tracing;
+
+mod server;
+pub use server::{MockOptions, MockServer, DEFAULT_ACCOUNT};
+
+mod frame;
+pub use frame::RequestFrame;
+
+pub mod responses;
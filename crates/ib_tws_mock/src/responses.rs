@@ -0,0 +1,100 @@
+//! Shorthands for the responses scripted most often, with the fields a test rarely cares
+//! about left at their defaults.
+
+use ib_tws_core::{
+    domain::{
+        market_data::{Bar, TickAttr, TickType},
+        ContractDetails,
+    },
+    message::{
+        response::{
+            ContractDataEndMsg, ContractDataMsg, ErrMsgMsg, HistoricalDataMsg, OrderStatusMsg,
+            TickPriceMsg, TickSizeMsg, TickSnapshotEndMsg,
+        },
+        Response,
+    },
+};
+use rust_decimal::Decimal;
+
+#[must_use]
+pub fn contract_data(req_id: i32, contract_details: ContractDetails) -> Response {
+    Response::ContractDataMsg(ContractDataMsg {
+        req_id,
+        contract_details,
+    })
+}
+
+#[must_use]
+pub fn contract_data_end(req_id: i32) -> Response {
+    Response::ContractDataEndMsg(ContractDataEndMsg { req_id })
+}
+
+#[must_use]
+pub fn historical_data(req_id: i32, bars: Vec<Bar>) -> Response {
+    Response::HistoricalDataMsg(HistoricalDataMsg {
+        req_id,
+        start_date: bars.first().map(|bar| bar.time.clone()).unwrap_or_default(),
+        end_date: bars.last().map(|bar| bar.time.clone()).unwrap_or_default(),
+        bars,
+    })
+}
+
+#[must_use]
+pub fn tick_price(req_id: i32, tick_type: TickType, price: f64, size: Decimal) -> Response {
+    Response::TickPriceMsg(TickPriceMsg {
+        req_id,
+        tick_type,
+        price,
+        size,
+        attribs: TickAttr::default(),
+    })
+}
+
+#[must_use]
+pub fn tick_size(req_id: i32, tick_type: TickType, size: Decimal) -> Response {
+    Response::TickSizeMsg(TickSizeMsg {
+        req_id,
+        tick_type,
+        size,
+    })
+}
+
+#[must_use]
+pub fn tick_snapshot_end(req_id: i32) -> Response {
+    Response::TickSnapshotEndMsg(TickSnapshotEndMsg { req_id })
+}
+
+#[must_use]
+pub fn order_status(
+    order_id: i32,
+    status: &str,
+    filled: Decimal,
+    remaining: Decimal,
+    avg_fill_price: f64,
+) -> Response {
+    Response::OrderStatusMsg(OrderStatusMsg {
+        id: order_id,
+        status: status.to_string(),
+        filled,
+        remaining,
+        avg_fill_price,
+        perm_id: 0,
+        parent_id: 0,
+        last_fill_price: avg_fill_price,
+        client_id: 0,
+        why_held: String::new(),
+        mkt_cap_price: 0.0,
+    })
+}
+
+/// An error for the request or order `id`, or a notification if `id` is -1.
+#[must_use]
+pub fn error(id: i32, error_code: i32, error_message: &str) -> Response {
+    Response::ErrMsgMsg(ErrMsgMsg {
+        id,
+        error_code,
+        error_message: error_message.to_string(),
+        advanced_order_reject_json: String::new(),
+        advanced_order_reject: None,
+    })
+}
@@ -0,0 +1,397 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use futures::{SinkExt, StreamExt};
+use ib_tws_core::message::{
+    constants::{
+        CANCEL_ORDER, MAX_MSG_LENGTH, MAX_VERSION, MIN_VERSION, PLACE_ORDER, REQ_CURRENT_TIME,
+        REQ_IDS, REQ_MANAGED_ACCTS, START_API,
+    },
+    context::Context,
+    response::{CurrentTimeMsg, HandshakeAck, ManagedAcctsMsg, NextValidIdMsg},
+    Request, Response,
+};
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, Notify},
+    task::JoinHandle,
+};
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+use crate::RequestFrame;
+
+/// The account reported in `ManagedAcctsMsg` unless set otherwise.
+pub const DEFAULT_ACCOUNT: &str = "DU1234567";
+
+/// The time the mock reports in the handshake, the mock has no clock of its own.
+const SERVER_TIME: &str = "20240102 09:30:00 US/Eastern";
+
+/// Options of a [`MockServer`].
+///
+/// ```
+/// # use ib_tws_mock::MockOptions;
+/// let options = MockOptions::new().accounts(["DU1", "DU2"]).next_valid_id(100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockOptions {
+    pub addr: SocketAddr,
+    pub accounts: Vec<String>,
+    pub next_valid_id: i32,
+    /// The highest server version the mock agrees to in the handshake.
+    pub server_version: i32,
+}
+
+impl MockOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        MockOptions {
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            accounts: vec![DEFAULT_ACCOUNT.to_string()],
+            next_valid_id: 1,
+            server_version: MAX_VERSION,
+        }
+    }
+
+    /// The address to listen on, by default a free port on localhost.
+    #[must_use]
+    pub fn addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    #[must_use]
+    pub fn accounts<S: Into<String>>(mut self, accounts: impl IntoIterator<Item = S>) -> Self {
+        self.accounts = accounts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn next_valid_id(mut self, next_valid_id: i32) -> Self {
+        self.next_valid_id = next_valid_id;
+        self
+    }
+
+    #[must_use]
+    pub fn server_version(mut self, server_version: i32) -> Self {
+        self.server_version = server_version.clamp(MIN_VERSION, MAX_VERSION);
+        self
+    }
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type Handler = Box<dyn FnMut(&RequestFrame) -> Vec<Response> + Send>;
+
+struct Shared {
+    options: MockOptions,
+    next_valid_id: AtomicI32,
+    handlers: Mutex<HashMap<i32, Handler>>,
+    requests: Mutex<Vec<RequestFrame>>,
+    received: Notify,
+    clients: Mutex<Vec<mpsc::UnboundedSender<Response>>>,
+}
+
+/// A stand-in for TWS or the Gateway, which speaks the API protocol on a local socket.
+///
+/// It accepts any number of clients, performs the handshake and answers `StartApi` with
+/// `ManagedAcctsMsg` and `NextValidIdMsg`, like TWS does. `ReqIds`, `ReqManagedAccts` and
+/// `ReqCurrentTime` are answered as well, every other request only gets the responses
+/// scripted with [`on`](MockServer::on) or [`reply`](MockServer::reply).
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// use ib_tws_core::{domain::ContractDetails, message::constants::REQ_CONTRACT_DATA};
+/// use ib_tws_mock::{responses, MockServer};
+///
+/// let server = MockServer::start().await?;
+/// server.reply(
+///     REQ_CONTRACT_DATA,
+///     vec![
+///         responses::contract_data(0, ContractDetails::default()),
+///         responses::contract_data_end(0),
+///     ],
+/// );
+/// // connect the client under test to `server.addr()`
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    accept: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Listen on a free port on localhost.
+    /// # Errors
+    /// Returns an error if the socket can't be bound.
+    pub async fn start() -> io::Result<Self> {
+        Self::start_with_options(MockOptions::new()).await
+    }
+
+    /// Listen with the given options.
+    /// # Errors
+    /// Returns an error if the socket can't be bound.
+    pub async fn start_with_options(options: MockOptions) -> io::Result<Self> {
+        let listener = TcpListener::bind(options.addr).await?;
+        let addr = listener.local_addr()?;
+        info!(%addr, "mock server listening");
+
+        let shared = Arc::new(Shared {
+            next_valid_id: AtomicI32::new(options.next_valid_id),
+            options,
+            handlers: Mutex::default(),
+            requests: Mutex::default(),
+            received: Notify::new(),
+            clients: Mutex::default(),
+        });
+        let accept = tokio::spawn(accept(listener, shared.clone()));
+
+        Ok(MockServer {
+            addr,
+            shared,
+            accept,
+        })
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Answer the requests with the message id `opcode`, see `ib_tws_core::message::constants`,
+    /// with the responses returned by `handler`. Replaces any earlier script for the id.
+    pub fn on<F>(&self, opcode: i32, handler: F)
+    where
+        F: FnMut(&RequestFrame) -> Vec<Response> + Send + 'static,
+    {
+        lock(&self.shared.handlers).insert(opcode, Box::new(handler));
+    }
+
+    /// Answer the requests with the message id `opcode` with `responses`, their request id, or
+    /// order id, replaced with the one of each request.
+    pub fn reply(&self, opcode: i32, responses: Vec<Response>) {
+        self.on(opcode, move |request| {
+            let Some(request_id) = request.request_id() else {
+                return responses.clone();
+            };
+            responses
+                .iter()
+                .cloned()
+                .map(|response| with_request_id(response, request.opcode(), request_id))
+                .collect()
+        });
+    }
+
+    /// Send a response to every connected client, e.g. a later order status or tick.
+    pub fn send(&self, response: &Response) {
+        lock(&self.shared.clients).retain(|client| client.send(response.clone()).is_ok());
+    }
+
+    /// Close the connections of all clients, while still accepting new ones.
+    pub fn disconnect_all(&self) {
+        lock(&self.shared.clients).clear();
+    }
+
+    /// The requests received so far, from all clients, the handshake excluded.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RequestFrame> {
+        lock(&self.shared.requests).clone()
+    }
+
+    /// Wait until a request with the message id `opcode` was received, returns the latest one.
+    pub async fn wait_for(&self, opcode: i32) -> RequestFrame {
+        loop {
+            let received = self.shared.received.notified();
+            if let Some(request) = lock(&self.shared.requests)
+                .iter()
+                .rev()
+                .find(|request| request.opcode() == opcode)
+            {
+                return request.clone();
+            }
+            received.await;
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.accept.abort();
+        self.disconnect_all();
+    }
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .field("options", &self.shared.options)
+            .finish_non_exhaustive()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn with_request_id(mut response: Response, opcode: i32, request_id: i32) -> Response {
+    if opcode == PLACE_ORDER || opcode == CANCEL_ORDER {
+        match response {
+            Response::OrderStatusMsg(ref mut msg) => msg.id = request_id,
+            Response::OpenOrderMsg(ref mut msg) => {
+                msg.order_id = request_id;
+                msg.order.order_id = request_id;
+            }
+            Response::ExecutionDataMsg(ref mut msg) => msg.exec.order_id = request_id,
+            // notifications stay notifications
+            Response::ErrMsgMsg(ref mut msg) if msg.id != -1 => msg.id = request_id,
+            _ => {}
+        }
+    } else if let Some(id) = response.request_id_mut() {
+        *id = request_id;
+    }
+    response
+}
+
+async fn accept(listener: TcpListener, shared: Arc<Shared>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                debug!(%peer, "client connected");
+                let shared = shared.clone();
+                tokio::spawn(async move {
+                    match serve(&shared, stream).await {
+                        Ok(()) => debug!(%peer, "client disconnected"),
+                        Err(error) => warn!(%peer, %error, "client connection failed"),
+                    }
+                });
+            }
+            Err(error) => warn!(%error, "failed to accept client"),
+        }
+    }
+}
+
+async fn serve(shared: &Shared, mut stream: TcpStream) -> io::Result<()> {
+    let mut prefix = [0; 4];
+    stream.read_exact(&mut prefix).await?;
+    if &prefix != b"API\0" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected the API prefix",
+        ));
+    }
+
+    let mut framed = Framed::new(
+        stream,
+        LengthDelimitedCodec::builder()
+            .max_frame_length(MAX_MSG_LENGTH)
+            .new_codec(),
+    );
+
+    let mut ctx = Context::new();
+    let mut handshake = framed.next().await.ok_or(io::ErrorKind::UnexpectedEof)??;
+    let Request::Handshake(handshake) = ctx.decode_request(&mut handshake)? else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected the handshake",
+        ));
+    };
+    let server_version = shared.options.server_version.min(handshake.max_version);
+    if server_version < handshake.min_version {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "client needs at least server version {}",
+                handshake.min_version
+            ),
+        ));
+    }
+    debug!(server_version, "handshake");
+    let ack = Response::HandshakeAck(HandshakeAck {
+        server_version,
+        server_time: Some(SERVER_TIME.to_string()),
+        redirect: None,
+    });
+    framed.send(ctx.encode_response(&ack)?.freeze()).await?;
+
+    let (client_tx, mut client_rx) = mpsc::unbounded_channel();
+    lock(&shared.clients).push(client_tx);
+
+    loop {
+        let responses = tokio::select! {
+            frame = framed.next() => match frame {
+                Some(frame) => shared.handle(RequestFrame::parse(&mut ctx, frame?)?),
+                None => return Ok(()),
+            },
+            response = client_rx.recv() => match response {
+                Some(response) => vec![response],
+                // disconnected by the server
+                None => return Ok(()),
+            },
+        };
+        for response in responses {
+            trace!(?response, "sending response");
+            framed
+                .feed(ctx.encode_response(&response)?.freeze())
+                .await?;
+        }
+        SinkExt::<bytes::Bytes>::flush(&mut framed).await?;
+    }
+}
+
+impl Shared {
+    fn handle(&self, request: RequestFrame) -> Vec<Response> {
+        debug!(?request, "received request");
+        if request.opcode() == PLACE_ORDER {
+            if let Some(order_id) = request.request_id() {
+                self.next_valid_id
+                    .fetch_max(order_id + 1, Ordering::Relaxed);
+            }
+        }
+
+        let scripted = lock(&self.handlers)
+            .get_mut(&request.opcode())
+            .map(|handler| handler(&request));
+        let responses = scripted.unwrap_or_else(|| self.default_responses(&request));
+
+        lock(&self.requests).push(request);
+        self.received.notify_waiters();
+        responses
+    }
+
+    fn default_responses(&self, request: &RequestFrame) -> Vec<Response> {
+        let managed_accounts = || {
+            Response::ManagedAcctsMsg(ManagedAcctsMsg {
+                accounts: self.options.accounts.join(","),
+            })
+        };
+        let next_valid_id = || {
+            Response::NextValidIdMsg(NextValidIdMsg {
+                order_id: self.next_valid_id.load(Ordering::Relaxed),
+            })
+        };
+        match request.opcode() {
+            START_API => vec![managed_accounts(), next_valid_id()],
+            REQ_MANAGED_ACCTS => vec![managed_accounts()],
+            REQ_IDS => vec![next_valid_id()],
+            REQ_CURRENT_TIME => vec![Response::CurrentTimeMsg(CurrentTimeMsg { time: 0 })],
+            opcode => {
+                debug!(opcode, "no script for request");
+                Vec::new()
+            }
+        }
+    }
+}
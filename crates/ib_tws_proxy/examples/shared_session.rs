@@ -0,0 +1,105 @@
+//! Two clients sharing one connection through the proxy, with a mock server in place of TWS.
+
+#[macro_use]
+extern crate tracing;
+
+use std::collections::HashSet;
+
+use futures::StreamExt;
+use ib_tws_core::{
+    domain::{market_data::TickType, Contract, Order},
+    message::{
+        constants::{PLACE_ORDER, REQ_MKT_DATA},
+        request::ReqMktData,
+        Response,
+    },
+    AsyncClient, ConnectOptions,
+};
+use ib_tws_mock::{responses, MockServer};
+use ib_tws_proxy::{Proxy, ProxyOptions};
+use miette::IntoDiagnostic;
+use rust_decimal_macros::dec;
+
+#[tokio::main]
+async fn main() -> miette::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let server = MockServer::start().await.into_diagnostic()?;
+    server.reply(
+        REQ_MKT_DATA,
+        vec![
+            responses::tick_price(0, TickType::BID, 185.5, dec!(300)),
+            responses::tick_price(0, TickType::ASK, 185.6, dec!(200)),
+        ],
+    );
+    server.reply(
+        PLACE_ORDER,
+        vec![responses::order_status(
+            0,
+            "Submitted",
+            dec!(0),
+            dec!(100),
+            0.0,
+        )],
+    );
+
+    let proxy = Proxy::start(ProxyOptions::new(server.addr()))
+        .await
+        .into_diagnostic()?;
+    let proxy_addr = proxy.local_addr().into_diagnostic()?;
+    tokio::spawn(proxy.run());
+
+    let contract = Contract::new_stock("AAPL", "SMART", "USD").into_diagnostic()?;
+    let request = ReqMktData::new(contract.clone(), HashSet::new(), false, false, vec![]);
+
+    let mut clients = Vec::new();
+    for client_id in [1, 2] {
+        let client = ib_tws_tokio::connect(proxy_addr, ConnectOptions::new(client_id)).await?;
+        clients.push((client_id, client));
+    }
+
+    // the second client joins while the first one is still subscribed, so they share one
+    // line upstream and the second client gets the latest ticks from the proxy
+    let mut subscriptions = Vec::new();
+    for (client_id, client) in &clients {
+        let mut subscription = client.request_market_data(request.clone()).await?;
+        let tick = subscription.next().await.transpose()?;
+        info!(client_id, ?tick, "market data");
+        subscriptions.push(subscription);
+    }
+    let subscriptions = server
+        .requests()
+        .iter()
+        .filter(|request| request.opcode() == REQ_MKT_DATA)
+        .count();
+    info!(subscriptions, "market data lines used upstream");
+
+    for (client_id, client) in &clients {
+        let order = Order {
+            action: "BUY".to_owned(),
+            total_quantity: dec!(100),
+            order_type: "MKT".to_owned(),
+            ..Order::default()
+        };
+        let update = client_order_update(client, contract.clone(), order).await?;
+        info!(client_id, ?update, "order update");
+    }
+    let order_ids: Vec<_> = server
+        .requests()
+        .iter()
+        .filter(|request| request.opcode() == PLACE_ORDER)
+        .filter_map(ib_tws_mock::RequestFrame::request_id)
+        .collect();
+    info!(?order_ids, "order ids used upstream");
+
+    Ok(())
+}
+
+async fn client_order_update(
+    client: &AsyncClient,
+    contract: Contract,
+    order: Order,
+) -> miette::Result<Option<Response>> {
+    let mut order = client.place_order(contract, order).await?;
+    Ok(order.next().await.transpose()?)
+}
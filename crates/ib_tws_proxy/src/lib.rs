@@ -0,0 +1,17 @@
+//! A proxy sharing one connection to TWS or the Gateway between many API clients.
+//!
+//! TWS accepts a limited number of client ids and counts every market data line against one
+//! limit for all of them. The proxy connects once, accepts any number of local API clients
+//! and maps their request and order ids into the id space of its own connection. Identical
+//! market data subscriptions of several clients share one line.
+
+#![warn(clippy::pedantic)]
+
+#[macro_use]
+extern crate tracing;
+
+mod proxy;
+pub use proxy::{Proxy, ProxyOptions};
+
+mod market_data;
+mod routes;
@@ -0,0 +1,50 @@
+use std::net::SocketAddr;
+
+use ib_tws_core::ConnectOptions;
+use ib_tws_proxy::{Proxy, ProxyOptions};
+use miette::{miette, IntoDiagnostic};
+
+const USAGE: &str = "\
+usage: ib_tws_proxy [--upstream ADDR] [--listen ADDR] [--client-id ID] [--connect-options OPTIONS]
+
+  --upstream ADDR              the API port of TWS or the Gateway [default: 127.0.0.1:4001]
+  --listen ADDR                the address the API clients connect to [default: 127.0.0.1:4100]
+  --client-id ID               the client id of the connection to TWS [default: 0]
+  --connect-options OPTIONS    handshake options, e.g. +PACEAPI";
+
+#[tokio::main]
+async fn main() -> miette::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut upstream_addr: SocketAddr = "127.0.0.1:4001".parse().into_diagnostic()?;
+    let mut listen_addr: SocketAddr = "127.0.0.1:4100".parse().into_diagnostic()?;
+    let mut connect_options = ConnectOptions::new(0);
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| miette!("{arg} needs a value\n\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--upstream" => upstream_addr = value()?.parse().into_diagnostic()?,
+            "--listen" => listen_addr = value()?.parse().into_diagnostic()?,
+            "--client-id" => connect_options.client_id = value()?.parse().into_diagnostic()?,
+            "--connect-options" => connect_options = connect_options.connect_options(value()?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(miette!("unknown argument {arg}\n\n{USAGE}")),
+        }
+    }
+
+    let proxy = Proxy::start(
+        ProxyOptions::new(upstream_addr)
+            .listen_addr(listen_addr)
+            .connect_options(connect_options),
+    )
+    .await
+    .into_diagnostic()?;
+    proxy.run().await.into_diagnostic()
+}
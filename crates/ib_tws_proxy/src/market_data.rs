@@ -0,0 +1,118 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem::{discriminant, Discriminant},
+};
+
+use ib_tws_core::message::{context::Context, request::ReqMktData, Request, Response};
+use rust_decimal::prelude::ToPrimitive;
+
+/// The streaming market data requests sent upstream, shared by every client requesting the
+/// same ticks of the same contract.
+///
+/// The latest value of every tick type is kept, for the clients joining a subscription after
+/// TWS sent the ticks which don't change often, e.g. the close price.
+#[derive(Debug, Default)]
+pub(crate) struct MarketData {
+    upstream_ids: HashMap<Vec<u8>, i32>,
+    subscriptions: HashMap<i32, Subscription>,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    key: Vec<u8>,
+    /// In the order TWS first sent them.
+    ticks: Vec<((Discriminant<Response>, i32), Response)>,
+}
+
+impl MarketData {
+    /// What makes two requests the same subscription: the request as encoded for TWS,
+    /// without its request id. `None` for snapshots, which aren't shared.
+    pub fn key(ctx: &mut Context, request: &Request) -> Option<Vec<u8>> {
+        let Request::ReqMktData(msg) = request else {
+            return None;
+        };
+        if msg.snapshot || msg.regulatory_snapshot {
+            return None;
+        }
+
+        // the generic ticks are a set, encoded in no particular order
+        let mut generic_ticks: Vec<_> = msg
+            .generic_tick_list
+            .iter()
+            .map(|tick| *tick as i32)
+            .collect();
+        generic_ticks.sort_unstable();
+        let request = Request::ReqMktData(ReqMktData {
+            req_id: 0,
+            generic_tick_list: HashSet::new(),
+            ..msg.clone()
+        });
+        let mut key = ctx.encode_message(&request).ok()?.to_vec();
+        for tick in generic_ticks {
+            key.extend_from_slice(&tick.to_be_bytes());
+        }
+        Some(key)
+    }
+
+    pub fn upstream_id(&self, key: &[u8]) -> Option<i32> {
+        self.upstream_ids.get(key).copied()
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, upstream_id: i32) {
+        self.upstream_ids.insert(key.clone(), upstream_id);
+        self.subscriptions.insert(
+            upstream_id,
+            Subscription {
+                key,
+                ticks: Vec::new(),
+            },
+        );
+    }
+
+    /// Keep the latest value of a tick type.
+    pub fn record(&mut self, upstream_id: i32, response: &Response) {
+        let Some(subscription) = self.subscriptions.get_mut(&upstream_id) else {
+            return;
+        };
+        let tick_type = match response {
+            Response::TickPriceMsg(msg) => msg.tick_type.clone() as i32,
+            Response::TickSizeMsg(msg) => msg.tick_type.clone() as i32,
+            Response::TickStringMsg(msg) => msg.tick_type.clone() as i32,
+            Response::TickGenericMsg(msg) => msg.tick_type.clone() as i32,
+            Response::TickOptionComputationMsg(msg) => msg.tick_type.clone() as i32,
+            Response::TickEFPMsg(msg) => msg.tick_type.to_i32().unwrap_or_default(),
+            Response::MarketDataTypeMsg(_) | Response::TickReqParamsMsg(_) => 0,
+            _ => return,
+        };
+        let tick_key = (discriminant(response), tick_type);
+        match subscription
+            .ticks
+            .iter_mut()
+            .find(|(key, _)| *key == tick_key)
+        {
+            Some((_, tick)) => *tick = response.clone(),
+            None => subscription.ticks.push((tick_key, response.clone())),
+        }
+    }
+
+    /// The latest ticks of a subscription, still with the upstream id.
+    pub fn ticks(&self, upstream_id: i32) -> Vec<Response> {
+        self.subscriptions
+            .get(&upstream_id)
+            .map(|subscription| {
+                subscription
+                    .ticks
+                    .iter()
+                    .map(|(_, tick)| tick.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Stop sharing a subscription, once it was cancelled or ended by TWS.
+    pub fn remove(&mut self, upstream_id: i32) {
+        if let Some(subscription) = self.subscriptions.remove(&upstream_id) {
+            self.upstream_ids.remove(&subscription.key);
+        }
+    }
+}
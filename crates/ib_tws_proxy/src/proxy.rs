@@ -0,0 +1,762 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use ib_tws_core::{
+    message::{
+        constants::MIN_VERSION,
+        context::Context,
+        request::{Handshake, PlaceOrder, StartApi},
+        response::{
+            ErrMsgMsg, ExecutionDataMsg, HandshakeAck, ManagedAcctsMsg, NextValidIdMsg,
+            OpenOrderMsg, OrderStatusMsg,
+        },
+        Request, Response,
+    },
+    ConnectOptions,
+};
+use ib_tws_tokio::{ServerCodec, Transport};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_util::codec::Framed;
+
+use crate::{
+    market_data::MarketData,
+    routes::{
+        cancel_request, is_cancel, is_last, is_update, reply_opcode, ClientId, Route, Routes,
+    },
+};
+
+/// The order id each client starts with, the ids of every client are mapped separately.
+const FIRST_ORDER_ID: i32 = 1;
+
+/// TWS's error for cancelling an order it doesn't know.
+const ORDER_NOT_FOUND: i32 = 135;
+
+/// Options of a [`Proxy`].
+///
+/// ```
+/// # use ib_tws_core::ConnectOptions;
+/// # use ib_tws_proxy::ProxyOptions;
+/// let options = ProxyOptions::new("127.0.0.1:4001".parse().unwrap())
+///     .listen_addr("127.0.0.1:4100".parse().unwrap())
+///     .connect_options(ConnectOptions::new(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProxyOptions {
+    /// The API port of TWS or the Gateway.
+    pub upstream_addr: SocketAddr,
+    /// The address the clients connect to, by default a free port on localhost.
+    pub listen_addr: SocketAddr,
+    /// The client id and handshake of the connection to TWS.
+    pub connect_options: ConnectOptions,
+}
+
+impl ProxyOptions {
+    #[must_use]
+    pub fn new(upstream_addr: SocketAddr) -> Self {
+        ProxyOptions {
+            upstream_addr,
+            listen_addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            connect_options: ConnectOptions::new(0),
+        }
+    }
+
+    #[must_use]
+    pub fn listen_addr(mut self, listen_addr: SocketAddr) -> Self {
+        self.listen_addr = listen_addr;
+        self
+    }
+
+    #[must_use]
+    pub fn connect_options(mut self, connect_options: ConnectOptions) -> Self {
+        self.connect_options = connect_options;
+        self
+    }
+}
+
+/// Shares one connection to TWS between any number of API clients.
+///
+/// The clients get their own handshake, managed accounts and next valid id from the proxy.
+/// Their request and order ids are mapped to the ids of the upstream connection and back,
+/// so they can use any ids they like. Streaming market data requests for the same contract
+/// and ticks share one upstream subscription, which is cancelled once the last client
+/// cancelled it or disconnected.
+///
+/// Replies to requests without an id, e.g. positions or account updates, are sent to every
+/// client, except the replies keyed by the opcode of their request, like the current time,
+/// which go to the clients in the order they asked. Updates of orders not placed through
+/// the proxy are only sent for orders placed in TWS itself, with the order id 0.
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// use ib_tws_proxy::{Proxy, ProxyOptions};
+///
+/// let proxy = Proxy::start(ProxyOptions::new("127.0.0.1:4001".parse().unwrap())).await?;
+/// println!("listening on {}", proxy.local_addr()?);
+/// proxy.run().await
+/// # }
+/// ```
+pub struct Proxy {
+    listener: TcpListener,
+    upstream: Transport,
+    ack: HandshakeAck,
+    state: State,
+}
+
+impl Proxy {
+    /// Connect to TWS, start the API and listen for clients.
+    /// # Errors
+    /// Returns an error if the connection or the handshake with TWS fails, TWS redirects to
+    /// another address, or the socket can't be bound.
+    pub async fn start(options: ProxyOptions) -> io::Result<Self> {
+        let connect_options = &options.connect_options;
+        let mut upstream =
+            Transport::connect(options.upstream_addr, connect_options.connect_timeout).await?;
+        upstream.set_tolerant(true);
+        let (ack, accounts, next_valid_id) = tokio::time::timeout(
+            connect_options.connect_timeout,
+            establish(&mut upstream, connect_options),
+        )
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "handshake timeout"))??;
+        info!(
+            server_version = ack.server_version,
+            %accounts,
+            next_valid_id,
+            "connected to TWS"
+        );
+
+        let listener = TcpListener::bind(options.listen_addr).await?;
+        info!(addr = %listener.local_addr()?, "proxy listening");
+
+        let mut ctx = Context::new();
+        ctx.set_server_version(ack.server_version);
+        Ok(Proxy {
+            listener,
+            upstream,
+            ack,
+            state: State {
+                ctx,
+                accounts,
+                clients: HashMap::new(),
+                routes: Routes::new(next_valid_id),
+                market_data: MarketData::default(),
+                pending_replies: HashMap::new(),
+                execution_clients: HashMap::new(),
+            },
+        })
+    }
+
+    /// The address the clients connect to.
+    /// # Errors
+    /// Returns an error if the address of the socket can't be read.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    #[must_use]
+    pub fn server_version(&self) -> i32 {
+        self.ack.server_version
+    }
+
+    /// Serve the clients until the connection to TWS is lost, the clients are disconnected
+    /// then.
+    /// # Errors
+    /// Returns the error which closed the connection to TWS.
+    pub async fn run(self) -> io::Result<()> {
+        let Proxy {
+            listener,
+            mut upstream,
+            ack,
+            mut state,
+        } = self;
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let mut next_client: ClientId = 0;
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        next_client += 1;
+                        let client = next_client;
+                        debug!(%peer, client, "client connected");
+                        let ack = ack.clone();
+                        let events = events_tx.clone();
+                        tokio::spawn(async move {
+                            match serve(stream, client, ack, events).await {
+                                Ok(()) => debug!(%peer, client, "client disconnected"),
+                                Err(error) => warn!(%peer, client, %error, "client connection failed"),
+                            }
+                        });
+                    }
+                    Err(error) => warn!(%error, "failed to accept client"),
+                },
+                response = upstream.next() => match response {
+                    Some(response) => state.dispatch(response?),
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "connection to TWS closed",
+                        ))
+                    }
+                },
+                Some(event) = events_rx.recv() => {
+                    for request in state.handle(event) {
+                        trace!(?request, "sending request to TWS");
+                        upstream.feed(request).await?;
+                    }
+                    upstream.flush().await?;
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Proxy")
+            .field("listener", &self.listener)
+            .field("ack", &self.ack)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Perform the handshake and start the API, like a client does. Returns the handshake ack,
+/// the managed accounts and the next valid id.
+async fn establish(
+    upstream: &mut Transport,
+    options: &ConnectOptions,
+) -> io::Result<(HandshakeAck, String, i32)> {
+    upstream
+        .send(Request::Handshake(Handshake {
+            min_version: options.min_version,
+            max_version: options.max_version,
+            option: options.connect_options.clone(),
+        }))
+        .await?;
+    let ack = loop {
+        match upstream.try_next().await? {
+            Some(Response::HandshakeAck(ack)) => break ack,
+            Some(response) => warn!(?response, "unexpected response before handshake ack"),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    };
+    if let Some(address) = ack.redirect {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("redirected to {address}, connect to it instead"),
+        ));
+    }
+
+    upstream
+        .send(Request::StartApi(StartApi {
+            client_id: options.client_id,
+            optional_capabilities: options.optional_capabilities.clone(),
+        }))
+        .await?;
+    let (mut accounts, mut next_valid_id) = (None, None);
+    while accounts.is_none() || next_valid_id.is_none() {
+        match upstream.try_next().await? {
+            Some(Response::ManagedAcctsMsg(msg)) => accounts = Some(msg.accounts),
+            Some(Response::NextValidIdMsg(msg)) => next_valid_id = Some(msg.order_id),
+            Some(response) => debug!(?response, "response before the API started"),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    Ok((
+        ack,
+        accounts.unwrap_or_default(),
+        next_valid_id.unwrap_or_default(),
+    ))
+}
+
+enum Event {
+    Connected {
+        client: ClientId,
+        client_id: i32,
+        tx: mpsc::UnboundedSender<Response>,
+    },
+    Request(ClientId, Box<Request>),
+    Disconnected(ClientId),
+}
+
+/// Speak the API protocol with a client: perform the handshake, wait for `StartApi` and
+/// then pass the requests to the proxy and its responses back.
+async fn serve(
+    stream: TcpStream,
+    client: ClientId,
+    mut ack: HandshakeAck,
+    events: mpsc::UnboundedSender<Event>,
+) -> io::Result<()> {
+    let mut framed = Framed::new(stream, ServerCodec::default());
+
+    let Some(Request::Handshake(handshake)) = framed.try_next().await? else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected the handshake",
+        ));
+    };
+    // older clients are served with their version, the requests are encoded for TWS anew
+    ack.server_version = ack.server_version.min(handshake.max_version);
+    if ack.server_version < handshake.min_version.max(MIN_VERSION) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "client needs at least server version {}",
+                handshake.min_version
+            ),
+        ));
+    }
+    debug!(client, server_version = ack.server_version, "handshake");
+    framed.send(Response::HandshakeAck(ack)).await?;
+
+    let Some(Request::StartApi(start_api)) = framed.try_next().await? else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected the start API request",
+        ));
+    };
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let connected = Event::Connected {
+        client,
+        client_id: start_api.client_id,
+        tx,
+    };
+    if events.send(connected).is_err() {
+        // the proxy stopped
+        return Ok(());
+    }
+
+    let result = async {
+        loop {
+            tokio::select! {
+                request = framed.try_next() => match request? {
+                    Some(request) => {
+                        if events.send(Event::Request(client, Box::new(request))).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    None => return Ok(()),
+                },
+                response = rx.recv() => match response {
+                    Some(response) => {
+                        framed.feed(response).await?;
+                        while let Ok(response) = rx.try_recv() {
+                            framed.feed(response).await?;
+                        }
+                        SinkExt::<Response>::flush(&mut framed).await?;
+                    }
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+    .await;
+
+    let _ = events.send(Event::Disconnected(client));
+    result
+}
+
+struct Connection {
+    tx: mpsc::UnboundedSender<Response>,
+    /// The client id the client sent with `StartApi`, reported in its orders and executions.
+    client_id: i32,
+    next_order_id: i32,
+}
+
+/// The clients and the ids they use, only touched by the task running the proxy.
+struct State {
+    /// Encodes market data requests like the upstream connection, to compare them.
+    ctx: Context,
+    accounts: String,
+    clients: HashMap<ClientId, Connection>,
+    routes: Routes,
+    market_data: MarketData,
+    /// The clients waiting for a reply keyed by the opcode of their request.
+    pending_replies: HashMap<i32, VecDeque<ClientId>>,
+    /// The clients of the executions whose commission report is yet to come.
+    execution_clients: HashMap<String, ClientId>,
+}
+
+impl State {
+    /// Handle an event of a client. Returns the requests to send to TWS.
+    fn handle(&mut self, event: Event) -> Vec<Request> {
+        match event {
+            Event::Connected {
+                client,
+                client_id,
+                tx,
+            } => {
+                info!(client, client_id, "client started the API");
+                self.clients.insert(
+                    client,
+                    Connection {
+                        tx,
+                        client_id,
+                        next_order_id: FIRST_ORDER_ID,
+                    },
+                );
+                self.send(
+                    client,
+                    Response::ManagedAcctsMsg(ManagedAcctsMsg {
+                        accounts: self.accounts.clone(),
+                    }),
+                );
+                self.send(
+                    client,
+                    Response::NextValidIdMsg(NextValidIdMsg {
+                        order_id: FIRST_ORDER_ID,
+                    }),
+                );
+                Vec::new()
+            }
+            Event::Request(client, request) => self.request(client, *request),
+            Event::Disconnected(client) => self.disconnect(client),
+        }
+    }
+
+    fn request(&mut self, client: ClientId, mut request: Request) -> Vec<Request> {
+        trace!(client, ?request, "request from client");
+        match &mut request {
+            Request::Handshake(_) | Request::StartApi(_) => {
+                warn!(client, ?request, "ignoring a repeated handshake");
+                return Vec::new();
+            }
+            Request::ReqIds(_) => {
+                if let Some(next_order_id) = self.clients.get(&client).map(|c| c.next_order_id) {
+                    self.send(
+                        client,
+                        Response::NextValidIdMsg(NextValidIdMsg {
+                            order_id: next_order_id,
+                        }),
+                    );
+                }
+                return Vec::new();
+            }
+            Request::ReqManagedAccts(_) => {
+                self.send(
+                    client,
+                    Response::ManagedAcctsMsg(ManagedAcctsMsg {
+                        accounts: self.accounts.clone(),
+                    }),
+                );
+                return Vec::new();
+            }
+            Request::PlaceOrder(msg) => self.place_order(client, msg),
+            Request::CancelOrder(msg) => {
+                let Some(upstream_id) = self.routes.upstream_id(client, msg.id) else {
+                    self.send(
+                        client,
+                        Response::ErrMsgMsg(ErrMsgMsg {
+                            id: msg.id,
+                            error_code: ORDER_NOT_FOUND,
+                            error_message: format!("Can't find order with id ={}", msg.id),
+                            advanced_order_reject_json: String::new(),
+                            advanced_order_reject: None,
+                        }),
+                    );
+                    return Vec::new();
+                };
+                msg.id = upstream_id;
+            }
+            Request::ReqGlobalCancel(_) => {
+                warn!(client, "cancelling the orders of all clients");
+            }
+            _ => {}
+        }
+
+        if let Some(key) = MarketData::key(&mut self.ctx, &request) {
+            return self.subscribe_market_data(client, request, key);
+        }
+        if let Some(opcode) = reply_opcode(&request) {
+            self.pending_replies
+                .entry(opcode)
+                .or_default()
+                .push_back(client);
+        }
+
+        let cancel = is_cancel(&request);
+        let update = is_update(&request);
+        let Some(id) = request.request_id_mut() else {
+            return vec![request];
+        };
+        if cancel {
+            let Some((upstream_id, unused)) = self.routes.release(client, *id) else {
+                debug!(client, id, "cancelling an unknown request id");
+                return Vec::new();
+            };
+            if !unused {
+                return Vec::new();
+            }
+            self.market_data.remove(upstream_id);
+            *id = upstream_id;
+        } else if update {
+            let Some(upstream_id) = self.routes.upstream_id(client, *id) else {
+                debug!(client, id, "updating an unknown request id");
+                return Vec::new();
+            };
+            *id = upstream_id;
+        } else {
+            let upstream_id = self.routes.map(client, *id);
+            *id = upstream_id;
+            if let Some(cancel) = cancel_request(&request, upstream_id) {
+                self.routes.set_cancel(upstream_id, cancel);
+            }
+        }
+        vec![request]
+    }
+
+    fn place_order(&mut self, client: ClientId, msg: &mut PlaceOrder) {
+        if let Some(state) = self.clients.get_mut(&client) {
+            state.next_order_id = state.next_order_id.max(msg.id + 1);
+        }
+        msg.id = self.routes.map(client, msg.id);
+        if msg.order.parent_id != 0 {
+            if let Some(parent_id) = self.routes.upstream_id(client, msg.order.parent_id) {
+                msg.order.parent_id = parent_id;
+            } else {
+                warn!(
+                    client,
+                    parent_id = msg.order.parent_id,
+                    "unknown parent order"
+                );
+            }
+        }
+    }
+
+    /// Share the subscription of another client requesting the same market data, sending
+    /// the latest ticks to the joining client, or subscribe upstream.
+    fn subscribe_market_data(
+        &mut self,
+        client: ClientId,
+        mut request: Request,
+        key: Vec<u8>,
+    ) -> Vec<Request> {
+        let Request::ReqMktData(msg) = &mut request else {
+            return vec![request];
+        };
+
+        if let Some(upstream_id) = self.market_data.upstream_id(&key) {
+            if self.routes.upstream_id(client, msg.req_id).is_none() {
+                debug!(
+                    client,
+                    req_id = msg.req_id,
+                    upstream_id,
+                    "sharing market data"
+                );
+                self.routes.join(
+                    upstream_id,
+                    Route {
+                        client,
+                        id: msg.req_id,
+                    },
+                );
+                for mut tick in self.market_data.ticks(upstream_id) {
+                    if let Some(id) = tick.request_id_mut() {
+                        *id = msg.req_id;
+                    }
+                    self.send(client, tick);
+                }
+                return Vec::new();
+            }
+        }
+
+        let upstream_id = self.routes.map(client, msg.req_id);
+        msg.req_id = upstream_id;
+        if let Some(cancel) = cancel_request(&request, upstream_id) {
+            self.routes.set_cancel(upstream_id, cancel);
+        }
+        self.market_data.insert(key, upstream_id);
+        vec![request]
+    }
+
+    /// Forget a disconnected client. Returns the requests cancelling the subscriptions no
+    /// other client uses.
+    fn disconnect(&mut self, client: ClientId) -> Vec<Request> {
+        self.clients.remove(&client);
+        self.execution_clients
+            .retain(|_, execution_client| *execution_client != client);
+
+        let mut cancels = Vec::new();
+        for (upstream_id, cancel) in self.routes.release_client(client) {
+            self.market_data.remove(upstream_id);
+            cancels.extend(cancel);
+        }
+        info!(client, cancelled = cancels.len(), "client disconnected");
+        cancels
+    }
+
+    /// Pass a response from TWS to the clients it is meant for.
+    fn dispatch(&mut self, response: Response) {
+        trace!(?response, "response from TWS");
+        match response {
+            Response::HandshakeAck(_) => {}
+            Response::ManagedAcctsMsg(msg) => self.accounts = msg.accounts,
+            Response::NextValidIdMsg(msg) => self.routes.advance(msg.order_id),
+            Response::UnknownMsg(msg) => debug!(msg.msg_id, "dropping an undecodable message"),
+            Response::OrderStatusMsg(msg) => self.order_status(msg),
+            Response::OpenOrderMsg(msg) => self.open_order(msg),
+            Response::ExecutionDataMsg(msg) => self.execution(msg),
+            Response::CommissionReportMsg(msg) => {
+                match self.execution_clients.remove(&msg.report.exec_id) {
+                    Some(client) => self.send(client, Response::CommissionReportMsg(msg)),
+                    None => self.broadcast(&Response::CommissionReportMsg(msg)),
+                }
+            }
+            response if response.is_global() => {
+                let client = response.request_id().and_then(|opcode| {
+                    self.pending_replies
+                        .get_mut(&opcode)
+                        .and_then(VecDeque::pop_front)
+                });
+                match client {
+                    Some(client) => self.send(client, response),
+                    None => self.broadcast(&response),
+                }
+            }
+            response => self.route(response),
+        }
+    }
+
+    fn route(&mut self, mut response: Response) {
+        let Some(upstream_id) = response.request_id_mut().map(|id| *id) else {
+            self.broadcast(&response);
+            return;
+        };
+
+        self.market_data.record(upstream_id, &response);
+        let failed = matches!(&response, Response::ErrMsgMsg(err) if !err.is_warning());
+        if failed {
+            // TWS ended the subscription, later requests subscribe anew
+            self.market_data.remove(upstream_id);
+        }
+        // an error is the only reply to a failed request, subscriptions are still cancelled
+        // by their clients
+        let last = is_last(&response)
+            || (failed || matches!(response, Response::HistoricalDataMsg(_)))
+                && !self.routes.is_subscription(upstream_id);
+
+        let routes = self.routes.routes(upstream_id).to_vec();
+        if routes.is_empty() {
+            debug!(
+                upstream_id,
+                ?response,
+                "dropping a response to an unknown id"
+            );
+        }
+        for route in routes {
+            let mut response = response.clone();
+            if let Some(id) = response.request_id_mut() {
+                *id = route.id;
+            }
+            self.send(route.client, response);
+        }
+        if last {
+            self.routes.finish(upstream_id);
+        }
+    }
+
+    fn order_status(&mut self, mut msg: OrderStatusMsg) {
+        let Some(route) = self.routes.owner(msg.id) else {
+            self.unknown_order(msg.id, &Response::OrderStatusMsg(msg));
+            return;
+        };
+        msg.id = route.id;
+        msg.parent_id = self.client_order_id(route.client, msg.parent_id);
+        msg.client_id = self.client_id(route.client);
+        self.send(route.client, Response::OrderStatusMsg(msg));
+    }
+
+    fn open_order(&mut self, mut msg: OpenOrderMsg) {
+        let Some(route) = self.routes.owner(msg.order_id) else {
+            self.unknown_order(msg.order_id, &Response::OpenOrderMsg(msg));
+            return;
+        };
+        msg.order_id = route.id;
+        msg.order.order_id = route.id;
+        msg.order.parent_id = self.client_order_id(route.client, msg.order.parent_id);
+        msg.order.client_id = self.client_id(route.client);
+        self.send(route.client, Response::OpenOrderMsg(msg));
+    }
+
+    /// Executions are sent for orders as they fill, with the request id -1, and in reply to
+    /// `ReqExecutions`. A client only gets the executions of its own orders and of orders
+    /// not placed through the proxy.
+    fn execution(&mut self, mut msg: ExecutionDataMsg) {
+        let owner = self.routes.owner(msg.exec.order_id);
+        let client = if msg.req_id == -1 {
+            let Some(owner) = owner else {
+                self.unknown_order(msg.exec.order_id, &Response::ExecutionDataMsg(msg));
+                return;
+            };
+            owner.client
+        } else {
+            let Some(route) = self.routes.owner(msg.req_id) else {
+                debug!(
+                    req_id = msg.req_id,
+                    "dropping an execution to an unknown id"
+                );
+                return;
+            };
+            if owner.is_some_and(|owner| owner.client != route.client) {
+                return;
+            }
+            msg.req_id = route.id;
+            route.client
+        };
+
+        if let Some(owner) = owner {
+            msg.exec.order_id = owner.id;
+            msg.exec.client_id = self.client_id(owner.client);
+        }
+        self.execution_clients
+            .insert(msg.exec.exec_id.clone(), client);
+        self.send(client, Response::ExecutionDataMsg(msg));
+    }
+
+    /// Updates of orders placed in TWS itself have the order id 0 and go to every client,
+    /// the ones of other API clients are dropped, their ids would clash with the ids of the
+    /// clients of the proxy.
+    fn unknown_order(&self, order_id: i32, response: &Response) {
+        if order_id <= 0 {
+            self.broadcast(response);
+        } else {
+            debug!(
+                order_id,
+                "dropping an update of an order not placed through the proxy"
+            );
+        }
+    }
+
+    /// The id a client knows an order of its own by, other ids are left as they are.
+    fn client_order_id(&self, client: ClientId, order_id: i32) -> i32 {
+        match self.routes.owner(order_id) {
+            Some(route) if route.client == client => route.id,
+            _ => order_id,
+        }
+    }
+
+    fn client_id(&self, client: ClientId) -> i32 {
+        self.clients.get(&client).map_or(0, |c| c.client_id)
+    }
+
+    fn send(&self, client: ClientId, response: Response) {
+        if let Some(c) = self.clients.get(&client) {
+            // a client disconnecting is handled once its task ends
+            let _ = c.tx.send(response);
+        }
+    }
+
+    fn broadcast(&self, response: &Response) {
+        for c in self.clients.values() {
+            let _ = c.tx.send(response.clone());
+        }
+    }
+}
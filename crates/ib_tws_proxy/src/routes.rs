@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use ib_tws_core::message::{
+    constants::{
+        OPCODE_REQUEST_FA, OPCODE_REQ_CURRENT_TIME, OPCODE_REQ_FAMILY_CODES,
+        OPCODE_REQ_MARKET_RULE, OPCODE_REQ_MKT_DEPTH_EXCHANGES, OPCODE_REQ_NEWS_PROVIDERS,
+        OPCODE_REQ_SCANNER_PARAMETERS,
+    },
+    request::{
+        CancelAccountSummary, CancelAccountUpdatesMulti, CancelHistoricalData, CancelMktData,
+        CancelMktDepth, CancelPnl, CancelPnlSingle, CancelPositionsMulti, CancelRealtimeBars,
+        CancelScannerSubscription, CancelTickByTickData, UbsubscribeFromGroupEvents,
+    },
+    Request, Response,
+};
+
+/// A client connected to the proxy, numbered in the order they connected.
+pub(crate) type ClientId = u64;
+
+/// The client a response to an upstream id goes to, and the id the client used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Route {
+    pub client: ClientId,
+    pub id: i32,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    routes: Vec<Route>,
+    cancel: Option<Request>,
+}
+
+/// The ids of the upstream connection, shared by the request and order ids of all clients
+/// like TWS shares them for a single client.
+///
+/// Ids are taken in increasing order from the next valid id of the upstream connection and
+/// never reused, so every id is a valid order id.
+#[derive(Debug)]
+pub(crate) struct Routes {
+    next_id: i32,
+    entries: HashMap<i32, Entry>,
+    upstream_ids: HashMap<(ClientId, i32), i32>,
+}
+
+impl Routes {
+    pub fn new(next_valid_id: i32) -> Self {
+        Routes {
+            next_id: next_valid_id,
+            entries: HashMap::new(),
+            upstream_ids: HashMap::new(),
+        }
+    }
+
+    /// Skip the ids below the next valid id reported by TWS.
+    pub fn advance(&mut self, next_valid_id: i32) {
+        self.next_id = self.next_id.max(next_valid_id);
+    }
+
+    pub fn upstream_id(&self, client: ClientId, id: i32) -> Option<i32> {
+        self.upstream_ids.get(&(client, id)).copied()
+    }
+
+    /// The upstream id for the id of a client, a new one unless the client used it before,
+    /// e.g. to modify an order.
+    pub fn map(&mut self, client: ClientId, id: i32) -> i32 {
+        if let Some(upstream_id) = self.upstream_id(client, id) {
+            return upstream_id;
+        }
+        let upstream_id = self.next_id;
+        self.next_id += 1;
+        self.join(upstream_id, Route { client, id });
+        upstream_id
+    }
+
+    /// Route the responses to an upstream id to another client as well.
+    pub fn join(&mut self, upstream_id: i32, route: Route) {
+        self.entries
+            .entry(upstream_id)
+            .or_default()
+            .routes
+            .push(route);
+        self.upstream_ids
+            .insert((route.client, route.id), upstream_id);
+    }
+
+    /// The request sent upstream once no client uses the id anymore.
+    pub fn set_cancel(&mut self, upstream_id: i32, cancel: Request) {
+        if let Some(entry) = self.entries.get_mut(&upstream_id) {
+            entry.cancel = Some(cancel);
+        }
+    }
+
+    /// Whether the id is a subscription, which ends only once cancelled.
+    pub fn is_subscription(&self, upstream_id: i32) -> bool {
+        self.entries
+            .get(&upstream_id)
+            .is_some_and(|entry| entry.cancel.is_some())
+    }
+
+    pub fn routes(&self, upstream_id: i32) -> &[Route] {
+        self.entries
+            .get(&upstream_id)
+            .map_or(&[], |entry| &entry.routes)
+    }
+
+    /// The route of an id to the client owning it, `None` for ids shared between clients.
+    pub fn owner(&self, upstream_id: i32) -> Option<Route> {
+        match self.routes(upstream_id) {
+            [route] => Some(*route),
+            _ => None,
+        }
+    }
+
+    /// Forget the id of a client. Returns the upstream id, and whether the id is unused now.
+    pub fn release(&mut self, client: ClientId, id: i32) -> Option<(i32, bool)> {
+        let upstream_id = self.upstream_ids.remove(&(client, id))?;
+        let entry = self.entries.get_mut(&upstream_id)?;
+        entry.routes.retain(|route| *route != Route { client, id });
+        let unused = entry.routes.is_empty();
+        if unused {
+            self.entries.remove(&upstream_id);
+        }
+        Some((upstream_id, unused))
+    }
+
+    /// Forget an upstream id which TWS won't send any more responses to.
+    pub fn finish(&mut self, upstream_id: i32) {
+        if let Some(entry) = self.entries.remove(&upstream_id) {
+            for route in entry.routes {
+                self.upstream_ids.remove(&(route.client, route.id));
+            }
+        }
+    }
+
+    /// Forget the ids of a disconnected client. Returns the ids no other client uses, with
+    /// the request cancelling them if they are subscriptions.
+    pub fn release_client(&mut self, client: ClientId) -> Vec<(i32, Option<Request>)> {
+        self.upstream_ids
+            .retain(|(route_client, _), _| *route_client != client);
+        let mut unused = Vec::new();
+        self.entries.retain(|upstream_id, entry| {
+            entry.routes.retain(|route| route.client != client);
+            if entry.routes.is_empty() {
+                unused.push((*upstream_id, entry.cancel.take()));
+                false
+            } else {
+                true
+            }
+        });
+        unused
+    }
+}
+
+/// Whether a request ends the request or subscription of its request id.
+pub(crate) fn is_cancel(request: &Request) -> bool {
+    matches!(
+        request,
+        Request::CancelScannerSubscription(_)
+            | Request::CancelHistoricalData(_)
+            | Request::CancelRealtimeBars(_)
+            | Request::CancelHeadTimestamp(_)
+            | Request::CancelMktData(_)
+            | Request::CancelMktDepth(_)
+            | Request::CancelFundamentalData(_)
+            | Request::CancelCalculateImpliedVolatility(_)
+            | Request::CancelCalculateOptionPrice(_)
+            | Request::CancelPositionsMulti(_)
+            | Request::CancelAccountUpdatesMulti(_)
+            | Request::CancelAccountSummary(_)
+            | Request::UnsubscribeFromGroupEvents(_)
+            | Request::CancelHistogramData(_)
+            | Request::CancelPnl(_)
+            | Request::CancelPnlSingle(_)
+            | Request::CancelTickByTickData(_)
+            | Request::CancelWshMetaData(_)
+            | Request::CancelWshEventData(_)
+    )
+}
+
+/// Whether a request reuses the id of an earlier one instead of starting a new request.
+pub(crate) fn is_update(request: &Request) -> bool {
+    matches!(request, Request::UpdateDisplayGroup(_))
+}
+
+/// The request cancelling a subscription, for the subscriptions of a client disconnecting
+/// without cancelling them. `req_id` is the upstream id.
+pub(crate) fn cancel_request(request: &Request, req_id: i32) -> Option<Request> {
+    let cancel = match request {
+        Request::ReqMktData(msg) if !msg.snapshot && !msg.regulatory_snapshot => {
+            Request::CancelMktData(CancelMktData { req_id })
+        }
+        Request::ReqMktDepth(msg) => Request::CancelMktDepth(CancelMktDepth {
+            req_id,
+            is_smart_depth: msg.is_smart_depth,
+        }),
+        Request::ReqRealtimeBars(_) => Request::CancelRealtimeBars(CancelRealtimeBars { req_id }),
+        Request::ReqHistoricalData(msg) if msg.keepup_to_date => {
+            Request::CancelHistoricalData(CancelHistoricalData { req_id })
+        }
+        Request::ReqTickByTickData(_) => {
+            Request::CancelTickByTickData(CancelTickByTickData { req_id })
+        }
+        Request::ReqScannerSubscription(_) => {
+            Request::CancelScannerSubscription(CancelScannerSubscription { req_id })
+        }
+        Request::ReqAccountSummary(_) => {
+            Request::CancelAccountSummary(CancelAccountSummary { req_id })
+        }
+        Request::ReqPositionsMulti(_) => {
+            Request::CancelPositionsMulti(CancelPositionsMulti { req_id })
+        }
+        Request::ReqAccountUpdatesMulti(_) => {
+            Request::CancelAccountUpdatesMulti(CancelAccountUpdatesMulti { req_id })
+        }
+        Request::ReqPnl(_) => Request::CancelPnl(CancelPnl { req_id }),
+        Request::ReqPnlSingle(_) => Request::CancelPnlSingle(CancelPnlSingle { req_id }),
+        Request::SubscribeToGroupEvents(_) => {
+            Request::UnsubscribeFromGroupEvents(UbsubscribeFromGroupEvents { req_id })
+        }
+        _ => return None,
+    };
+    Some(cancel)
+}
+
+/// Whether a response is the last one TWS sends to its request id.
+pub(crate) fn is_last(response: &Response) -> bool {
+    match response {
+        Response::HistoricalTicksMsg(msg) => msg.done,
+        Response::HistoricalTickLastMsg(msg) => msg.done,
+        Response::HistoricalTickBidAskMsg(msg) => msg.done,
+        Response::ContractDataEndMsg(_)
+        | Response::TickSnapshotEndMsg(_)
+        | Response::ExecutionDataEndMsg(_)
+        | Response::SecurityDefinitionOptionalParameterEndMsg(_)
+        | Response::HistoricalNewsEndMsg(_)
+        | Response::SymbolSamplesMsg(_)
+        | Response::HeadTimestampMsg(_)
+        | Response::FundamentalDataMsg(_)
+        | Response::SoftDollarTiersMsg(_)
+        | Response::SmartComponentsMsg(_)
+        | Response::NewsArticleMsg(_)
+        | Response::HistogramDataMsg(_)
+        | Response::ReplaceFaEndMsg(_)
+        | Response::HistoricalScheduleMsg(_)
+        | Response::UserInfoMsg(_) => true,
+        _ => false,
+    }
+}
+
+/// The opcode a reply to a request without an id is keyed by, see `Response::is_global`.
+pub(crate) fn reply_opcode(request: &Request) -> Option<i32> {
+    match request {
+        Request::ReqCurrentTime(_) => Some(OPCODE_REQ_CURRENT_TIME),
+        Request::ReqScannerParameters(_) => Some(OPCODE_REQ_SCANNER_PARAMETERS),
+        Request::RequestFA(_) => Some(OPCODE_REQUEST_FA),
+        Request::ReqFamilyCodes(_) => Some(OPCODE_REQ_FAMILY_CODES),
+        Request::ReqMktDepthExchanges(_) => Some(OPCODE_REQ_MKT_DEPTH_EXCHANGES),
+        Request::ReqNewsProvider(_) => Some(OPCODE_REQ_NEWS_PROVIDERS),
+        Request::ReqMarketRule(_) => Some(OPCODE_REQ_MARKET_RULE),
+        _ => None,
+    }
+}
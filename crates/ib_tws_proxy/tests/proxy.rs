@@ -0,0 +1,243 @@
+//! Several clients sharing the connection of the proxy to the mock server.
+
+use std::{collections::HashSet, future::Future, net::SocketAddr, time::Duration};
+
+use futures::StreamExt;
+use ib_tws_core::{
+    domain::{market_data::TickType, Contract, ContractDetails, Order},
+    message::{
+        constants::{CANCEL_MKT_DATA, CANCEL_ORDER, PLACE_ORDER, REQ_CONTRACT_DATA, REQ_MKT_DATA},
+        request::{CacelOrder, ReqContractDetails, ReqMktData},
+        Request, Response,
+    },
+    AsyncClient, ConnectOptions,
+};
+use ib_tws_mock::{responses, MockOptions, MockServer};
+use ib_tws_proxy::{Proxy, ProxyOptions};
+use rust_decimal::Decimal;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the proxy starts counting the ids it sends to TWS, far from the ids of the clients.
+const NEXT_VALID_ID: i32 = 500;
+
+/// Start the mock server and a proxy connected to it, returns the address of the proxy.
+async fn start() -> (MockServer, SocketAddr) {
+    let server = MockServer::start_with_options(MockOptions::new().next_valid_id(NEXT_VALID_ID))
+        .await
+        .unwrap();
+    // the details of the contract asked for, telling the replies to each client apart
+    server.on(REQ_CONTRACT_DATA, |request| {
+        let Request::ReqContractDetails(msg) = request.request() else {
+            unreachable!();
+        };
+        let req_id = request.request_id().unwrap();
+        let details = ContractDetails {
+            contract: msg.contract.clone(),
+            ..ContractDetails::default()
+        };
+        vec![
+            responses::contract_data(req_id, details),
+            responses::contract_data_end(req_id),
+        ]
+    });
+    let proxy = Proxy::start(ProxyOptions::new(server.addr()))
+        .await
+        .unwrap();
+    let addr = proxy.local_addr().unwrap();
+    tokio::spawn(proxy.run());
+    (server, addr)
+}
+
+async fn connect(addr: SocketAddr, client_id: i32) -> AsyncClient {
+    ib_tws_tokio::connect(addr, ConnectOptions::new(client_id))
+        .await
+        .unwrap()
+}
+
+fn stock(symbol: &str) -> Contract {
+    Contract::new_stock(symbol, "SMART", "USD").unwrap()
+}
+
+fn market_data() -> ReqMktData {
+    ReqMktData::new(stock("AAPL"), HashSet::new(), false, false, vec![])
+}
+
+async fn timeout<F: Future>(future: F) -> F::Output {
+    tokio::time::timeout(TIMEOUT, future).await.unwrap()
+}
+
+/// The symbol of the details a client gets, once every request it sent before reached the
+/// mock server.
+async fn contract_details(client: &AsyncClient, symbol: &str) -> String {
+    let details = client
+        .request_contract_details(ReqContractDetails::new(stock(symbol)))
+        .await
+        .unwrap();
+    details.contract.symbol
+}
+
+fn received(server: &MockServer, opcode: i32) -> Vec<i32> {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.opcode() == opcode)
+        .filter_map(|request| request.request_id())
+        .collect()
+}
+
+#[tokio::test]
+async fn ids_are_mapped_in_both_directions() {
+    let (server, addr) = start().await;
+    let first = connect(addr, 1).await;
+    let second = connect(addr, 2).await;
+
+    let (aapl, msft) = timeout(async {
+        tokio::join!(
+            contract_details(&first, "AAPL"),
+            contract_details(&second, "MSFT")
+        )
+    })
+    .await;
+    assert_eq!(aapl, "AAPL");
+    assert_eq!(msft, "MSFT");
+    let upstream_ids = received(&server, REQ_CONTRACT_DATA);
+    assert_eq!(upstream_ids.len(), 2);
+    assert_ne!(upstream_ids[0], upstream_ids[1]);
+
+    server.reply(
+        PLACE_ORDER,
+        vec![responses::order_status(
+            0,
+            "Submitted",
+            Decimal::ZERO,
+            Decimal::ONE,
+            0.0,
+        )],
+    );
+    for client in [&first, &second] {
+        let mut order = client
+            .place_order(stock("AAPL"), Order::default())
+            .await
+            .unwrap();
+        // both clients start with the same order id
+        assert_eq!(order.order_id(), 1);
+        match timeout(order.next()).await {
+            Some(Ok(Response::OrderStatusMsg(msg))) => assert_eq!(msg.id, 1),
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+    let upstream_ids = received(&server, PLACE_ORDER);
+    assert_eq!(upstream_ids.len(), 2);
+    assert_ne!(upstream_ids[0], upstream_ids[1]);
+    assert!(upstream_ids.iter().all(|id| *id >= NEXT_VALID_ID));
+}
+
+#[tokio::test]
+async fn identical_market_data_is_shared() {
+    let (server, addr) = start().await;
+    server.reply(
+        REQ_MKT_DATA,
+        vec![responses::tick_price(
+            0,
+            TickType::CLOSE,
+            185.64,
+            Decimal::ZERO,
+        )],
+    );
+    let first = connect(addr, 1).await;
+    let second = connect(addr, 2).await;
+
+    let mut ticks = first.request_market_data(market_data()).await.unwrap();
+    let tick = timeout(ticks.next()).await;
+    assert!(
+        matches!(tick, Some(Ok(Response::TickPriceMsg(_)))),
+        "{tick:?}"
+    );
+
+    // the close price came before the second client joined
+    let mut joined = second.request_market_data(market_data()).await.unwrap();
+    match timeout(joined.next()).await {
+        Some(Ok(Response::TickPriceMsg(msg))) => {
+            assert!(matches!(msg.tick_type, TickType::CLOSE));
+            assert!((msg.price - 185.64).abs() < f64::EPSILON);
+        }
+        tick => panic!("unexpected tick {tick:?}"),
+    }
+    timeout(contract_details(&second, "AAPL")).await;
+    assert_eq!(received(&server, REQ_MKT_DATA).len(), 1);
+}
+
+#[tokio::test]
+async fn last_cancel_cancels_upstream() {
+    let (server, addr) = start().await;
+    server.reply(REQ_MKT_DATA, vec![]);
+    let first = connect(addr, 1).await;
+    let second = connect(addr, 2).await;
+
+    let ticks = first.request_market_data(market_data()).await.unwrap();
+    let joined = second.request_market_data(market_data()).await.unwrap();
+    timeout(contract_details(&second, "AAPL")).await;
+    drop(ticks);
+    timeout(contract_details(&first, "AAPL")).await;
+    assert!(received(&server, CANCEL_MKT_DATA).is_empty());
+
+    drop(joined);
+    let cancel = timeout(server.wait_for(CANCEL_MKT_DATA)).await;
+    assert_eq!(
+        cancel.request_id(),
+        received(&server, REQ_MKT_DATA).first().copied()
+    );
+}
+
+#[tokio::test]
+async fn last_disconnect_cancels_upstream() {
+    let (server, addr) = start().await;
+    server.reply(REQ_MKT_DATA, vec![]);
+    let first = connect(addr, 1).await;
+    let second = connect(addr, 2).await;
+
+    // without a subscription cancelling them when dropped
+    for client in [&first, &second] {
+        client
+            .send(Request::ReqMktData(market_data()))
+            .await
+            .unwrap();
+        timeout(contract_details(client, "AAPL")).await;
+    }
+    assert_eq!(received(&server, REQ_MKT_DATA).len(), 1);
+    drop(first);
+    timeout(contract_details(&second, "AAPL")).await;
+    assert!(received(&server, CANCEL_MKT_DATA).is_empty());
+
+    drop(second);
+    timeout(server.wait_for(CANCEL_MKT_DATA)).await;
+}
+
+#[tokio::test]
+async fn cancelling_an_unknown_order_fails() {
+    let (server, addr) = start().await;
+    let client = connect(addr, 1).await;
+
+    let mut responses = client.response_stream();
+    client
+        .send(Request::CancelOrder(CacelOrder {
+            id: 42,
+            manual_order_cancel_time: String::new(),
+        }))
+        .await
+        .unwrap();
+    let error = timeout(async {
+        loop {
+            if let Ok(Response::ErrMsgMsg(err)) = responses.recv().await {
+                break err;
+            }
+        }
+    })
+    .await;
+    assert_eq!(error.id, 42);
+    assert_eq!(error.error_code, 135);
+
+    timeout(contract_details(&client, "AAPL")).await;
+    assert!(received(&server, CANCEL_ORDER).is_empty());
+}
@@ -0,0 +1,38 @@
+use std::io;
+
+use asynchronous_codec::{Decoder, Encoder};
+use bytes::BytesMut;
+use ib_tws_core::message::{context::Context, message_codec, Request, Response};
+
+/// The [`message_codec::Codec`] of the core crate, for `asynchronous_codec::Framed`.
+#[derive(Debug, Default)]
+pub struct Codec(message_codec::Codec);
+
+impl Codec {
+    #[must_use]
+    pub fn context(&self) -> &Context {
+        self.0.context()
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        self.0.context_mut()
+    }
+}
+
+impl Decoder for Codec {
+    type Item = Response;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.0.decode(src)
+    }
+}
+
+impl Encoder for Codec {
+    type Item<'a> = Request;
+    type Error = io::Error;
+
+    fn encode(&mut self, request: Request, buf: &mut BytesMut) -> Result<(), Self::Error> {
+        self.0.encode(&request, buf)
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use asynchronous_codec::Framed;
+use futures::{
+    channel::oneshot, future, AsyncRead, AsyncWrite, AsyncWriteExt, Future, FutureExt, Sink,
+    SinkExt, Stream, StreamExt,
+};
+use ib_tws_core::{
+    message::{Request, Response},
+    SpawnTask,
+};
+use smol::{net::TcpStream, Timer};
+
+use crate::Codec;
+
+/// The API protocol over a stream to TWS, a TCP socket unless set otherwise, e.g.
+/// `smol::net::unix::UnixStream` or an `async-std` stream.
+pub struct Transport<S = TcpStream> {
+    framed_stream: Framed<S, Codec>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Transport<S> {
+    /// Start the API protocol on a connected stream, by writing the `API\0` prefix.
+    /// # Errors
+    /// Returns a `std::io::Error` if the prefix can't be written.
+    pub async fn new(mut stream: S) -> Result<Transport<S>, io::Error> {
+        stream.write_all(b"API\0").await?;
+        stream.flush().await?;
+
+        let framed_stream = Framed::new(stream, Codec::default());
+
+        Ok(Transport { framed_stream })
+    }
+
+    /// In tolerant mode, frames that cannot be decoded are logged and yielded as
+    /// `Response::UnknownMsg` instead of ending the stream with an error.
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.framed_stream
+            .codec_mut()
+            .context_mut()
+            .set_tolerant(tolerant);
+    }
+}
+
+impl Transport {
+    /// # Errors
+    /// Returns a `std::io::Error` upon timeout or TCP connection failure.
+    pub async fn connect(
+        addr: SocketAddr,
+        timeout_duration: Duration,
+    ) -> Result<Transport, io::Error> {
+        let timeout = async {
+            Timer::after(timeout_duration).await;
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "connect request timeout",
+            ))
+        };
+        let connect = async { Transport::new(TcpStream::connect(addr).await?).await };
+        future::select(Box::pin(connect), Box::pin(timeout))
+            .await
+            .factor_first()
+            .0
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Stream for Transport<S> {
+    type Item = Result<Response, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.framed_stream.poll_next_unpin(cx)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Sink<Request> for Transport<S> {
+    type Error = io::Error;
+
+    fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
+        self.framed_stream.start_send_unpin(item)
+    }
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.framed_stream.poll_ready_unpin(cx)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.framed_stream.poll_flush_unpin(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.framed_stream.poll_close_unpin(cx)
+    }
+}
+
+impl<S> SpawnTask for Transport<S> {
+    /// Resolves to the output of the task, which keeps running when this is dropped.
+    type JoinHandle<T> = oneshot::Receiver<T>;
+    type Sleep = future::Map<Timer, fn(Instant)>;
+
+    fn spawn_task<F, T>(name: &str, future: F) -> Self::JoinHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        info!(%name, "spawning task");
+        let (output_tx, output_rx) = oneshot::channel();
+        smol::spawn(async move {
+            let _ = output_tx.send(future.await);
+        })
+        .detach();
+        output_rx
+    }
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        FutureExt::map(Timer::after(duration), drop as fn(Instant))
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use ib_tws_core::{AsyncClient, ConnectOptions, Error};
+
+use crate::Transport;
+
+/// How many redirects of the Gateway are followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Connect to TWS or the Gateway at `addr` and set up a client, following the redirects of
+/// the Gateway to another host or port.
+/// # Errors
+/// Returns an error if the connection, the handshake or initial start API request fails, or
+/// the Gateway keeps redirecting.
+pub async fn connect(addr: SocketAddr, options: ConnectOptions) -> Result<AsyncClient, Error> {
+    let mut addr = addr;
+    for _ in 0..=MAX_REDIRECTS {
+        let transport = Transport::connect(addr, options.connect_timeout).await?;
+        match AsyncClient::setup_with_options(transport, options.clone()).await {
+            Err(Error::Redirected(address)) => {
+                addr = resolve_redirect(&address, addr.port()).await?;
+                info!(%addr, "following redirect");
+            }
+            result => return result,
+        }
+    }
+    Err(Error::TransportIo(io::Error::other("too many redirects")))
+}
+
+/// Set up a client which connects to `addr` again whenever the connection is lost, see
+/// [`AsyncClient::setup_reconnecting`]. The first redirect target of the Gateway is kept
+/// for reconnecting.
+/// # Errors
+/// Returns an error if the first connection, the handshake or initial start API request
+/// fails, or the Gateway keeps redirecting.
+pub async fn connect_reconnecting(
+    addr: SocketAddr,
+    options: ConnectOptions,
+) -> Result<AsyncClient, Error> {
+    let mut addr = addr;
+    for _ in 0..=MAX_REDIRECTS {
+        let timeout = options.connect_timeout;
+        let connect = move || Transport::connect(addr, timeout);
+        match AsyncClient::setup_reconnecting_with_options(connect, options.clone()).await {
+            Err(Error::Redirected(address)) => {
+                addr = resolve_redirect(&address, addr.port()).await?;
+                info!(%addr, "following redirect");
+            }
+            result => return result,
+        }
+    }
+    Err(Error::TransportIo(io::Error::other("too many redirects")))
+}
+
+/// Resolve a `host[:port]` redirect address, the port defaults to the current one. IPv6
+/// addresses are bracketed when they come with a port.
+async fn resolve_redirect(address: &str, port: u16) -> Result<SocketAddr, io::Error> {
+    if let Ok(addr) = SocketAddr::from_str(address) {
+        return Ok(addr);
+    }
+    let ip = address
+        .strip_prefix('[')
+        .and_then(|ip| ip.strip_suffix(']'));
+    if let Ok(ip) = IpAddr::from_str(ip.unwrap_or(address)) {
+        return Ok(SocketAddr::new(ip, port));
+    }
+
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid redirect port"))?,
+        ),
+        None => (address, port),
+    };
+    tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "redirect host not found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn redirect_addresses() {
+        let ipv4 = IpAddr::from(Ipv4Addr::new(10, 0, 0, 2));
+        let ipv6 = IpAddr::from(Ipv6Addr::LOCALHOST);
+        let cases = [
+            ("10.0.0.2", SocketAddr::new(ipv4, 4001)),
+            ("10.0.0.2:4002", SocketAddr::new(ipv4, 4002)),
+            ("::1", SocketAddr::new(ipv6, 4001)),
+            ("[::1]", SocketAddr::new(ipv6, 4001)),
+            ("[::1]:4002", SocketAddr::new(ipv6, 4002)),
+        ];
+        for (address, expected) in cases {
+            assert_eq!(resolve_redirect(address, 4001).await.unwrap(), expected);
+        }
+
+        let addr = resolve_redirect("localhost", 4001).await.unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(addr.port(), 4001);
+        let addr = resolve_redirect("localhost:4002", 4001).await.unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(addr.port(), 4002);
+
+        assert!(resolve_redirect("localhost:port", 4001).await.is_err());
+    }
+}
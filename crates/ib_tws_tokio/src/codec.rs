@@ -1,40 +1,21 @@
 use std::io;
 
-use bytes::{BufMut, BytesMut};
-use ib_tws_core::message::{
-    context::Context,
-    message_codec::{decode_data, decode_head, FrameState, FRAME_HEAD_LEN},
-    Request, Response,
-};
+use bytes::BytesMut;
+use ib_tws_core::message::{context::Context, message_codec, Request, Response};
 use tokio_util::codec::{Decoder, Encoder};
 
-#[derive(Debug)]
-pub struct Codec {
-    state: FrameState,
-    ctx: Context,
-}
+/// The [`message_codec::Codec`] of the core crate, for `tokio_util::codec::Framed`.
+#[derive(Debug, Default)]
+pub struct Codec(message_codec::Codec);
 
 impl Codec {
     #[must_use]
     pub fn context(&self) -> &Context {
-        &self.ctx
+        self.0.context()
     }
 
     pub fn context_mut(&mut self) -> &mut Context {
-        &mut self.ctx
-    }
-
-    fn decode_message(&mut self, data: &mut BytesMut) -> Result<Option<Response>, io::Error> {
-        Ok(Some(self.ctx.decode_message(data)?))
-    }
-}
-
-impl Default for Codec {
-    fn default() -> Self {
-        Codec {
-            state: FrameState::Head,
-            ctx: Context::new(),
-        }
+        self.0.context_mut()
     }
 }
 
@@ -42,49 +23,48 @@ impl Decoder for Codec {
     type Item = Response;
     type Error = io::Error;
 
-    #[instrument(err)]
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if src.is_empty() {
-            return Ok(None);
-        }
-        {
-            let n = match self.state {
-                FrameState::Head => {
-                    if let Some(n) = decode_head(src)? {
-                        self.state = FrameState::Data(n);
-                        n
-                    } else {
-                        return Ok(None);
-                    }
-                }
-                FrameState::Data(n) => n,
-            };
+        self.0.decode(src)
+    }
+}
+
+impl Encoder<Request> for Codec {
+    type Error = io::Error;
+
+    fn encode(&mut self, request: Request, buf: &mut BytesMut) -> Result<(), Self::Error> {
+        self.0.encode(&request, buf)
+    }
+}
 
-            if let Some(mut data) = decode_data(n, src)? {
-                let response = self.decode_message(&mut data)?;
-                // trace!(?response, "decoded response");
+/// The [`message_codec::ServerCodec`] of the core crate, for servers and proxies speaking to
+/// API clients with `tokio_util::codec::Framed`.
+#[derive(Debug, Default)]
+pub struct ServerCodec(message_codec::ServerCodec);
 
-                self.state = FrameState::Head;
+impl ServerCodec {
+    #[must_use]
+    pub fn context(&self) -> &Context {
+        self.0.context()
+    }
 
-                src.reserve(FRAME_HEAD_LEN);
+    pub fn context_mut(&mut self) -> &mut Context {
+        self.0.context_mut()
+    }
+}
+
+impl Decoder for ServerCodec {
+    type Item = Request;
+    type Error = io::Error;
 
-                Ok(response)
-            } else {
-                Ok(None)
-            }
-        }
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.0.decode(src)
     }
 }
 
-impl Encoder<Request> for Codec {
+impl Encoder<Response> for ServerCodec {
     type Error = io::Error;
 
-    fn encode(&mut self, request: Request, buf: &mut BytesMut) -> Result<(), Self::Error> {
-        let mut req = self.ctx.encode_message(&request)?;
-        buf.reserve(req.len() + 4);
-        buf.put_u32(req.len().try_into().expect("tried to encode message longer than maximum"));
-        buf.put(&mut req);
-        trace!(?request, ?buf, "encoded request");
-        Ok(())
+    fn encode(&mut self, response: Response, buf: &mut BytesMut) -> Result<(), Self::Error> {
+        self.0.encode(&response, buf)
     }
 }
@@ -1,3 +1,6 @@
+//! ## Feature flags
+#![doc = document_features::document_features!()]
+
 #![warn(clippy::pedantic)]
 
 #[macro_use]
@@ -6,5 +9,19 @@ extern crate tracing;
 mod transport;
 pub use transport::Transport;
 
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
+
 mod codec;
-pub use codec::Codec;
+pub use codec::{Codec, ServerCodec};
+
+mod client;
+pub use client::{connect, connect_reconnecting};
+
+mod record;
+pub use record::{Direction, RecordedFrame, Recording, RecordingStream};
+
+mod replay;
+pub use replay::{ReplayMode, ReplayTransport};
@@ -0,0 +1,308 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    net::SocketAddr,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use ib_tws_core::message::constants::API_HEAD;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+use crate::{transport::connect_tcp, Transport};
+
+const MAGIC: &[u8] = b"IBTWSREC";
+const FORMAT_VERSION: u8 = 1;
+const FRAME_HEAD_LEN: usize = 4;
+
+/// Which way a recorded frame went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// A request written by the client.
+    ToTws,
+    /// A response read from TWS.
+    FromTws,
+}
+
+impl Direction {
+    fn to_byte(self) -> u8 {
+        match self {
+            Direction::ToTws => 0,
+            Direction::FromTws => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, io::Error> {
+        match byte {
+            0 => Ok(Direction::ToTws),
+            1 => Ok(Direction::FromTws),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown frame direction {byte}"),
+            )),
+        }
+    }
+}
+
+/// A frame of a recorded session, without its length prefix.
+#[derive(Debug, Clone)]
+pub struct RecordedFrame {
+    /// Time since the start of the recording.
+    pub elapsed: Duration,
+    pub direction: Direction,
+    pub data: Bytes,
+}
+
+/// A session read back from a recording file.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Wall clock time the recording was started at.
+    pub started_at: SystemTime,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Read a recording written by `RecordingStream`.
+    /// # Errors
+    /// Returns a `std::io::Error` if the file can't be read or isn't a recording.
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::parse(Bytes::from(bytes))
+    }
+
+    /// Parse a recording from its bytes.
+    /// # Errors
+    /// Returns a `std::io::Error` if the bytes aren't a recording or are truncated.
+    pub fn parse(mut bytes: Bytes) -> Result<Self, io::Error> {
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated recording");
+
+        if bytes.len() < MAGIC.len() + 1 + 8 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a recording",
+            ));
+        }
+        bytes.advance(MAGIC.len());
+        let version = bytes.get_u8();
+        if version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported recording version {version}"),
+            ));
+        }
+        let started_at = UNIX_EPOCH + Duration::from_micros(bytes.get_u64());
+
+        let mut frames = Vec::new();
+        while bytes.has_remaining() {
+            if bytes.remaining() < 8 + 1 + 4 {
+                return Err(truncated());
+            }
+            let elapsed = Duration::from_micros(bytes.get_u64());
+            let direction = Direction::from_byte(bytes.get_u8())?;
+            let len = bytes.get_u32() as usize;
+            if bytes.remaining() < len {
+                return Err(truncated());
+            }
+            frames.push(RecordedFrame {
+                elapsed,
+                direction,
+                data: bytes.split_to(len),
+            });
+        }
+
+        Ok(Recording { started_at, frames })
+    }
+}
+
+/// Splits the bytes going one way into frames, the length prefixes are dropped.
+#[derive(Debug, Default)]
+struct Framer {
+    skip: usize,
+    buf: BytesMut,
+}
+
+impl Framer {
+    fn push(&mut self, mut bytes: &[u8]) {
+        let skipped = self.skip.min(bytes.len());
+        self.skip -= skipped;
+        bytes = &bytes[skipped..];
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn next_frame(&mut self) -> Option<Bytes> {
+        if self.buf.len() < FRAME_HEAD_LEN {
+            return None;
+        }
+        let len = u32::from_be_bytes([self.buf[0], self.buf[1], self.buf[2], self.buf[3]]) as usize;
+        if self.buf.len() < FRAME_HEAD_LEN + len {
+            return None;
+        }
+        self.buf.advance(FRAME_HEAD_LEN);
+        Some(self.buf.split_to(len).freeze())
+    }
+}
+
+/// Wraps the stream under a `Transport` and writes every frame going either way to a
+/// recording, with the time since the start and its direction, to be replayed by
+/// `ReplayTransport`.
+///
+/// The stream has to be wrapped before the `API\0` prefix is written, i.e. before
+/// `Transport::new`. If the recording can't be written, the error is logged and the session
+/// goes on unrecorded.
+pub struct RecordingStream<S, W: Write = BufWriter<File>> {
+    stream: S,
+    writer: Option<W>,
+    started: Instant,
+    incoming: Framer,
+    outgoing: Framer,
+}
+
+impl<S> RecordingStream<S> {
+    /// Record to a new file at `path`, replacing any existing one.
+    /// # Errors
+    /// Returns a `std::io::Error` if the file can't be created.
+    pub fn create(stream: S, path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        Self::with_writer(stream, BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<S, W: Write> RecordingStream<S, W> {
+    /// Record to any writer, e.g. a `Vec<u8>`.
+    /// # Errors
+    /// Returns a `std::io::Error` if the header can't be written.
+    pub fn with_writer(stream: S, mut writer: W) -> Result<Self, io::Error> {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut header = BytesMut::with_capacity(MAGIC.len() + 1 + 8);
+        header.put_slice(MAGIC);
+        header.put_u8(FORMAT_VERSION);
+        header.put_u64(u64::try_from(started_at.as_micros()).unwrap_or(u64::MAX));
+        writer.write_all(&header)?;
+
+        Ok(RecordingStream {
+            stream,
+            writer: Some(writer),
+            started: Instant::now(),
+            incoming: Framer::default(),
+            outgoing: Framer {
+                skip: API_HEAD.len(),
+                buf: BytesMut::new(),
+            },
+        })
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Stop recording and return the stream and the writer, `None` if writing failed.
+    pub fn into_inner(mut self) -> (S, Option<W>) {
+        self.flush_recording();
+        (self.stream, self.writer)
+    }
+
+    fn record(&mut self, direction: Direction, bytes: &[u8]) {
+        let framer = match direction {
+            Direction::ToTws => &mut self.outgoing,
+            Direction::FromTws => &mut self.incoming,
+        };
+        framer.push(bytes);
+
+        let Some(writer) = self.writer.as_mut() else {
+            while framer.next_frame().is_some() {}
+            return;
+        };
+        let elapsed = u64::try_from(self.started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        let mut result = Ok(());
+        while let Some(frame) = framer.next_frame() {
+            let mut head = [0; 8 + 1 + 4];
+            head[..8].copy_from_slice(&elapsed.to_be_bytes());
+            head[8] = direction.to_byte();
+            head[9..]
+                .copy_from_slice(&u32::try_from(frame.len()).unwrap_or(u32::MAX).to_be_bytes());
+            result = writer
+                .write_all(&head)
+                .and_then(|()| writer.write_all(&frame));
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Err(error) = result {
+            error!(%error, "failed to write the recording, recording stopped");
+            self.writer = None;
+        }
+    }
+
+    /// Flush the recording along with the stream, rather than after every read and write.
+    fn flush_recording(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(error) = writer.flush() {
+                error!(%error, "failed to flush the recording, recording stopped");
+                self.writer = None;
+            }
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for RecordingStream<S, W> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            self.record(Direction::FromTws, &buf.filled()[filled..]);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin, W: Write + Unpin> AsyncWrite for RecordingStream<S, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.record(Direction::ToTws, &buf[..n]);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.flush_recording();
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.flush_recording();
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+impl Transport<RecordingStream<TcpStream>> {
+    /// Connect like `Transport::connect`, recording the session to a new file at `path`.
+    /// # Errors
+    /// Returns a `std::io::Error` upon timeout, TCP connection failure or if the file can't
+    /// be created.
+    pub async fn connect_recording(
+        addr: SocketAddr,
+        timeout_duration: Duration,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, io::Error> {
+        let stream = connect_tcp(addr, timeout_duration).await?;
+        let stream = RecordingStream::create(stream, path)?;
+        Self::start(stream, timeout_duration).await
+    }
+}
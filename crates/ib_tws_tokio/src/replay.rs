@@ -0,0 +1,196 @@
+use std::{
+    collections::VecDeque,
+    io,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures::{Future, Sink, Stream};
+use ib_tws_core::{
+    message::{context::Context as ApiContext, Request, Response},
+    SpawnTask,
+};
+use tokio::time::Sleep;
+
+use crate::record::{Direction, RecordedFrame, Recording};
+
+/// How fast `ReplayTransport` yields the recorded responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayMode {
+    /// Keep the recorded time between a request and the responses following it.
+    RealTime,
+    /// Yield every response as soon as the requests before it were sent.
+    #[default]
+    AsFastAsPossible,
+}
+
+/// A transport playing back a recorded session, to run `AsyncClient` against it
+/// deterministically.
+///
+/// Responses are decoded like on a live connection and yielded in the recorded order. A
+/// response recorded after a request is only yielded once the client has sent as many
+/// requests, so the request ids match as long as the client makes the same calls. Requests
+/// which differ from the recording are logged. The stream ends after the last response.
+pub struct ReplayTransport {
+    ctx: ApiContext,
+    mode: ReplayMode,
+    frames: Vec<RecordedFrame>,
+    position: usize,
+    end: usize,
+    requests: Vec<usize>,
+    sent_at: VecDeque<Instant>,
+    sent: usize,
+    mark: Option<(Duration, Instant)>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    waker: Option<Waker>,
+}
+
+impl ReplayTransport {
+    #[must_use]
+    pub fn new(recording: Recording, mode: ReplayMode) -> Self {
+        let frames = recording.frames;
+        let end = frames
+            .iter()
+            .rposition(|frame| frame.direction == Direction::FromTws)
+            .map_or(0, |index| index + 1);
+        let requests = frames
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| frame.direction == Direction::ToTws)
+            .map(|(index, _)| index)
+            .collect();
+
+        ReplayTransport {
+            ctx: ApiContext::new(),
+            mode,
+            frames,
+            position: 0,
+            end,
+            requests,
+            sent_at: VecDeque::new(),
+            sent: 0,
+            mark: None,
+            sleep: None,
+            waker: None,
+        }
+    }
+
+    /// Read a recording from a file, see `Recording::read_from`.
+    /// # Errors
+    /// Returns a `std::io::Error` if the file can't be read or isn't a recording.
+    pub async fn open(path: impl AsRef<Path>, mode: ReplayMode) -> Result<Self, io::Error> {
+        let recording = Recording::parse(Bytes::from(tokio::fs::read(path).await?))?;
+        Ok(Self::new(recording, mode))
+    }
+
+    /// In tolerant mode, frames that cannot be decoded are logged and yielded as
+    /// `Response::UnknownMsg` instead of an error, like `Transport::set_tolerant`.
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.ctx.set_tolerant(tolerant);
+    }
+}
+
+impl Stream for ReplayTransport {
+    type Item = Result<Response, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.position >= this.end {
+                return Poll::Ready(None);
+            }
+            let frame = &this.frames[this.position];
+            let elapsed = frame.elapsed;
+
+            if frame.direction == Direction::ToTws {
+                let Some(sent_at) = this.sent_at.pop_front() else {
+                    this.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                };
+                this.mark = Some((elapsed, sent_at));
+                this.position += 1;
+                continue;
+            }
+
+            let now = Instant::now();
+            let due = match (this.mode, this.mark) {
+                (ReplayMode::RealTime, Some((marked, at))) => at + elapsed.saturating_sub(marked),
+                _ => now,
+            };
+            if due > now {
+                let sleep = this
+                    .sleep
+                    .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(due.into())));
+                if sleep.deadline() != due.into() {
+                    sleep.as_mut().reset(due.into());
+                }
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+            }
+
+            let mut data = BytesMut::from(&this.frames[this.position].data[..]);
+            this.mark = Some((elapsed, due));
+            this.position += 1;
+            return Poll::Ready(Some(this.ctx.decode_message(&mut data)));
+        }
+    }
+}
+
+impl Sink<Request> for ReplayTransport {
+    type Error = io::Error;
+
+    fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
+        let this = &mut *self;
+        let encoded = this.ctx.encode_message(&item)?;
+        match this.requests.get(this.sent) {
+            Some(&index) if this.frames[index].data[..] == encoded[..] => {}
+            Some(_) => {
+                warn!(index = this.sent, request = ?item, "request differs from the recording");
+            }
+            None => warn!(request = ?item, "request beyond the end of the recording"),
+        }
+
+        this.sent += 1;
+        if this.sent <= this.requests.len() {
+            this.sent_at.push_back(Instant::now());
+        }
+        if let Some(waker) = this.waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl SpawnTask for ReplayTransport {
+    type JoinHandle<T> = tokio::task::JoinHandle<T>;
+    type Sleep = tokio::time::Sleep;
+
+    fn spawn_task<F, T>(name: &str, future: F) -> Self::JoinHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        info!(%name, "spawning task");
+        tokio::task::spawn(future)
+    }
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
+}
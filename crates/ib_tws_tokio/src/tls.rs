@@ -0,0 +1,86 @@
+use std::{io, sync::Arc};
+
+use tokio_rustls::rustls::{
+    self,
+    pki_types::{CertificateDer, PrivateKeyDer},
+    ClientConfig, RootCertStore,
+};
+
+/// Certificates of a TLS connection to an API port with SSL enabled.
+///
+/// TWS and the Gateway usually present a self-signed certificate, which has to be added as
+/// a root certificate.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    root_certificates: RootCertStore,
+    client_certificate: Option<(Vec<CertificateDer<'static>>, Arc<PrivateKeyDer<'static>>)>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig::new()
+    }
+}
+
+impl TlsConfig {
+    #[must_use]
+    pub fn new() -> Self {
+        TlsConfig {
+            root_certificates: RootCertStore::empty(),
+            client_certificate: None,
+        }
+    }
+
+    /// Trust a root certificate, DER encoded.
+    /// # Errors
+    /// Returns an error if the certificate can't be parsed.
+    pub fn add_root_certificate(
+        &mut self,
+        certificate: CertificateDer<'static>,
+    ) -> Result<(), io::Error> {
+        self.root_certificates
+            .add(certificate)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Trust every certificate of a PEM file.
+    /// # Errors
+    /// Returns an error if the file or one of its certificates can't be parsed.
+    pub fn add_root_certificates_pem(&mut self, mut pem: &[u8]) -> Result<(), io::Error> {
+        for certificate in rustls_pemfile::certs(&mut pem) {
+            self.add_root_certificate(certificate?)?;
+        }
+        Ok(())
+    }
+
+    /// Authenticate with a client certificate chain and its private key, both PEM encoded.
+    /// # Errors
+    /// Returns an error if either can't be parsed, or there is no private key.
+    pub fn set_client_certificate_pem(
+        &mut self,
+        mut certificate_chain: &[u8],
+        mut private_key: &[u8],
+    ) -> Result<(), io::Error> {
+        let chain = rustls_pemfile::certs(&mut certificate_chain).collect::<Result<_, _>>()?;
+        let key = rustls_pemfile::private_key(&mut private_key)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no private key found"))?;
+        self.client_certificate = Some((chain, Arc::new(key)));
+        Ok(())
+    }
+
+    pub(crate) fn client_config(&self) -> Result<Arc<ClientConfig>, io::Error> {
+        let builder =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(io::Error::other)?
+                .with_root_certificates(self.root_certificates.clone());
+
+        let config = match self.client_certificate {
+            Some((ref chain, ref key)) => builder
+                .with_client_auth_cert(chain.clone(), key.clone_key())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+}
@@ -6,20 +6,60 @@ use std::{
     time::Duration,
 };
 
-use futures::{Future, Sink, Stream, StreamExt, SinkExt};
+use futures::{Future, Sink, SinkExt, Stream, StreamExt};
 use ib_tws_core::{
-    SpawnTask,
     message::{Request, Response},
+    SpawnTask,
 };
-use tokio::{io::AsyncWriteExt, net::TcpStream};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+#[cfg(feature = "tls")]
+use tokio_rustls::{client::TlsStream, rustls::pki_types::ServerName, TlsConnector};
 use tokio_util::codec::Framed;
 
 use crate::Codec;
+#[cfg(feature = "tls")]
+use crate::TlsConfig;
+
+pub type FramedStream<S = TcpStream> = Framed<S, Codec>;
 
-pub type FramedStream = Framed<TcpStream, Codec>;
+/// The API protocol over a stream to TWS, a TCP socket unless set otherwise, e.g.
+/// `tokio::io::DuplexStream` or `tokio::net::UnixStream`.
+pub struct Transport<S = TcpStream> {
+    framed_stream: FramedStream<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Transport<S> {
+    /// Start the API protocol on a connected stream, by writing the `API\0` prefix.
+    /// # Errors
+    /// Returns a `std::io::Error` if the prefix can't be written.
+    pub async fn new(mut stream: S) -> Result<Transport<S>, io::Error> {
+        stream.write_all(b"API\0").await?;
 
-pub struct Transport {
-    framed_stream: FramedStream,
+        let framed_stream = Framed::new(stream, Codec::default());
+
+        Ok(Transport { framed_stream })
+    }
+
+    /// In tolerant mode, frames that cannot be decoded are logged and yielded as
+    /// `Response::UnknownMsg` instead of ending the stream with an error.
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.framed_stream
+            .codec_mut()
+            .context_mut()
+            .set_tolerant(tolerant);
+    }
+
+    pub(crate) async fn start(
+        stream: S,
+        timeout_duration: Duration,
+    ) -> Result<Transport<S>, io::Error> {
+        tokio::time::timeout(timeout_duration, Self::new(stream))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "write API head error"))?
+    }
 }
 
 impl Transport {
@@ -29,23 +69,45 @@ impl Transport {
         addr: SocketAddr,
         timeout_duration: Duration,
     ) -> Result<Transport, io::Error> {
-        let mut stream = tokio::time::timeout(timeout_duration, TcpStream::connect(&addr))
-            .await
-            .map_err(|_| io::Error::new(io::ErrorKind::Other, "connect request timeout"))??;
-
-        tokio::time::timeout(timeout_duration, stream.write_all(b"API\0"))
-            .await
-            .map_err(|_| io::Error::new(io::ErrorKind::Other, "write API head error"))??;
+        let stream = connect_tcp(addr, timeout_duration).await?;
+        Self::start(stream, timeout_duration).await
+    }
+}
 
-        let framed_stream = Framed::new(stream, Codec::default());
+#[cfg(feature = "tls")]
+impl Transport<TlsStream<TcpStream>> {
+    /// Connect to an API port with SSL enabled, like `EClientSocketSSL`. `server_name` is
+    /// checked against the certificate of the server.
+    /// # Errors
+    /// Returns a `std::io::Error` upon timeout, TCP connection or TLS handshake failure.
+    pub async fn connect_tls(
+        addr: SocketAddr,
+        server_name: &str,
+        tls_config: &TlsConfig,
+        timeout_duration: Duration,
+    ) -> Result<Self, io::Error> {
+        let server_name = ServerName::try_from(server_name.to_owned())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let connector = TlsConnector::from(tls_config.client_config()?);
 
-        Ok(Transport {
-            framed_stream,
-        })
+        let stream = connect_tcp(addr, timeout_duration).await?;
+        let stream = tokio::time::timeout(timeout_duration, connector.connect(server_name, stream))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "tls handshake timeout"))??;
+        Self::start(stream, timeout_duration).await
     }
 }
 
-impl Stream for Transport {
+pub(crate) async fn connect_tcp(
+    addr: SocketAddr,
+    timeout_duration: Duration,
+) -> Result<TcpStream, io::Error> {
+    tokio::time::timeout(timeout_duration, TcpStream::connect(&addr))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "connect request timeout"))?
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Stream for Transport<S> {
     type Item = Result<Response, io::Error>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
@@ -53,7 +115,7 @@ impl Stream for Transport {
     }
 }
 
-impl Sink<Request> for Transport {
+impl<S: AsyncRead + AsyncWrite + Unpin> Sink<Request> for Transport<S> {
     type Error = io::Error;
 
     fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
@@ -73,8 +135,9 @@ impl Sink<Request> for Transport {
     }
 }
 
-impl SpawnTask for Transport {
+impl<S> SpawnTask for Transport<S> {
     type JoinHandle<T> = tokio::task::JoinHandle<T>;
+    type Sleep = tokio::time::Sleep;
 
     fn spawn_task<F, T>(name: &str, future: F) -> Self::JoinHandle<T>
     where
@@ -84,4 +147,8 @@ impl SpawnTask for Transport {
         info!(%name, "spawning task");
         tokio::task::spawn(future)
     }
+
+    fn sleep(duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
 }
@@ -0,0 +1,58 @@
+//! Helpers shared by the tests against the mock server, each test uses a part of them.
+
+#![allow(dead_code)]
+
+use ib_tws_core::{
+    domain::{Contract, ContractDetails},
+    message::{constants::REQ_CONTRACT_DATA, request::ReqContractDetails},
+    AsyncClient, ConnectOptions,
+};
+use ib_tws_mock::{responses, MockOptions, MockServer};
+
+pub fn contract() -> Contract {
+    Contract::new_stock("AAPL", "SMART", "USD").unwrap()
+}
+
+/// Start a mock server which answers contract details requests.
+pub async fn start(options: MockOptions) -> MockServer {
+    let server = MockServer::start_with_options(options).await.unwrap();
+    server.reply(
+        REQ_CONTRACT_DATA,
+        vec![
+            responses::contract_data(0, ContractDetails::default()),
+            responses::contract_data_end(0),
+        ],
+    );
+    server
+}
+
+/// Start a mock server which answers contract details requests and connect a client to it.
+pub async fn connect() -> (MockServer, AsyncClient) {
+    connect_with_options(MockOptions::new()).await
+}
+
+pub async fn connect_with_options(options: MockOptions) -> (MockServer, AsyncClient) {
+    let server = start(options).await;
+    let client = ib_tws_tokio::connect(server.addr(), ConnectOptions::new(0))
+        .await
+        .unwrap();
+    (server, client)
+}
+
+/// Wait until every request sent before got through, with a contract details request.
+pub async fn round_trip(client: &AsyncClient) {
+    client
+        .request_contract_details(ReqContractDetails::new(contract()))
+        .await
+        .unwrap();
+}
+
+/// Whether the mock received a request with `opcode`, once every request sent before got
+/// through.
+pub async fn received(server: &MockServer, client: &AsyncClient, opcode: i32) -> bool {
+    round_trip(client).await;
+    server
+        .requests()
+        .iter()
+        .any(|request| request.opcode() == opcode)
+}
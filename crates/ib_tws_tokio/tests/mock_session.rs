@@ -0,0 +1,124 @@
+//! A whole session of the client against the mock server.
+
+use futures::StreamExt;
+use ib_tws_core::{
+    domain::{market_data::Bar, ContractDetails, Order, OrderState},
+    message::{
+        constants::{MAX_VERSION, PLACE_ORDER, REQ_CONTRACT_DATA, REQ_HISTORICAL_DATA, START_API},
+        request::{ReqContractDetails, ReqHistoricalData},
+        response::OpenOrderMsg,
+        Response,
+    },
+    ConnectOptions,
+};
+use ib_tws_mock::{responses, MockOptions, MockServer};
+use rust_decimal::Decimal;
+
+mod common;
+use common::{connect_with_options, contract};
+
+const CLIENT_ID: i32 = 7;
+
+#[tokio::test]
+async fn session() {
+    let server = MockServer::start_with_options(
+        MockOptions::new()
+            .accounts(["DU111", "DU222"])
+            .next_valid_id(100),
+    )
+    .await
+    .unwrap();
+    let details = ContractDetails {
+        contract: contract(),
+        long_name: "APPLE INC".to_string(),
+        ..ContractDetails::default()
+    };
+    server.reply(
+        REQ_CONTRACT_DATA,
+        vec![
+            responses::contract_data(0, details),
+            responses::contract_data_end(0),
+        ],
+    );
+    let bar = Bar {
+        time: "20240102".to_string(),
+        open: 187.15,
+        high: 188.44,
+        low: 183.89,
+        close: 185.64,
+        volume: 82_488_700.into(),
+        count: 1_009_074,
+        wap: Default::default(),
+    };
+    server.reply(
+        REQ_HISTORICAL_DATA,
+        vec![responses::historical_data(0, vec![bar])],
+    );
+
+    // handshake, StartApi, managed accounts and next valid id
+    let client = ib_tws_tokio::connect(server.addr(), ConnectOptions::new(CLIENT_ID))
+        .await
+        .unwrap();
+    assert_eq!(client.server_version(), MAX_VERSION);
+    let start_api = server.wait_for(START_API).await;
+    assert_eq!(start_api.field(2), Some(CLIENT_ID.to_string().as_str()));
+    assert_eq!(client.managed_accounts().await, ["DU111", "DU222"]);
+    assert_eq!(client.next_valid_order_id(), 100);
+
+    let details = client
+        .request_contract_details(ReqContractDetails::new(contract()))
+        .await
+        .unwrap();
+    assert_eq!(details.contract.symbol, "AAPL");
+    assert_eq!(details.long_name, "APPLE INC");
+
+    let request = ReqHistoricalData::new(
+        contract(),
+        String::new(),
+        "1 D".to_string(),
+        "1 day".to_string(),
+        "TRADES".to_string(),
+        1,
+        1,
+        false,
+        vec![],
+    );
+    let data = client.request_historical_data(request).await.unwrap();
+    assert_eq!(data.bars.len(), 1);
+    assert_eq!(data.bars[0].time, "20240102");
+    assert!((data.bars[0].close - 185.64).abs() < f64::EPSILON);
+    assert_eq!(data.bars[0].count, 1_009_074);
+}
+
+#[tokio::test]
+async fn scripted_order_replies_take_the_order_id() {
+    let (server, client) = connect_with_options(MockOptions::new().next_valid_id(100)).await;
+    server.reply(
+        PLACE_ORDER,
+        vec![
+            Response::OpenOrderMsg(OpenOrderMsg {
+                order_id: 0,
+                contract: contract(),
+                order: Order::default(),
+                order_state: OrderState::default(),
+            }),
+            responses::order_status(0, "Submitted", Decimal::ZERO, Decimal::ONE, 0.0),
+        ],
+    );
+
+    let mut handle = client
+        .place_order(contract(), Order::default())
+        .await
+        .unwrap();
+    match handle.next().await {
+        Some(Ok(Response::OpenOrderMsg(msg))) => {
+            assert_eq!(msg.order_id, 100);
+            assert_eq!(msg.order.order_id, 100);
+        }
+        response => panic!("unexpected response {response:?}"),
+    }
+    match handle.next().await {
+        Some(Ok(Response::OrderStatusMsg(msg))) => assert_eq!(msg.id, 100),
+        response => panic!("unexpected response {response:?}"),
+    }
+}
@@ -0,0 +1,83 @@
+//! A reconnecting client sends its live subscriptions again once the connection is back.
+
+use std::{collections::HashSet, time::Duration};
+
+use futures::StreamExt;
+use ib_tws_core::{
+    domain::{market_data::TickType, Contract},
+    message::{
+        constants::{REQ_MKT_DATA, START_API},
+        request::ReqMktData,
+        Request, Response,
+    },
+    ConnectOptions, ConnectionEvent,
+};
+use ib_tws_mock::{responses, MockOptions};
+use rust_decimal::Decimal;
+
+mod common;
+use common::{round_trip, start};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn market_data(symbol: &str) -> ReqMktData {
+    let contract = Contract::new_stock(symbol, "SMART", "USD").unwrap();
+    ReqMktData::new(contract, HashSet::new(), false, false, vec![])
+}
+
+#[tokio::test]
+async fn live_subscriptions_are_sent_again() {
+    let server = start(MockOptions::new()).await;
+    let client = ib_tws_tokio::connect_reconnecting(server.addr(), ConnectOptions::new(0))
+        .await
+        .unwrap();
+    let mut events = client.connection_events();
+
+    let mut live = client
+        .request_market_data(market_data("AAPL"))
+        .await
+        .unwrap();
+    let cancelled = client
+        .request_market_data(market_data("MSFT"))
+        .await
+        .unwrap();
+    drop(cancelled);
+    round_trip(&client).await;
+
+    server.disconnect_all();
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(events.recv().await, Ok(ConnectionEvent::Reconnected)) {}
+    })
+    .await
+    .unwrap();
+    round_trip(&client).await;
+
+    // the requests since the client started the API again
+    let requests = server.requests();
+    let reconnected = requests
+        .iter()
+        .rposition(|request| request.opcode() == START_API)
+        .unwrap();
+    let replayed: Vec<_> = requests[reconnected..]
+        .iter()
+        .filter(|request| request.opcode() == REQ_MKT_DATA)
+        .collect();
+    assert_eq!(replayed.len(), 1, "{replayed:?}");
+    assert!(
+        matches!(replayed[0].request(), Request::ReqMktData(msg) if msg.contract.symbol == "AAPL"),
+        "{replayed:?}"
+    );
+
+    // the stream carries on with the original request id
+    server.send(&responses::tick_price(
+        replayed[0].request_id().unwrap(),
+        TickType::LAST,
+        185.5,
+        Decimal::ONE,
+    ));
+    let tick = tokio::time::timeout(TIMEOUT, live.next()).await.unwrap();
+    assert!(
+        matches!(tick, Some(Ok(Response::TickPriceMsg(_)))),
+        "{tick:?}"
+    );
+}
@@ -0,0 +1,158 @@
+//! A session recorded against the mock server and played back without it.
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use ib_tws_core::{
+    domain::ContractDetails,
+    message::{
+        constants::{API_HEAD, MAX_VERSION, REQ_CONTRACT_DATA},
+        context::Context,
+        request::{ReqContractDetails, ReqCurrentTime},
+        response::CurrentTimeMsg,
+        Request, Response,
+    },
+    AsyncClient,
+};
+use ib_tws_mock::{responses, MockOptions};
+use ib_tws_tokio::{Direction, Recording, RecordingStream, ReplayMode, ReplayTransport, Transport};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+mod common;
+use common::{contract, start};
+
+/// How much later than recorded the contract details are played back.
+const DELAY: Duration = Duration::from_millis(300);
+
+/// A writer the test keeps a handle to, while the client owns the recording stream.
+#[derive(Clone, Default)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn with_length(frame: &[u8]) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    buf.put_u32(u32::try_from(frame.len()).unwrap());
+    buf.put_slice(frame);
+    buf.to_vec()
+}
+
+#[tokio::test]
+async fn recording_round_trip() {
+    let (client, mut server) = tokio::io::duplex(1024);
+    let mut stream = RecordingStream::with_writer(client, Vec::new()).unwrap();
+
+    let mut ctx = Context::new();
+    ctx.set_server_version(MAX_VERSION);
+    let request = ctx
+        .encode_message(&Request::ReqCurrentTime(ReqCurrentTime {}))
+        .unwrap();
+    let response = ctx
+        .encode_response(&Response::CurrentTimeMsg(CurrentTimeMsg {
+            time: 1_700_000_000,
+        }))
+        .unwrap();
+
+    // the prefix isn't a frame, the request is written in two parts
+    stream.write_all(API_HEAD).await.unwrap();
+    let framed = with_length(&request);
+    stream.write_all(&framed[..3]).await.unwrap();
+    stream.write_all(&framed[3..]).await.unwrap();
+    stream.flush().await.unwrap();
+    server.write_all(&with_length(&response)).await.unwrap();
+    let mut read = vec![0; 4 + response.len()];
+    stream.read_exact(&mut read).await.unwrap();
+
+    let (_, writer) = stream.into_inner();
+    let recording = Recording::parse(Bytes::from(writer.unwrap())).unwrap();
+    assert_eq!(recording.frames.len(), 2);
+    assert_eq!(recording.frames[0].direction, Direction::ToTws);
+    assert_eq!(recording.frames[0].data[..], request[..]);
+    assert_eq!(recording.frames[1].direction, Direction::FromTws);
+    assert_eq!(recording.frames[1].data[..], response[..]);
+    assert!(recording.frames[0].elapsed <= recording.frames[1].elapsed);
+
+    assert!(Recording::parse(Bytes::from_static(b"not a recording")).is_err());
+}
+
+/// Record a session asking for contract details, with the replies played back `DELAY`
+/// later than they came.
+async fn record() -> Recording {
+    let server = start(MockOptions::new()).await;
+    let details = ContractDetails {
+        contract: contract(),
+        ..ContractDetails::default()
+    };
+    server.reply(
+        REQ_CONTRACT_DATA,
+        vec![
+            responses::contract_data(0, details),
+            responses::contract_data_end(0),
+        ],
+    );
+    let writer = SharedWriter::default();
+    let stream = TcpStream::connect(server.addr()).await.unwrap();
+    let stream = RecordingStream::with_writer(stream, writer.clone()).unwrap();
+    let client = AsyncClient::setup(Transport::new(stream).await.unwrap(), 0)
+        .await
+        .unwrap();
+    client
+        .request_contract_details(ReqContractDetails::new(contract()))
+        .await
+        .unwrap();
+    drop(client);
+
+    let bytes = writer.0.lock().unwrap().clone();
+    let mut recording = Recording::parse(Bytes::from(bytes)).unwrap();
+    let request = recording
+        .frames
+        .iter()
+        .rposition(|frame| frame.direction == Direction::ToTws)
+        .unwrap();
+    for frame in &mut recording.frames[request + 1..] {
+        frame.elapsed += DELAY;
+    }
+    recording
+}
+
+/// Replay a recording, returns how long the contract details took.
+async fn replay(recording: Recording, mode: ReplayMode) -> Duration {
+    let client = AsyncClient::setup(ReplayTransport::new(recording, mode), 0)
+        .await
+        .unwrap();
+    let started = Instant::now();
+    let details = client
+        .request_contract_details(ReqContractDetails::new(contract()))
+        .await
+        .unwrap();
+    assert_eq!(details.contract.symbol, "AAPL");
+    started.elapsed()
+}
+
+#[tokio::test]
+async fn replay_as_fast_as_possible() {
+    let recording = record().await;
+    assert!(replay(recording, ReplayMode::AsFastAsPossible).await < DELAY);
+}
+
+#[tokio::test]
+async fn replay_in_real_time() {
+    let recording = record().await;
+    assert!(replay(recording, ReplayMode::RealTime).await >= DELAY);
+}
@@ -0,0 +1,101 @@
+//! Dropping a subscription cancels it, unless TWS already ended it.
+
+use std::collections::HashSet;
+
+use futures::StreamExt;
+use ib_tws_core::{
+    message::{
+        constants::{
+            CANCEL_ACCOUNT_SUMMARY, CANCEL_HEAD_TIMESTAMP, CANCEL_MKT_DATA, REQ_ACCOUNT_SUMMARY,
+            REQ_HEAD_TIMESTAMP, REQ_MKT_DATA,
+        },
+        request::{ReqAccountSummary, ReqHeadTimestamp, ReqMktData},
+        response::{AccountSummaryEndMsg, HeadTimestampMsg},
+        Response,
+    },
+    Error,
+};
+use ib_tws_mock::responses;
+
+mod common;
+use common::{connect, contract, received};
+
+#[tokio::test]
+async fn live_subscription_is_cancelled() {
+    let (server, client) = connect().await;
+    server.reply(REQ_MKT_DATA, vec![]);
+
+    let request = ReqMktData::new(contract(), HashSet::new(), false, false, vec![]);
+    drop(client.request_market_data(request).await.unwrap());
+
+    assert!(received(&server, &client, CANCEL_MKT_DATA).await);
+}
+
+#[tokio::test]
+async fn rejected_subscription_is_not_cancelled() {
+    let (server, client) = connect().await;
+    server.reply(
+        REQ_MKT_DATA,
+        vec![responses::error(
+            0,
+            200,
+            "No security definition has been found",
+        )],
+    );
+
+    let request = ReqMktData::new(contract(), HashSet::new(), false, false, vec![]);
+    let mut subscription = client.request_market_data(request).await.unwrap();
+    let result = subscription.next().await;
+    assert!(
+        matches!(result, Some(Err(Error::ApiError(_)))),
+        "{result:?}"
+    );
+    drop(subscription);
+
+    assert!(!received(&server, &client, CANCEL_MKT_DATA).await);
+}
+
+#[tokio::test]
+async fn answered_head_timestamp_is_not_cancelled() {
+    let (server, client) = connect().await;
+    server.reply(
+        REQ_HEAD_TIMESTAMP,
+        vec![Response::HeadTimestampMsg(HeadTimestampMsg {
+            req_id: 0,
+            head_time_stamp: "19801212 14:30:00".to_string(),
+        })],
+    );
+
+    let request = ReqHeadTimestamp {
+        req_id: 0,
+        contract: contract(),
+        what_to_show: "TRADES".to_string(),
+        use_rth: 1,
+        format_date: 1,
+    };
+    let mut subscription = client.request_head_timestamp(request).await.unwrap();
+    let head_timestamp = subscription.next().await.unwrap().unwrap();
+    assert_eq!(head_timestamp.head_time_stamp, "19801212 14:30:00");
+    drop(subscription);
+
+    assert!(!received(&server, &client, CANCEL_HEAD_TIMESTAMP).await);
+}
+
+#[tokio::test]
+async fn account_summary_is_cancelled_after_the_snapshot() {
+    let (server, client) = connect().await;
+    server.reply(
+        REQ_ACCOUNT_SUMMARY,
+        vec![Response::AccountSummaryEndMsg(AccountSummaryEndMsg {
+            req_id: 0,
+        })],
+    );
+
+    let request = ReqAccountSummary::new("All".to_string(), "NetLiquidation".to_string());
+    let summary = client.request_account_summary(request).await.unwrap();
+    let summary: Vec<_> = summary.collect().await;
+    assert!(summary.is_empty(), "{summary:?}");
+
+    // TWS keeps updating the summary after its end
+    assert!(received(&server, &client, CANCEL_ACCOUNT_SUMMARY).await);
+}
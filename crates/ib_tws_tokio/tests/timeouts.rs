@@ -0,0 +1,84 @@
+//! The one-shot requests give up when TWS never answers, and the order snapshots fail when
+//! TWS never sends their end message.
+
+use std::time::Duration;
+
+use ib_tws_core::{
+    domain::WshEventData,
+    message::constants::{
+        CANCEL_WSH_EVENT_DATA, CANCEL_WSH_META_DATA, REQ_COMPLETED_ORDERS, REQ_OPEN_ORDERS,
+        REQ_WSH_EVENT_DATA, REQ_WSH_META_DATA,
+    },
+    Error,
+};
+
+mod common;
+use common::connect;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_millis(100));
+
+#[tokio::test]
+async fn order_snapshots_time_out() {
+    let (_server, client) = connect().await;
+
+    let result = client.request_open_orders_with_timeout(TIMEOUT).await;
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+
+    let result = client.request_all_open_orders_with_timeout(TIMEOUT).await;
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+
+    let result = client
+        .request_completed_orders_with_timeout(false, TIMEOUT)
+        .await;
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+}
+
+#[tokio::test]
+async fn open_orders_fail_when_disconnected() {
+    let (server, client) = connect().await;
+
+    let (result, ()) = tokio::join!(client.request_open_orders_with_timeout(None), async {
+        server.wait_for(REQ_OPEN_ORDERS).await;
+        server.disconnect_all();
+    });
+    assert!(
+        matches!(result, Err(Error::ResponseChannelClosed)),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn completed_orders_fail_when_disconnected() {
+    let (server, client) = connect().await;
+
+    let (result, ()) = tokio::join!(
+        client.request_completed_orders_with_timeout(false, None),
+        async {
+            server.wait_for(REQ_COMPLETED_ORDERS).await;
+            server.disconnect_all();
+        }
+    );
+    assert!(
+        matches!(result, Err(Error::ResponseChannelClosed)),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn wsh_requests_time_out_and_cancel() {
+    let (server, client) = connect().await;
+
+    let result = client.request_wsh_meta_data_with_timeout(TIMEOUT).await;
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+    let request = server.wait_for(REQ_WSH_META_DATA).await;
+    let cancel = server.wait_for(CANCEL_WSH_META_DATA).await;
+    assert_eq!(cancel.request_id(), request.request_id());
+
+    let result = client
+        .request_wsh_event_data_with_timeout(WshEventData::by_con_id(8314), TIMEOUT)
+        .await;
+    assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+    let request = server.wait_for(REQ_WSH_EVENT_DATA).await;
+    let cancel = server.wait_for(CANCEL_WSH_EVENT_DATA).await;
+    assert_eq!(cancel.request_id(), request.request_id());
+}
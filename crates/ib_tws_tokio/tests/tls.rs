@@ -0,0 +1,167 @@
+//! The client against the mock server behind a TLS terminator, like an API port with SSL
+//! enabled.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use ib_tws_core::{
+    message::constants::{MAX_VERSION, START_API},
+    AsyncClient,
+};
+use ib_tws_mock::{MockOptions, MockServer};
+use ib_tws_tokio::{TlsConfig, Transport};
+use rcgen::{generate_simple_self_signed, CertifiedKey, KeyPair};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{
+    rustls::{
+        self,
+        pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+        server::WebPkiClientVerifier,
+        RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const SERVER_NAME: &str = "localhost";
+
+fn certificate(name: &str) -> CertifiedKey<KeyPair> {
+    generate_simple_self_signed(vec![name.to_string()]).unwrap()
+}
+
+fn private_key(certified: &CertifiedKey<KeyPair>) -> PrivateKeyDer<'static> {
+    PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der()).into()
+}
+
+/// Accept TLS connections and forward the decrypted stream to the mock server. Clients
+/// have to present a certificate signed by `client_root`, if set.
+async fn terminate_tls(
+    server: &MockServer,
+    certified: &CertifiedKey<KeyPair>,
+    client_root: Option<&CertifiedKey<KeyPair>>,
+) -> SocketAddr {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = match client_root {
+        Some(client_root) => {
+            let mut roots = RootCertStore::empty();
+            roots.add(client_root.cert.der().clone()).unwrap();
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let config = builder
+        .with_single_cert(vec![certified.cert.der().clone()], private_key(certified))
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let upstream = server.addr();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut upstream = TcpStream::connect(upstream).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            });
+        }
+    });
+    addr
+}
+
+fn trusting(certified: &CertifiedKey<KeyPair>) -> TlsConfig {
+    let mut tls_config = TlsConfig::new();
+    tls_config
+        .add_root_certificates_pem(certified.cert.pem().as_bytes())
+        .unwrap();
+    tls_config
+}
+
+async fn setup(
+    addr: SocketAddr,
+    tls_config: &TlsConfig,
+) -> Result<AsyncClient, Box<dyn std::error::Error>> {
+    let transport = Transport::connect_tls(addr, SERVER_NAME, tls_config, TIMEOUT).await?;
+    let client = tokio::time::timeout(TIMEOUT, AsyncClient::setup(transport, 0)).await??;
+    Ok(client)
+}
+
+#[tokio::test]
+async fn handshake_over_tls() {
+    let server = MockServer::start_with_options(MockOptions::new().accounts(["DU111"]))
+        .await
+        .unwrap();
+    let certified = certificate(SERVER_NAME);
+    let addr = terminate_tls(&server, &certified, None).await;
+
+    let client = setup(addr, &trusting(&certified)).await.unwrap();
+    assert_eq!(client.server_version(), MAX_VERSION);
+    server.wait_for(START_API).await;
+    assert_eq!(client.managed_accounts().await, vec!["DU111".to_string()]);
+}
+
+#[tokio::test]
+async fn untrusted_server_certificate() {
+    let server = MockServer::start().await.unwrap();
+    let certified = certificate(SERVER_NAME);
+    let addr = terminate_tls(&server, &certified, None).await;
+
+    let result = Transport::connect_tls(addr, SERVER_NAME, &TlsConfig::new(), TIMEOUT).await;
+    assert!(result.is_err());
+
+    let other = certificate(SERVER_NAME);
+    let result = Transport::connect_tls(addr, SERVER_NAME, &trusting(&other), TIMEOUT).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn server_name_mismatch() {
+    let server = MockServer::start().await.unwrap();
+    let certified = certificate("tws.example.com");
+    let addr = terminate_tls(&server, &certified, None).await;
+
+    let result = Transport::connect_tls(addr, SERVER_NAME, &trusting(&certified), TIMEOUT).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn client_certificate() {
+    let server = MockServer::start().await.unwrap();
+    let certified = certificate(SERVER_NAME);
+    let client_certified = certificate("client");
+    let addr = terminate_tls(&server, &certified, Some(&client_certified)).await;
+
+    let mut tls_config = trusting(&certified);
+    tls_config
+        .set_client_certificate_pem(
+            client_certified.cert.pem().as_bytes(),
+            client_certified.signing_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+    let client = setup(addr, &tls_config).await.unwrap();
+    assert_eq!(client.server_version(), MAX_VERSION);
+
+    // with TLS 1.3 the server only rejects the missing certificate after the handshake of
+    // the client, so the API handshake fails instead
+    let result = setup(addr, &trusting(&certified)).await;
+    assert!(result.is_err());
+}
+
+#[test]
+fn invalid_pem() {
+    let mut tls_config = TlsConfig::new();
+    assert!(tls_config.set_client_certificate_pem(b"", b"").is_err());
+
+    let certified = certificate("client");
+    let result =
+        tls_config.set_client_certificate_pem(certified.cert.pem().as_bytes(), b"no key here");
+    assert!(result.is_err());
+}
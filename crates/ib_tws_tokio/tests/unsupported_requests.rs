@@ -0,0 +1,63 @@
+//! Requests the server version doesn't support fail on their own, the connection stays up.
+
+use ib_tws_core::{
+    message::{constants::REQ_HISTORICAL_DATA, request::ReqHistoricalData},
+    AsyncClient, Error,
+};
+use ib_tws_mock::{MockOptions, MockServer};
+
+mod common;
+use common::{connect_with_options, contract, round_trip};
+
+/// Below `MIN_SERVER_VER_REQ_COMPLETED_ORDERS` and `MIN_SERVER_VER_HISTORICAL_SCHEDULE`.
+const SERVER_VERSION: i32 = 149;
+
+async fn connect() -> (MockServer, AsyncClient) {
+    let (server, client) =
+        connect_with_options(MockOptions::new().server_version(SERVER_VERSION)).await;
+    assert_eq!(client.server_version(), SERVER_VERSION);
+    (server, client)
+}
+
+async fn assert_connected(client: &AsyncClient) {
+    round_trip(client).await;
+    assert!(client.connection_state().connected);
+}
+
+#[tokio::test]
+async fn sent_request_fails_immediately() {
+    let (_server, client) = connect().await;
+
+    let result = client.request_completed_orders(false).await;
+    assert!(matches!(result, Err(Error::Encode(_))), "{result:?}");
+
+    assert_connected(&client).await;
+}
+
+#[tokio::test]
+async fn routed_request_fails_its_consumer() {
+    let (server, client) = connect().await;
+
+    let request = ReqHistoricalData::new(
+        contract(),
+        String::new(),
+        "1 D".to_string(),
+        "1 day".to_string(),
+        "SCHEDULE".to_string(),
+        1,
+        1,
+        false,
+        vec![],
+    );
+    let result = client.request_historical_data(request).await;
+    assert!(matches!(result, Err(Error::Encode(_))), "{result:?}");
+
+    assert_connected(&client).await;
+    let requests = server.requests();
+    assert!(
+        requests
+            .iter()
+            .all(|request| request.opcode() != REQ_HISTORICAL_DATA),
+        "{requests:?}"
+    );
+}